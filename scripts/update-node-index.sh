@@ -0,0 +1,46 @@
+#!/bin/bash
+# update-node-index.sh - Regenerate the bundled release index (data/node-index.json)
+#
+# Usage:
+#   ./scripts/update-node-index.sh                 # From nodejs.org
+#   ./scripts/update-node-index.sh index.json      # From a downloaded dist/index.json
+#
+# Keeps every release from 4.0.0 on (0.x predates the release lines anvs
+# resolves), with only the fields anvs reads: version, date, LTS codename and
+# whether it's a security release.
+# Release line EOL dates aren't in dist/index.json, so the existing "lines"
+# are kept; add new lines by hand.
+
+set -euo pipefail
+
+SOURCE=${1:-https://nodejs.org/dist/index.json}
+TARGET=data/node-index.json
+
+if [[ "$SOURCE" == http* ]]; then
+    DIST=$(curl -fsSL "$SOURCE")
+else
+    DIST=$(cat "$SOURCE")
+fi
+
+RELEASES=$(echo "$DIST" | jq -c '
+    .[]
+    | select((.version | ltrimstr("v") | split(".")[0] | tonumber) >= 4)
+    | {version: (.version | ltrimstr("v")), date: .date, lts: (if .lts then .lts else null end)}
+      + (if .security then {security: true} else {} end)')
+UPDATED=$(echo "$DIST" | jq -r 'map(.date) | max')
+LINES=$(jq -c '.lines[]' "$TARGET")
+
+{
+    echo "{"
+    echo "  \"updated\": \"$UPDATED\","
+    echo "  \"releases\": ["
+    echo "$RELEASES" | sed 's/^/    /; s/,"/, "/g; s/":/": /g; $!s/$/,/'
+    echo "  ],"
+    echo "  \"lines\": ["
+    echo "$LINES" | sed 's/^/    /; s/,"/, "/g; s/":/": /g; $!s/$/,/'
+    echo "  ]"
+    echo "}"
+} > "$TARGET.tmp"
+mv "$TARGET.tmp" "$TARGET"
+
+echo "Wrote $(echo "$RELEASES" | wc -l | tr -d ' ') releases to $TARGET (updated $UPDATED)"
@@ -21,61 +21,26 @@ __anvs_debug() {
     fi
 }
 
-# Find version file by walking up directory tree
-# Usage: __anvs_find_file <start_path>
-# Returns: Full path to version file, or empty string if not found
-__anvs_find_file() {
-    local current_dir="${1:-$PWD}"
-    local search_files="${ANVS_VERSION_FILES:-.nvmrc .node-version}"
-
-    __anvs_debug "Searching for version files: $search_files"
-
-    while [[ "$current_dir" != "/" ]]; do
-        # Word splitting for zsh compatibility
-        # In zsh, we need to enable word splitting with setopt
-        # In bash, it's enabled by default
-        if [[ -n "${ZSH_VERSION:-}" ]]; then
-            setopt local_options sh_word_split
-        fi
-        # shellcheck disable=SC2086
-        for filename in $search_files; do
-            local filepath="$current_dir/$filename"
-            if [[ -f "$filepath" ]]; then
-                __anvs_debug "Found version file: $filepath"
-                echo "$filepath"
-                return 0
-            fi
-        done
-        current_dir="$(dirname "$current_dir")"
-    done
-
-    __anvs_debug "No version file found"
-    return 1
+# Check whether activation is needed for a directory
+# Usage: __anvs_hook_check <dir>
+# Returns: "none" (no version file), "unchanged" (already active), or a new
+# active key. The version file search runs in the binary (VersionFile::find),
+# so the hook and `anvs activate` always agree about which file wins.
+__anvs_hook_check() {
+    local dir="${1:-$PWD}"
+    anvs hook-check "$dir" "${ANVS_ACTIVE_KEY:-}" 2>/dev/null
 }
 
-# Activate version for a given path
-# Usage: __anvs_activate <version_file_path>
+# Activate version for a given directory
+# Usage: __anvs_activate <dir> <active_key>
 __anvs_activate() {
-    local version_file="$1"
+    local dir="$1"
+    local active_key="$2"
 
-    # Check if already activated for this file+version (idempotency)
-    # This prevents re-activation when:
-    # - User runs 'cd .' in same directory
-    # - User cd's into subdirectory of same project
-    # - Shell re-runs hook on prompt refresh
-    # We check both the file path AND its content hash to detect version changes
-    local version_hash
-    version_hash=$(cksum "$version_file" 2>/dev/null | cut -d' ' -f1)
-    local active_key="${version_file}:${version_hash}"
-
-    if [[ "${ANVS_ACTIVE_KEY:-}" == "$active_key" ]]; then
-        __anvs_debug "Already activated for $version_file (hash: $version_hash), skipping"
-        return 0
-    fi
-
-    __anvs_debug "Activating version from $version_file"
+    __anvs_debug "Activating version for $dir (key: $active_key)"
 
-    # Call anvs binary with FD:3 protocol
+    # Call anvs binary with FD:3 protocol (pinned, so a PowerShell or Nushell
+    # parent's environment can't switch it)
     # FD:3 Protocol redirection explanation:
     # We want to capture only FD:3 output in $commands, while keeping stdout/stderr visible.
     #
@@ -86,7 +51,7 @@ __anvs_activate() {
     #
     # Net effect: Only FD:3 is captured; stdout and stderr are swapped but both visible.
     local commands
-    commands=$(anvs activate "$(dirname "$version_file")" 3>&1 1>&2 2>&3) || {
+    commands=$(anvs activate "$dir" --protocol fd3 3>&1 1>&2 2>&3) || {
         # Activation failed, but don't break the shell
         __anvs_debug "Activation failed (exit code $?)"
         return 1
@@ -105,33 +70,52 @@ __anvs_activate() {
 __anvs_chpwd() {
     __anvs_debug "Directory changed to: $PWD"
 
-    local version_file
-    if version_file=$(__anvs_find_file "$PWD"); then
-        __anvs_activate "$version_file"
-    else
-        # No version file found - switch to default version if configured
-        if [[ -n "${ANVS_ACTIVE_KEY:-}" ]]; then
-            __anvs_debug "Left project directory, switching to default version"
-
-            # Call anvs activate with --use-default flag
-            # This will switch to the version manager's default version (e.g., nvm default)
-            local commands
-            commands=$(anvs activate "$PWD" --use-default 3>&1 1>&2 2>&3) || {
-                __anvs_debug "Default version activation failed (exit code $?)"
-                # Clear active key even if activation fails
-                unset ANVS_ACTIVE_KEY
-                return 0
-            }
+    # Check if already activated for this file+version (idempotency)
+    # This prevents re-activation when:
+    # - User runs 'cd .' in same directory
+    # - User cd's into subdirectory of same project
+    # - Shell re-runs hook on prompt refresh
+    local result
+    result=$(__anvs_hook_check "$PWD") || {
+        __anvs_debug "Hook check failed (exit code $?)"
+        return 0
+    }
 
-            if [[ -n "$commands" ]]; then
-                __anvs_debug "Evaluating default activation commands: $commands"
-                eval "$commands"
+    case "$result" in
+        unchanged)
+            __anvs_debug "Already activated for ${ANVS_ACTIVE_KEY:-}, skipping"
+            ;;
+        none)
+            # No version file found - switch to default version if configured
+            if [[ -n "${ANVS_ACTIVE_KEY:-}" ]]; then
+                __anvs_debug "Left project directory, switching to default version"
+
+                # Call anvs activate with --use-default flag
+                # This will switch to the version manager's default version (e.g., nvm default)
+                # or restore the pre-anvs environment, depending on leave_action
+                local commands
+                commands=$(anvs activate "$PWD" --use-default --protocol fd3 3>&1 1>&2 2>&3) || {
+                    __anvs_debug "Default version activation failed (exit code $?)"
+                    # Clear active key even if activation fails
+                    unset ANVS_ACTIVE_KEY
+                    return 0
+                }
+
+                if [[ -n "$commands" ]]; then
+                    __anvs_debug "Evaluating default activation commands: $commands"
+                    eval "$commands"
+                fi
+
+                # Clear active key to allow re-activation if entering another project
+                unset ANVS_ACTIVE_KEY
             fi
-
-            # Clear active key to allow re-activation if entering another project
-            unset ANVS_ACTIVE_KEY
-        fi
-    fi
+            ;;
+        *)
+            # ANVS_ACTIVE_KEY still names the project being left, so its
+            # on_leave hooks run before the new project's
+            __anvs_activate "$PWD" "$result"
+            ;;
+    esac
 }
 
 # Bash-specific integration
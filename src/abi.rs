@@ -0,0 +1,331 @@
+//! Native module ABI checks
+//!
+//! Native addons in `node_modules` are compiled for one `NODE_MODULE_VERSION`
+//! (`process.versions.modules`), which changes with every Node.js major.
+//! After a switch, anvs compares the ABI `node_modules` was built for with
+//! the activated version's.
+//!
+//! The ABI `node_modules` was built for comes from a `node_modules/.anvs-abi`
+//! marker if the project writes one (e.g., `node -p process.versions.modules`
+//! in a postinstall script), or otherwise from the compiled addons
+//! themselves, which export `node_register_module_v{ABI}`. N-API addons are
+//! ABI-stable and don't export it, so they never cause a mismatch. Reading
+//! addons means reading whole binaries, so their ABIs are cached by mtime.
+
+use crate::cache::ResolutionCache;
+use log::{debug, trace};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marker file recording the ABI `node_modules` was installed with
+pub const ABI_MARKER: &str = ".anvs-abi";
+
+/// Symbol prefix exported by non-N-API addons
+const REGISTER_SYMBOL: &[u8] = b"node_register_module_v";
+
+/// NODE_MODULE_VERSION of each Node.js major, for versions that aren't
+/// installed yet (installed versions are read from their headers)
+const ABI_BY_MAJOR: &[(u64, u32)] = &[
+    (8, 57),
+    (9, 59),
+    (10, 64),
+    (11, 67),
+    (12, 72),
+    (13, 79),
+    (14, 83),
+    (15, 88),
+    (16, 93),
+    (17, 102),
+    (18, 108),
+    (19, 111),
+    (20, 115),
+    (21, 120),
+    (22, 127),
+    (23, 131),
+    (24, 137),
+    (25, 141),
+];
+
+/// `node_modules` built for a different ABI than the activated version
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbiMismatch {
+    /// Directory containing `node_modules`
+    pub project_root: PathBuf,
+    /// ABI `node_modules` was built for
+    pub installed: u32,
+    /// ABI of the activated version
+    pub active: u32,
+    /// Version that was activated
+    pub version: String,
+}
+
+impl AbiMismatch {
+    /// Warning shown after activation
+    pub fn message(&self) -> String {
+        format!(
+            "node_modules in {} was built for NODE_MODULE_VERSION {}, but Node.js {} uses {}. \
+             Native modules need rebuilding: {}",
+            self.project_root.display(),
+            self.installed,
+            self.version,
+            self.active,
+            self.rebuild_command()
+        )
+    }
+
+    /// Shell command that rebuilds native modules with the project's package manager
+    ///
+    /// Refreshes the `.anvs-abi` marker too, if the project has one.
+    pub fn rebuild_command(&self) -> String {
+        let manager = if self.project_root.join("pnpm-lock.yaml").is_file() {
+            "pnpm"
+        } else {
+            "npm"
+        };
+        let mut command = format!(
+            "(cd {} && {manager} rebuild",
+            shell_escape::escape(self.project_root.to_string_lossy())
+        );
+        if self
+            .project_root
+            .join("node_modules")
+            .join(ABI_MARKER)
+            .is_file()
+        {
+            command.push_str(&format!(
+                " && node -p process.versions.modules > node_modules/{ABI_MARKER}"
+            ));
+        }
+        command.push(')');
+        command
+    }
+}
+
+/// Compares the nearest `node_modules` above `start_dir` with `version`
+///
+/// `node_dir` is the version's installation prefix, if known; its headers
+/// give the exact ABI. Returns `None` if there's no `node_modules`, it has no
+/// ABI-specific addons, or the ABIs match.
+pub fn check(
+    start_dir: &Path,
+    version: &str,
+    node_dir: Option<&Path>,
+    cache: Option<&ResolutionCache>,
+) -> Option<AbiMismatch> {
+    let project_root = find_node_modules(start_dir)?;
+    let installed = installed_abi(&project_root.join("node_modules"), cache)?;
+    let active = node_dir
+        .and_then(header_abi)
+        .or_else(|| abi_for_version(version))?;
+    debug!("ABI check: node_modules {installed}, Node.js {version} {active}");
+
+    (installed != active).then(|| AbiMismatch {
+        project_root,
+        installed,
+        active,
+        version: version.to_string(),
+    })
+}
+
+/// NODE_MODULE_VERSION for a Node.js version, from the built-in table
+pub fn abi_for_version(version: &str) -> Option<u32> {
+    let major: u64 = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()?;
+    ABI_BY_MAJOR
+        .iter()
+        .find(|(m, _)| *m == major)
+        .map(|(_, abi)| *abi)
+}
+
+/// Nearest directory above `start_dir` containing `node_modules` (stops at HOME)
+fn find_node_modules(start_dir: &Path) -> Option<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    for dir in start_dir.ancestors() {
+        if dir.join("node_modules").is_dir() {
+            return Some(dir.to_path_buf());
+        }
+        if dir == home {
+            break;
+        }
+    }
+    None
+}
+
+/// ABI `node_modules` was built for: the marker, or the first ABI-specific addon
+fn installed_abi(node_modules: &Path, cache: Option<&ResolutionCache>) -> Option<u32> {
+    if let Ok(marker) = fs::read_to_string(node_modules.join(ABI_MARKER)) {
+        if let Ok(abi) = marker.trim().parse() {
+            return Some(abi);
+        }
+        debug!("Ignoring invalid {ABI_MARKER} marker: {marker:?}");
+    }
+
+    let mut read = Vec::new();
+    let abi = addon_paths(node_modules).into_iter().find_map(|path| {
+        if let Some(abi) = cache.and_then(|cache| cache.get_addon_abi(&path)) {
+            return abi;
+        }
+        let abi = addon_abi(&path);
+        read.push((path, abi));
+        abi
+    });
+
+    if let Some(cache) = cache.filter(|_| !read.is_empty()) {
+        if let Err(e) = cache.put_addon_abis(&read) {
+            debug!("Failed to cache addon ABIs: {e}");
+        }
+    }
+    abi
+}
+
+/// Compiled addons in `build/Release` of each (scoped) package
+fn addon_paths(node_modules: &Path) -> Vec<PathBuf> {
+    let mut packages = Vec::new();
+    for entry in read_dirs(node_modules) {
+        let is_scope = entry
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('@'));
+        if is_scope {
+            packages.extend(read_dirs(&entry));
+        } else {
+            packages.push(entry);
+        }
+    }
+
+    packages
+        .iter()
+        .flat_map(|package| {
+            fs::read_dir(package.join("build/Release"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "node"))
+        })
+        .collect()
+}
+
+/// Subdirectories of `dir`
+fn read_dirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+/// ABI an addon was compiled for, from its `node_register_module_v{ABI}` export
+fn addon_abi(path: &Path) -> Option<u32> {
+    trace!("Reading addon ABI: {}", path.display());
+    let bytes = fs::read(path).ok()?;
+    let start = bytes
+        .windows(REGISTER_SYMBOL.len())
+        .position(|window| window == REGISTER_SYMBOL)?
+        + REGISTER_SYMBOL.len();
+    let digits: String = bytes[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .map(|&b| b as char)
+        .collect();
+    digits.parse().ok()
+}
+
+/// NODE_MODULE_VERSION from an installation's `include/node/node_version.h`
+fn header_abi(node_dir: &Path) -> Option<u32> {
+    let header = fs::read_to_string(node_dir.join("include/node/node_version.h")).ok()?;
+    header.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("#define NODE_MODULE_VERSION ")?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_abi_for_version() {
+        assert_eq!(abi_for_version("18.20.0"), Some(108));
+        assert_eq!(abi_for_version("v20.11.0"), Some(115));
+        assert_eq!(abi_for_version("22"), Some(127));
+        assert_eq!(abi_for_version("lts/iron"), None);
+        assert_eq!(abi_for_version("4.0.0"), None);
+    }
+
+    #[test]
+    fn test_marker_mismatch() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+
+        // Pure-JS node_modules never mismatch
+        assert_eq!(check(&root.join("src"), "20.11.0", None, None), None);
+
+        fs::write(root.join("node_modules").join(ABI_MARKER), "108\n").unwrap();
+        let mismatch = check(&root.join("src"), "20.11.0", None, None).unwrap();
+        assert_eq!(mismatch.installed, 108);
+        assert_eq!(mismatch.active, 115);
+        assert_eq!(mismatch.project_root, root);
+        assert!(mismatch.message().contains("npm rebuild"));
+        assert!(mismatch
+            .rebuild_command()
+            .contains("> node_modules/.anvs-abi"));
+
+        assert_eq!(check(root, "18.20.0", None, None), None);
+    }
+
+    #[test]
+    fn test_addon_detection() {
+        let temp = tempdir().unwrap();
+        let release = temp.path().join("node_modules/@scope/native/build/Release");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(
+            release.join("addon.node"),
+            b"\x7fELF\0\0junk\0node_register_module_v93\0more",
+        )
+        .unwrap();
+        fs::write(temp.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let mismatch = check(temp.path(), "18.20.0", None, None).unwrap();
+        assert_eq!(mismatch.installed, 93);
+        assert!(mismatch.rebuild_command().contains("pnpm rebuild)"));
+
+        // With a cache, the addon's ABI is remembered
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        assert!(check(temp.path(), "18.20.0", None, Some(&cache)).is_some());
+        assert_eq!(
+            cache.get_addon_abi(&release.join("addon.node")),
+            Some(Some(93))
+        );
+    }
+
+    #[test]
+    fn test_header_abi() {
+        let temp = tempdir().unwrap();
+        let include = temp.path().join("include/node");
+        fs::create_dir_all(&include).unwrap();
+        fs::write(
+            include.join("node_version.h"),
+            "#define NODE_MAJOR_VERSION 20\n#define NODE_MODULE_VERSION 115\n",
+        )
+        .unwrap();
+        assert_eq!(header_abi(temp.path()), Some(115));
+
+        fs::create_dir_all(temp.path().join("node_modules")).unwrap();
+        fs::write(temp.path().join("node_modules").join(ABI_MARKER), "115").unwrap();
+        // The header wins over the version table
+        assert_eq!(check(temp.path(), "18.20.0", Some(temp.path()), None), None);
+    }
+}
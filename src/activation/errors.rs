@@ -27,6 +27,28 @@ pub enum ActivationError {
     #[error("Node.js version {version} is not installed")]
     VersionNotInstalled { version: String, hint: String },
 
+    /// An install run by anvs (`install_mode: process`) failed; the shell
+    /// wasn't changed
+    #[error("installing Node.js {version} failed{}", exit_code_suffix(*.code))]
+    InstallFailed {
+        version: String,
+        code: Option<i32>,
+        /// Last lines of install output
+        output: Vec<String>,
+        /// Command to retry the install by hand
+        command: String,
+        /// Version still active in the shell
+        previous: Option<String>,
+    },
+
+    /// An install run by anvs was stopped with Ctrl-C; the shell wasn't changed
+    #[error("installing Node.js {version} was cancelled")]
+    InstallCancelled {
+        version: String,
+        command: String,
+        previous: Option<String>,
+    },
+
     /// No plugins are available on the system
     #[error("no version manager plugins available")]
     NoPluginsAvailable,
@@ -39,6 +61,37 @@ pub enum ActivationError {
         source: anyhow::Error,
     },
 
+    /// A version manager command was killed after exceeding its timeout
+    #[error("version manager command timed out after {timeout_ms}ms")]
+    CommandTimeout { command: String, timeout_ms: u64 },
+
+    /// The whole activation took longer than `activation_timeout_ms`
+    #[error("activation took longer than {timeout_ms}ms, no changes were made")]
+    DeadlineExceeded { timeout_ms: u64 },
+
+    /// The version is end-of-life and `eol_severity` is `block`
+    #[error("Node.js {version} is end-of-life (since {eol})")]
+    EndOfLife { version: String, eol: String },
+
+    /// The version file contradicts package.json engines.node and
+    /// `version_check` is `strict`
+    #[error("Node.js {version} from {file} doesn't satisfy engines.node {range}")]
+    EnginesMismatch {
+        version: String,
+        file: String,
+        range: String,
+    },
+
+    /// The workspace root pin doesn't satisfy the nearest version file's range
+    /// (`workspace_strategy: root-satisfies-nearest`)
+    #[error("workspace root pins Node.js {version}, but {nearest} requires {range}")]
+    WorkspaceMismatch {
+        version: String,
+        root: String,
+        nearest: String,
+        range: String,
+    },
+
     /// Configuration error
     #[error("configuration error")]
     ConfigError(#[from] crate::error::AnvsError),
@@ -48,7 +101,43 @@ pub enum ActivationError {
     IoError(#[from] io::Error),
 }
 
+/// " (exit code N)" for an install's exit code, empty if it was killed
+fn exit_code_suffix(code: Option<i32>) -> String {
+    code.map(|code| format!(" (exit code {code})"))
+        .unwrap_or_default()
+}
+
+/// Which version the shell kept after a failed or cancelled install
+fn still_active(previous: Option<&str>) -> String {
+    match previous {
+        Some(version) => format!("Nothing was changed; Node.js {version} is still active."),
+        None => "Nothing was changed in your shell.".to_string(),
+    }
+}
+
 impl ActivationError {
+    /// Wraps a plugin error, surfacing command timeouts as `CommandTimeout`
+    pub fn plugin(plugin: impl Into<String>, source: anyhow::Error) -> Self {
+        match crate::plugins::CommandTimeout::find(&source) {
+            Some(timeout) => Self::CommandTimeout {
+                command: timeout.command.clone(),
+                timeout_ms: timeout.timeout.as_millis() as u64,
+            },
+            None => Self::PluginError {
+                plugin: plugin.into(),
+                source,
+            },
+        }
+    }
+
+    /// Whether another version manager might succeed where this one failed
+    ///
+    /// Timeouts don't qualify: whatever hung one version manager (often the
+    /// network) tends to hang the next.
+    pub fn allows_fallback(&self) -> bool {
+        matches!(self, Self::PluginError { .. } | Self::InstallFailed { .. })
+    }
+
     /// Get an actionable hint for this error
     pub fn hint(&self) -> Option<String> {
         match self {
@@ -60,6 +149,31 @@ impl ActivationError {
                     .to_string(),
             ),
             Self::VersionNotInstalled { hint, .. } => Some(hint.clone()),
+            Self::InstallFailed {
+                output,
+                command,
+                previous,
+                ..
+            } => {
+                let mut hint = String::new();
+                if !output.is_empty() {
+                    hint.push_str("Last output:\n");
+                    for line in output {
+                        hint.push_str(&format!("  {line}\n"));
+                    }
+                }
+                hint.push_str(&format!(
+                    "{}\nCheck your network connection and version manager, then retry:\n  {command}",
+                    still_active(previous.as_deref())
+                ));
+                Some(hint)
+            }
+            Self::InstallCancelled {
+                command, previous, ..
+            } => Some(format!(
+                "{}\nTo install it later:\n  {command}",
+                still_active(previous.as_deref())
+            )),
             Self::EmptyVersionFile { path } => Some(format!(
                 "The version file '{path}' is empty.\n\
                  Add a Node.js version (e.g., '18.20.0') to the file."
@@ -68,6 +182,31 @@ impl ActivationError {
                 "The version file '{path}' could not be read.\n\
                  Check file permissions and format."
             )),
+            Self::CommandTimeout { command, .. } => Some(format!(
+                "This command hung and was stopped:\n  {command}\n\
+                 Check that your version manager works in a new shell, or raise\n\
+                 command_timeout_ms in ~/.anvsrc (0 disables the limit)."
+            )),
+            Self::DeadlineExceeded { .. } => Some(
+                "Your version manager is responding slowly. Run 'anvs activate -v' to see\n\
+                 which step is slow, or raise activation_timeout_ms in ~/.anvsrc\n\
+                 (0 disables the limit)."
+                    .to_string(),
+            ),
+            Self::EndOfLife { .. } => Some(
+                "End-of-life versions no longer receive security fixes. Update the\n\
+                 version file to a supported release, or set eol_severity: warn in\n\
+                 .anvs.yaml to allow it."
+                    .to_string(),
+            ),
+            Self::EnginesMismatch { file, .. } => Some(format!(
+                "Update {file} or package.json so they agree, then run 'anvs check'.\n\
+                 Set version_check: warn in .anvs.yaml to activate anyway."
+            )),
+            Self::WorkspaceMismatch { root, .. } => Some(format!(
+                "Update {root} to a version the package supports, or set\n\
+                 workspace_strategy: root in .anvs.yaml to always use the root pin."
+            )),
             Self::ConfigError(_) => Some(
                 "Run 'anvs setup' to create a default configuration, or check ~/.anvsrc syntax."
                     .to_string(),
@@ -105,6 +244,33 @@ mod tests {
         assert_eq!(hint, "Run: nvm install 18.20.0");
     }
 
+    #[test]
+    fn test_error_hints_install_failed() {
+        let err = ActivationError::InstallFailed {
+            version: "20.11.0".to_string(),
+            code: Some(3),
+            output: vec!["curl: (6) Could not resolve host".to_string()],
+            command: "nvm install 20.11.0".to_string(),
+            previous: Some("18.20.0".to_string()),
+        };
+
+        assert_eq!(
+            format!("{err}"),
+            "installing Node.js 20.11.0 failed (exit code 3)"
+        );
+        let hint = err.hint().unwrap();
+        assert!(hint.contains("  curl: (6) Could not resolve host"));
+        assert!(hint.contains("Node.js 18.20.0 is still active"));
+        assert!(hint.ends_with("  nvm install 20.11.0"));
+
+        let err = ActivationError::InstallCancelled {
+            version: "20.11.0".to_string(),
+            command: "nvm install 20.11.0".to_string(),
+            previous: None,
+        };
+        assert!(err.hint().unwrap().contains("Nothing was changed"));
+    }
+
     #[test]
     fn test_error_hints_empty_version_file() {
         let err = ActivationError::EmptyVersionFile {
@@ -132,6 +298,65 @@ mod tests {
         assert!(msg.contains("not installed"));
     }
 
+    #[test]
+    fn test_plugin_error_maps_timeouts() {
+        let timeout = crate::plugins::CommandTimeout {
+            command: "fnm list".to_string(),
+            timeout: std::time::Duration::from_millis(250),
+        };
+        let err = ActivationError::plugin("fnm", anyhow::Error::new(timeout).context("failed"));
+        assert!(matches!(
+            err,
+            ActivationError::CommandTimeout { ref command, timeout_ms: 250 } if command == "fnm list"
+        ));
+        assert!(err.hint().unwrap().contains("command_timeout_ms"));
+
+        let err = ActivationError::plugin("fnm", anyhow::anyhow!("boom"));
+        assert!(matches!(err, ActivationError::PluginError { .. }));
+    }
+
+    #[test]
+    fn test_end_of_life_error() {
+        let err = ActivationError::EndOfLife {
+            version: "16.20.2".to_string(),
+            eol: "2023-09-11".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Node.js 16.20.2 is end-of-life (since 2023-09-11)"
+        );
+        assert!(err.hint().unwrap().contains("eol_severity"));
+    }
+
+    #[test]
+    fn test_engines_mismatch_error() {
+        let err = ActivationError::EnginesMismatch {
+            version: "16.20.2".to_string(),
+            file: ".nvmrc".to_string(),
+            range: ">=18".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Node.js 16.20.2 from .nvmrc doesn't satisfy engines.node >=18"
+        );
+        assert!(err.hint().unwrap().contains("anvs check"));
+    }
+
+    #[test]
+    fn test_workspace_mismatch_error() {
+        let err = ActivationError::WorkspaceMismatch {
+            version: "18.20.0".to_string(),
+            root: "/repo/.nvmrc".to_string(),
+            nearest: "/repo/packages/api/package.json".to_string(),
+            range: ">=20".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "workspace root pins Node.js 18.20.0, but /repo/packages/api/package.json requires >=20"
+        );
+        assert!(err.hint().unwrap().contains("/repo/.nvmrc"));
+    }
+
     #[test]
     fn test_no_hint_for_io_error() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
@@ -0,0 +1,229 @@
+//! Activation history
+//!
+//! Every activation decision is appended as one JSON line to
+//! `~/.anvs/activations.jsonl`, so "node changed on its own" can be traced
+//! back to the directory, version file and command responsible. When the log
+//! grows past `MAX_LOG_BYTES` it's moved to `activations.jsonl.1`, keeping
+//! one older generation.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, Local};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Size at which the log is rotated
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// One activation decision
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivationRecord {
+    /// When the activation started (RFC 3339, local time)
+    pub timestamp: String,
+    /// Directory activated for
+    pub cwd: PathBuf,
+    /// Version file (or path rule glob) that applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_file: Option<PathBuf>,
+    /// Version as written in the version file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+    /// Version that was activated or installed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Version manager used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    /// Commands written to the shell
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Time taken, in milliseconds
+    pub duration_ms: u64,
+    /// Error that stopped the activation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the daemon answered
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub daemon: bool,
+}
+
+impl ActivationRecord {
+    /// Starts a record for an activation of `cwd` beginning now
+    pub fn start(cwd: &Path) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            cwd: cwd.to_path_buf(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the elapsed time
+    pub fn finish(&mut self, elapsed: Duration) {
+        self.duration_ms = elapsed.as_millis().try_into().unwrap_or(u64::MAX);
+    }
+
+    /// When the activation started
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+}
+
+/// Append-only JSONL activation log with one rotated generation
+#[derive(Debug, Clone)]
+pub struct ActivationLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl ActivationLog {
+    /// Log stored at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: MAX_LOG_BYTES,
+        }
+    }
+
+    /// Rotates once the log reaches `max_bytes` (for testing)
+    #[cfg(test)]
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Default location: ~/.anvs/activations.jsonl
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".anvs").join("activations.jsonl"))
+    }
+
+    /// Log in the default location
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(Self::default_path()?))
+    }
+
+    /// Path of the current log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the rotated generation
+    fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    /// Appends a record, rotating first if the log is full
+    pub fn append(&self, record: &ActivationRecord) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size >= self.max_bytes {
+            debug!("Rotating activation log at {size} bytes");
+            fs::rename(&self.path, self.rotated_path())
+                .with_context(|| format!("failed to rotate {}", self.path.display()))?;
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+
+    /// Appends a record, logging (not returning) failures
+    ///
+    /// History must never get in the way of switching versions.
+    pub fn record(&self, record: &ActivationRecord) {
+        if let Err(e) = self.append(record) {
+            warn!("Failed to write activation history: {e:#}");
+        }
+    }
+
+    /// Records from both generations, oldest first
+    ///
+    /// Unparseable lines (e.g., from a crash mid-write) are skipped.
+    pub fn read(&self) -> Vec<ActivationRecord> {
+        [self.rotated_path(), self.path.clone()]
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .flat_map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect::<Vec<ActivationRecord>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(version: &str) -> ActivationRecord {
+        ActivationRecord {
+            version: Some(version.to_string()),
+            commands: vec![format!("nvm use {version}")],
+            ..ActivationRecord::start(Path::new("/work/app"))
+        }
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let temp = tempdir().unwrap();
+        let log = ActivationLog::new(temp.path().join("anvs/activations.jsonl"));
+        assert!(log.read().is_empty());
+
+        log.append(&record("18.20.0")).unwrap();
+        log.append(&record("20.11.0")).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(b"{truncated\n")
+            .unwrap();
+
+        let records = log.read();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].commands, vec!["nvm use 18.20.0"]);
+        assert_eq!(records[1].version.as_deref(), Some("20.11.0"));
+        assert!(records[1].time().is_some());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let temp = tempdir().unwrap();
+        let log = ActivationLog::new(temp.path().join("activations.jsonl")).with_max_bytes(1);
+
+        log.append(&record("16.20.2")).unwrap();
+        log.append(&record("18.20.0")).unwrap();
+        log.append(&record("20.11.0")).unwrap();
+
+        // Only one older generation is kept
+        let versions: Vec<_> = log
+            .read()
+            .into_iter()
+            .filter_map(|record| record.version)
+            .collect();
+        assert_eq!(versions, vec!["18.20.0", "20.11.0"]);
+    }
+
+    #[test]
+    fn test_optional_fields_omitted() {
+        let line = serde_json::to_string(&ActivationRecord::start(Path::new("/tmp"))).unwrap();
+        assert!(!line.contains("error"));
+        assert!(!line.contains("daemon"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Per-version auto-install policy
+//!
+//! `auto_install_rules` refine the global `auto_install` mode for the version
+//! about to be installed. A rule lists conditions (a range, whether the major
+//! is already installed, end-of-life, prerelease, release age); the first rule
+//! whose conditions all hold decides, and `auto_install` applies otherwise.
+
+use crate::config::{AutoInstallMode, AutoInstallRule, Config};
+use crate::release_index::ReleaseIndex;
+use crate::version_file::{parse_version, VersionRange};
+use chrono::NaiveDate;
+use log::debug;
+use semver::Version;
+
+/// What auto-install rules can test about a version
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionFacts {
+    /// The version, if it's a concrete version
+    pub version: Option<Version>,
+    /// Whether another version of the same major is installed
+    pub installed_major: bool,
+    /// Whether the release line is past its end-of-life date
+    pub eol: bool,
+    /// Whether the version is a prerelease (rc, nightly, ...)
+    pub prerelease: bool,
+    /// Days since the release, if the release index knows it
+    pub age_days: Option<i64>,
+}
+
+impl VersionFacts {
+    /// Facts about `version`, given the versions `installed` by any plugin
+    pub fn gather(
+        version: &str,
+        installed: &[String],
+        index: &ReleaseIndex,
+        today: NaiveDate,
+    ) -> Self {
+        let Some(parsed) = parse_version(version) else {
+            return Self::default();
+        };
+
+        let installed_major = installed
+            .iter()
+            .filter_map(|installed| parse_version(installed))
+            .any(|installed| installed.major == parsed.major);
+        let eol = index
+            .line(parsed.major)
+            .and_then(|line| line.eol)
+            .is_some_and(|eol| eol < today);
+        let age_days = index
+            .release(&parsed)
+            .and_then(|release| release.date)
+            .map(|date| (today - date).num_days());
+
+        Self {
+            installed_major,
+            eol,
+            prerelease: !parsed.pre.is_empty(),
+            age_days,
+            version: Some(parsed),
+        }
+    }
+}
+
+/// Whether every condition of `rule` holds for `facts`
+///
+/// Conditions on unknown facts (the age of a release missing from the index,
+/// the range of an alias) don't hold.
+pub fn rule_matches(rule: &AutoInstallRule, facts: &VersionFacts) -> bool {
+    let range = rule.range.as_ref().is_none_or(|range| {
+        match (VersionRange::parse(range), &facts.version) {
+            (Ok(parsed), Some(version)) => parsed.matches(version),
+            (Err(e), _) => {
+                debug!("Ignoring auto_install_rules range '{range}': {e}");
+                false
+            }
+            (Ok(_), None) => false,
+        }
+    });
+    let age = |check: fn(i64, i64) -> bool, limit: Option<u32>| {
+        limit.is_none_or(|limit| facts.age_days.is_some_and(|age| check(age, limit.into())))
+    };
+
+    range
+        && rule
+            .installed_major
+            .is_none_or(|want| want == facts.installed_major)
+        && rule.eol.is_none_or(|want| want == facts.eol)
+        && rule.prerelease.is_none_or(|want| want == facts.prerelease)
+        && age(|age, min| age >= min, rule.min_age_days)
+        && age(|age, max| age <= max, rule.max_age_days)
+}
+
+/// Auto-install mode for a version: the first matching rule, or `auto_install`
+pub fn mode_for(config: &Config, facts: &VersionFacts) -> AutoInstallMode {
+    match config
+        .auto_install_rules
+        .iter()
+        .position(|rule| rule_matches(rule, facts))
+    {
+        Some(i) => {
+            let mode = config.auto_install_rules[i].auto_install;
+            debug!("auto_install_rules[{i}] matched: {mode:?}");
+            mode
+        }
+        None => config.auto_install,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(yaml: &str) -> AutoInstallRule {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn facts(version: &str) -> VersionFacts {
+        VersionFacts {
+            version: parse_version(version),
+            ..VersionFacts::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let patch = rule("installed_major: true\nauto_install: always\n");
+        assert!(!rule_matches(&patch, &facts("20.11.1")));
+        assert!(rule_matches(
+            &patch,
+            &VersionFacts {
+                installed_major: true,
+                ..facts("20.11.1")
+            }
+        ));
+
+        let range = rule("range: '>=22'\nauto_install: prompt\n");
+        assert!(rule_matches(&range, &facts("22.1.0")));
+        assert!(!rule_matches(&range, &facts("20.11.1")));
+        assert!(!rule_matches(&range, &VersionFacts::default()));
+
+        let fresh = rule("max_age_days: 7\nauto_install: never\n");
+        assert!(!rule_matches(&fresh, &facts("22.1.0")));
+        let aged = |days| VersionFacts {
+            age_days: Some(days),
+            ..facts("22.1.0")
+        };
+        assert!(rule_matches(&fresh, &aged(3)));
+        assert!(!rule_matches(&fresh, &aged(30)));
+
+        let everything = rule("auto_install: always\n");
+        assert!(rule_matches(&everything, &VersionFacts::default()));
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let config: Config = serde_yaml::from_str(
+            "auto_install: prompt\n\
+             auto_install_rules:\n\
+             \x20 - eol: true\n\
+             \x20   auto_install: never\n\
+             \x20 - prerelease: true\n\
+             \x20   auto_install: never\n\
+             \x20 - installed_major: true\n\
+             \x20   auto_install: always\n",
+        )
+        .unwrap();
+
+        let installed_eol = VersionFacts {
+            installed_major: true,
+            eol: true,
+            ..facts("16.20.2")
+        };
+        assert_eq!(mode_for(&config, &installed_eol), AutoInstallMode::Never);
+
+        let patch = VersionFacts {
+            installed_major: true,
+            ..facts("20.11.1")
+        };
+        assert_eq!(mode_for(&config, &patch), AutoInstallMode::Always);
+        assert_eq!(mode_for(&config, &facts("22.1.0")), AutoInstallMode::Prompt);
+    }
+
+    #[test]
+    fn test_gather() {
+        let index = ReleaseIndex::bundled();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let installed = vec!["v18.19.0".to_string(), "system".to_string()];
+
+        let facts = VersionFacts::gather("18.20.0", &installed, &index, today);
+        assert!(facts.installed_major);
+        assert!(facts.eol);
+        assert!(!facts.prerelease);
+        assert!(facts.age_days.is_some_and(|days| days > 0));
+
+        let facts = VersionFacts::gather("v26.0.0-rc.1", &installed, &index, today);
+        assert!(!facts.installed_major);
+        assert!(facts.prerelease);
+        assert_eq!(facts.age_days, None);
+
+        assert_eq!(
+            VersionFacts::gather("lts/iron", &installed, &index, today),
+            VersionFacts::default()
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Installs run by anvs itself
+//!
+//! With `install_mode: process`, the version manager's install runs as a
+//! child process instead of being written to the shell as `install && use`.
+//! anvs shows progress, stops the install on Ctrl-C, and only switches the
+//! shell once the install succeeded, so a failed install leaves the previous
+//! version active.
+
+use crate::plugins::VersionManagerPlugin;
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lines of install output kept for the failure diagnostic
+pub const OUTPUT_TAIL_LINES: usize = 10;
+
+/// How often progress is redrawn and Ctrl-C is checked
+const TICK: Duration = Duration::from_millis(100);
+
+/// How long to keep reading output after the install exits
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Set by the SIGINT handler while an install is running
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// How an install ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallOutcome {
+    Installed,
+    /// The install exited unsuccessfully
+    Failed {
+        /// Exit code (`None` if killed by a signal)
+        code: Option<i32>,
+        /// Last lines of output
+        output: Vec<String>,
+    },
+    /// Stopped with Ctrl-C
+    Cancelled,
+}
+
+/// Runs installs for the orchestrator
+pub trait Installer {
+    /// Installs `version` with `plugin`
+    fn install(
+        &mut self,
+        plugin: &dyn VersionManagerPlugin,
+        version: &str,
+    ) -> Result<InstallOutcome>;
+}
+
+/// Runs the plugin's install as a child process
+pub struct ProcessInstaller;
+
+impl Installer for ProcessInstaller {
+    fn install(
+        &mut self,
+        plugin: &dyn VersionManagerPlugin,
+        version: &str,
+    ) -> Result<InstallOutcome> {
+        let mut command = plugin.install_process(version)?;
+        run(
+            &mut command,
+            &format!("Installing Node.js {version} with {}", plugin.name()),
+        )
+    }
+}
+
+/// Installer for `anvs activate --dry-run`: prints the install, runs nothing
+pub struct DryRunInstaller;
+
+impl Installer for DryRunInstaller {
+    fn install(
+        &mut self,
+        plugin: &dyn VersionManagerPlugin,
+        version: &str,
+    ) -> Result<InstallOutcome> {
+        println!("Would run: {} (dry run)", plugin.install_command(version)?);
+        Ok(InstallOutcome::Installed)
+    }
+}
+
+/// Mock implementation for testing
+#[cfg(test)]
+pub struct MockInstaller {
+    pub outcome: InstallOutcome,
+    /// Plugins whose installs fail regardless of `outcome`
+    pub failing: Vec<String>,
+    pub installs: Vec<String>,
+}
+
+#[cfg(test)]
+impl MockInstaller {
+    pub fn new(outcome: InstallOutcome) -> Self {
+        Self {
+            outcome,
+            failing: Vec::new(),
+            installs: Vec::new(),
+        }
+    }
+
+    pub fn failing_for(mut self, plugin: &str) -> Self {
+        self.failing.push(plugin.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+impl Installer for MockInstaller {
+    fn install(
+        &mut self,
+        plugin: &dyn VersionManagerPlugin,
+        version: &str,
+    ) -> Result<InstallOutcome> {
+        self.installs.push(format!("{} {version}", plugin.name()));
+        if self.failing.iter().any(|name| name == plugin.name()) {
+            return Ok(InstallOutcome::Failed {
+                code: Some(1),
+                output: vec![format!("{} install failed", plugin.name())],
+            });
+        }
+        Ok(self.outcome.clone())
+    }
+}
+
+/// Runs `command` with a progress line labelled `label`, until it exits or
+/// the user presses Ctrl-C
+///
+/// The command runs in its own process group, so Ctrl-C reaches anvs only
+/// and anvs stops the whole install (including downloads it started).
+pub fn run(command: &mut Command, label: &str) -> Result<InstallOutcome> {
+    let description = format!("{command:?}");
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+        .with_context(|| format!("failed to run {description}"))?;
+
+    let (tx, rx) = mpsc::channel();
+    forward_lines(child.stdout.take(), tx.clone());
+    forward_lines(child.stderr.take(), tx);
+
+    let _sigint = SigintGuard::install();
+    let mut progress = Progress::new(label);
+    let mut tail = VecDeque::with_capacity(OUTPUT_TAIL_LINES);
+
+    let status = loop {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            progress.clear();
+            warn!("Install interrupted, stopping {description}");
+            kill_group(&mut child);
+            return Ok(InstallOutcome::Cancelled);
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        while let Ok(line) = rx.try_recv() {
+            progress.set_line(&line);
+            push_line(&mut tail, line);
+        }
+        progress.draw();
+        thread::sleep(TICK);
+    };
+
+    // Collect what's left once the pipes close
+    drain_lines(&rx, &mut tail);
+    progress.clear();
+    debug!("{description} exited with {status}");
+
+    Ok(outcome(status, tail))
+}
+
+fn outcome(status: ExitStatus, tail: VecDeque<String>) -> InstallOutcome {
+    if status.success() {
+        InstallOutcome::Installed
+    } else {
+        InstallOutcome::Failed {
+            code: status.code(),
+            output: tail.into(),
+        }
+    }
+}
+
+/// Sends each line (or `\r`-separated progress update) of `pipe` to `tx`
+fn forward_lines(pipe: Option<impl Read + Send + 'static>, tx: Sender<String>) {
+    let Some(mut pipe) = pipe else {
+        return;
+    };
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut line = Vec::new();
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            for &byte in &buf[..n] {
+                if byte == b'\n' || byte == b'\r' {
+                    send_line(&tx, &mut line);
+                } else {
+                    line.push(byte);
+                }
+            }
+        }
+        send_line(&tx, &mut line);
+    });
+}
+
+fn send_line(tx: &Sender<String>, line: &mut Vec<u8>) {
+    let text = String::from_utf8_lossy(line).trim().to_string();
+    line.clear();
+    if !text.is_empty() {
+        let _ = tx.send(text);
+    }
+}
+
+/// Receives remaining lines until both pipes close
+fn drain_lines(rx: &Receiver<String>, tail: &mut VecDeque<String>) {
+    // A leftover grandchild may hold a pipe open, so give up eventually
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match rx.recv_timeout(left) {
+            Ok(line) => push_line(tail, line),
+            Err(_) => break,
+        }
+    }
+}
+
+fn push_line(tail: &mut VecDeque<String>, line: String) {
+    if tail.len() == OUTPUT_TAIL_LINES {
+        tail.pop_front();
+    }
+    tail.push_back(line);
+}
+
+/// Terminates the child's process group and reaps the child
+fn kill_group(child: &mut Child) {
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: signals the process group the child leads; no memory is shared
+        unsafe {
+            libc::kill(-pid, libc::SIGTERM);
+        }
+    }
+    if let Err(e) = child.kill() {
+        debug!("Failed to kill install process: {e}");
+    }
+    let _ = child.wait();
+}
+
+extern "C" fn on_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Catches SIGINT while an install runs, restoring the previous handler on drop
+struct SigintGuard(libc::sighandler_t);
+
+impl SigintGuard {
+    fn install() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        let handler = on_sigint as extern "C" fn(libc::c_int) as *const () as libc::sighandler_t;
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        Self(unsafe { libc::signal(libc::SIGINT, handler) })
+    }
+}
+
+impl Drop for SigintGuard {
+    fn drop(&mut self) {
+        // SAFETY: restores the handler returned by `signal` in `install`
+        unsafe {
+            libc::signal(libc::SIGINT, self.0);
+        }
+    }
+}
+
+/// Single-line spinner on stdout (the terminal, in the shell hook)
+///
+/// Without a terminal nothing is drawn.
+struct Progress {
+    label: String,
+    started: Instant,
+    frame: usize,
+    line: String,
+    enabled: bool,
+}
+
+impl Progress {
+    fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            started: Instant::now(),
+            frame: 0,
+            line: String::new(),
+            enabled: io::stdout().is_terminal(),
+        }
+    }
+
+    fn set_line(&mut self, line: &str) {
+        self.line = line.chars().take(60).collect();
+    }
+
+    fn draw(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        let spinner = SPINNER[self.frame % SPINNER.len()];
+        self.frame += 1;
+        let mut stdout = io::stdout();
+        let _ = write!(
+            stdout,
+            "\r\x1b[2K{spinner} {} ({}s) {}",
+            self.label,
+            self.started.elapsed().as_secs(),
+            self.line
+        );
+        let _ = stdout.flush();
+    }
+
+    fn clear(&self) {
+        if self.enabled {
+            let mut stdout = io::stdout();
+            let _ = write!(stdout, "\r\x1b[2K");
+            let _ = stdout.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// Installs share the SIGINT handler and flag, so tests run one at a time
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn serial() -> MutexGuard<'static, ()> {
+        SERIAL.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn sh(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn test_successful_install() {
+        let _serial = serial();
+        let outcome = run(&mut sh("echo downloading; echo done"), "Installing").unwrap();
+        assert_eq!(outcome, InstallOutcome::Installed);
+    }
+
+    #[test]
+    fn test_failed_install_keeps_output_tail() {
+        let _serial = serial();
+        let script = "for i in $(seq 1 15); do echo line $i; done; exit 3";
+        let outcome = run(&mut sh(script), "Installing").unwrap();
+
+        let InstallOutcome::Failed { code, output } = outcome else {
+            panic!("expected a failed install, got {outcome:?}");
+        };
+        assert_eq!(code, Some(3));
+        assert_eq!(output.len(), OUTPUT_TAIL_LINES);
+        assert_eq!(output.first().map(String::as_str), Some("line 6"));
+        assert_eq!(output.last().map(String::as_str), Some("line 15"));
+    }
+
+    #[test]
+    fn test_stderr_progress_is_split_on_carriage_returns() {
+        // Only stderr, so the order of lines is known
+        let _serial = serial();
+        let script =
+            "printf 'Downloading\\r10%%\\r50%%\\r' >&2; echo 'curl: (56) reset' >&2; exit 7";
+        let outcome = run(&mut sh(script), "Installing").unwrap();
+
+        assert_eq!(
+            outcome,
+            InstallOutcome::Failed {
+                code: Some(7),
+                output: ["Downloading", "10%", "50%", "curl: (56) reset"]
+                    .map(String::from)
+                    .to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_interrupted_install_is_cancelled() {
+        let _serial = serial();
+        let interrupter = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(300));
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        });
+
+        let started = Instant::now();
+        let outcome = run(&mut sh("sleep 10"), "Installing").unwrap();
+        interrupter.join().unwrap();
+
+        assert_eq!(outcome, InstallOutcome::Cancelled);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
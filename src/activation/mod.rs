@@ -1,10 +1,22 @@
 mod errors;
+mod history;
+mod install_policy;
+mod installer;
 mod orchestrator;
+mod prompt_state;
 mod user_prompt;
 
 pub use errors::{ActivationError, ActivationResult};
-pub use orchestrator::Orchestrator;
-pub use user_prompt::{StdinUserPrompt, UserPrompt};
+pub use history::{ActivationLog, ActivationRecord, MAX_LOG_BYTES};
+pub use install_policy::VersionFacts;
+pub use installer::{
+    DryRunInstaller, InstallOutcome, Installer, ProcessInstaller, OUTPUT_TAIL_LINES,
+};
+pub use orchestrator::{ActivationTarget, Orchestrator, ShellEnv};
+pub use prompt_state::{PromptState, Remembered, DECLINE_EXPIRY_DAYS};
+pub use user_prompt::{DryRunPrompt, InstallAnswer, StdinUserPrompt, UserPrompt};
 
+#[cfg(test)]
+pub use installer::MockInstaller;
 #[cfg(test)]
 pub use user_prompt::MockUserPrompt;
@@ -1,161 +1,653 @@
-use super::{ActivationError, ActivationResult, StdinUserPrompt, UserPrompt};
-use crate::config::{AutoInstallMode, Config};
+use super::history::{ActivationLog, ActivationRecord};
+use super::install_policy::{self, VersionFacts};
+use super::{
+    ActivationError, ActivationResult, InstallAnswer, InstallOutcome, Installer, ProcessInstaller,
+    PromptState, Remembered, StdinUserPrompt, UserPrompt,
+};
+use crate::abi;
+use crate::cache::ResolutionCache;
+use crate::config::{
+    AbiCheck, AutoInstallMode, Config, EolSeverity, InstallMode, LeaveAction, PackageManagerMode,
+    ProjectHooks, VersionCheck, WorkspaceStrategy,
+};
 use crate::engines_resolver::EnginesResolver;
+use crate::hooks::{self, HookStage, HookVars, TrustStore};
 use crate::output;
+use crate::package_manager::{self, Requirements};
 use crate::plugins::{PluginRegistry, VersionManagerPlugin};
-use crate::shell::CommandWriter;
-use crate::version_file::{SemverResolver, VersionFile, VersionFileSource};
+use crate::release_index::{Advisory, ReleaseIndex};
+use crate::scan;
+use crate::shell::{CommandSink, EnvSnapshot};
+use crate::version_file::{
+    parse_version, SemverResolver, VersionFile, VersionFileSource, VersionRange, WorkspacePins,
+};
 use log::{debug, info, warn};
-use std::path::Path;
+use std::cell::{OnceCell, RefCell};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
+
+/// Version file and resolved version for a directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivationTarget {
+    /// Version file that determined the version
+    pub version_file: VersionFile,
+    /// Version to activate (package.json ranges are resolved)
+    pub version: String,
+    /// Version manager to prefer, from a matching path rule
+    pub plugin: Option<String>,
+}
+
+/// The shell an activation is for
+///
+/// anvs normally runs as a child of the shell and reads these from its own
+/// environment. The daemon gets them from the client instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShellEnv {
+    /// The shell's PATH
+    pub path: Option<String>,
+    /// `node` on the shell's PATH
+    pub node_path: Option<PathBuf>,
+    /// Version of that `node`
+    pub current_version: Option<String>,
+    /// The shell's active key, naming the project it's in
+    pub active_key: Option<String>,
+}
+
+impl ShellEnv {
+    /// Reads the shell from this process's environment
+    pub fn from_env() -> Self {
+        Self {
+            path: std::env::var("PATH").ok(),
+            node_path: which::which("node").ok(),
+            current_version: hooks::active_node_version(),
+            active_key: std::env::var(hooks::ACTIVE_KEY_VAR).ok(),
+        }
+    }
+}
 
 /// Orchestrates the complete version activation flow
 pub struct Orchestrator<'a> {
     config: &'a Config,
     registry: &'a PluginRegistry,
-    command_writer: &'a mut CommandWriter,
+    sink: &'a mut dyn CommandSink,
     user_prompt: Box<dyn UserPrompt>,
+    installer: Box<dyn Installer>,
+    env_snapshot: EnvSnapshot,
+    snapshot_commands: Vec<String>,
+    shell: Option<ShellEnv>,
+    active_key: Option<String>,
+    leaving: Option<(PathBuf, Option<ProjectHooks>)>,
+    ran_hooks: bool,
+    cache: Option<Arc<ResolutionCache>>,
+    index: OnceCell<ReleaseIndex>,
+    trust: OnceCell<TrustStore>,
+    deadline: Option<Instant>,
+    history: Option<ActivationLog>,
+    prompt_state: Option<PromptState>,
+    record: ActivationRecord,
+    messages: RefCell<Vec<String>>,
 }
 
 impl<'a> Orchestrator<'a> {
     /// Creates a new orchestrator with default stdin prompt
+    ///
+    /// Commands for the parent shell go to `sink`.
     pub fn new(
         config: &'a Config,
         registry: &'a PluginRegistry,
-        command_writer: &'a mut CommandWriter,
+        sink: &'a mut dyn CommandSink,
     ) -> Self {
         Self {
             config,
             registry,
-            command_writer,
+            sink,
             user_prompt: Box::new(StdinUserPrompt::new()),
+            installer: Box::new(ProcessInstaller),
+            env_snapshot: EnvSnapshot::from_env(),
+            snapshot_commands: Vec::new(),
+            shell: None,
+            active_key: std::env::var(hooks::ACTIVE_KEY_VAR).ok(),
+            leaving: None,
+            ran_hooks: false,
+            cache: None,
+            index: OnceCell::new(),
+            trust: OnceCell::new(),
+            history: None,
+            prompt_state: None,
+            record: ActivationRecord::default(),
+            messages: RefCell::new(Vec::new()),
+            deadline: config
+                .activation_timeout()
+                .map(|timeout| Instant::now() + timeout),
         }
     }
 
-    /// Sets a custom user prompt (for testing)
-    #[cfg(test)]
+    /// Sets a custom user prompt (e.g., for dry runs)
     pub fn with_user_prompt(mut self, prompt: Box<dyn UserPrompt>) -> Self {
         self.user_prompt = prompt;
         self
     }
 
+    /// Sets how `install_mode: process` installs run (e.g., for dry runs)
+    pub fn with_installer(mut self, installer: Box<dyn Installer>) -> Self {
+        self.installer = installer;
+        self
+    }
+
+    /// Enables the on-disk resolution cache for engines requirements
+    pub fn with_cache(mut self, cache: Arc<ResolutionCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Appends each activation decision to `log`
+    pub fn with_history(mut self, log: ActivationLog) -> Self {
+        self.history = Some(log);
+        self
+    }
+
+    /// Remembers install prompt answers per project in `state`
+    ///
+    /// Without it, every missing version is asked about.
+    pub fn with_prompt_state(mut self, state: PromptState) -> Self {
+        self.prompt_state = Some(state);
+        self
+    }
+
+    /// Activates for `shell` instead of the shell this process runs in
+    ///
+    /// Its active version is the one package.json ranges may prefer and hooks
+    /// see as `{previous_version}`.
+    pub fn with_shell_env(mut self, shell: ShellEnv) -> Self {
+        self.active_key = shell.active_key.clone();
+        self.shell = Some(shell);
+        self
+    }
+
+    /// Sets the shell's environment snapshot
+    pub fn with_env_snapshot(mut self, snapshot: EnvSnapshot) -> Self {
+        self.env_snapshot = snapshot;
+        self
+    }
+
+    /// Sets a custom release index (for testing)
+    #[cfg(test)]
+    pub fn with_index(self, index: ReleaseIndex) -> Self {
+        let _ = self.index.set(index);
+        self
+    }
+
+    /// Sets a custom trust store for project hooks (for testing)
+    #[cfg(test)]
+    pub fn with_trust_store(self, trust: TrustStore) -> Self {
+        let _ = self.trust.set(trust);
+        self
+    }
+
+    /// Sets the shell's active key, naming the project being left (for testing)
+    #[cfg(test)]
+    pub fn with_active_key(mut self, key: &str) -> Self {
+        self.active_key = Some(key.to_string());
+        self
+    }
+
     /// Main activation flow
     ///
+    /// Nothing is written to the shell if `activation_timeout_ms` passes
+    /// before the version is known (time spent at the install prompt doesn't count).
+    ///
     /// 1. Find version file
     /// 2. Try to find a plugin with this version installed
     /// 3. If found -> activate
     /// 4. If not found -> handle auto-install
     /// 5. If no version file and use_default -> activate default version
+    ///    (or restore the pre-anvs environment, depending on `leave_action`)
     pub fn activate(&mut self, path: &Path, use_default: bool) -> ActivationResult<()> {
-        // 1. Find version file
-        let version_file = match VersionFile::find(path, &self.config.version_files) {
-            Ok(Some(vf)) => vf,
-            Ok(None) => {
-                // No version file found
-                debug!("No version file found in {}", path.display());
+        let started = Instant::now();
+        self.record = ActivationRecord::start(path);
+        self.snapshot_commands.clear();
 
-                // If use_default is enabled and config allows, activate default version
-                if use_default && self.config.use_default {
-                    return self.activate_default_version();
-                }
+        let result = self.activate_inner(path, use_default);
 
-                // Otherwise, just do nothing
-                return Ok(());
+        if let Some(history) = &self.history {
+            let mut record = std::mem::take(&mut self.record);
+            record.finish(started.elapsed());
+            record.error = result.as_ref().err().map(ToString::to_string);
+            history.record(&record);
+        }
+        result
+    }
+
+    /// Outcome of the last `activate`, unless it went to the activation history
+    pub fn record(&self) -> &ActivationRecord {
+        &self.record
+    }
+
+    /// Warnings and notices printed so far, besides the switch message
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.borrow().clone()
+    }
+
+    /// Whether any hooks were looked up so far, even if none were written
+    pub fn ran_hooks(&self) -> bool {
+        self.ran_hooks
+    }
+
+    /// Activation flow behind `activate`, which records its outcome
+    fn activate_inner(&mut self, path: &Path, use_default: bool) -> ActivationResult<()> {
+        // 1. Find version file and resolve the version it requires
+        let Some(target) = self.find_target(path)? else {
+            // No version file found
+            debug!("No version file found in {}", path.display());
+
+            // If use_default is enabled and config allows, leave the project
+            if use_default && self.config.use_default {
+                return self.leave_project();
+            }
+
+            // Otherwise, just do nothing
+            return Ok(());
+        };
+
+        self.record.version_file = Some(target.version_file.path.clone());
+        self.record.requirement = Some(target.version_file.version.clone());
+        self.record.version = Some(target.version.clone());
+
+        // Switching straight from another project leaves it first
+        self.leaving = self.switched_from(&project_root(path, &target));
+
+        // 2. Try to find a plugin with this version installed
+        let preferred = target
+            .plugin
+            .as_deref()
+            .map(|name| self.registry.preferring(name));
+        let registry = preferred.as_ref().unwrap_or(self.registry);
+        let lookup = registry.find_plugin_with_version(&target.version);
+        self.check_deadline()?;
+        self.check_support(&target.version)?;
+        self.check_engines(&target)?;
+
+        // Save the pre-anvs environment before the first switch in this shell
+        if self.config.leave_action == LeaveAction::Restore {
+            self.capture_environment();
+        }
+
+        let requirements = self.package_manager_requirements(path);
+        let vars = self.hook_vars(path, &target);
+
+        match lookup {
+            Ok(Some(plugin)) => {
+                // Version is already installed - activate it
+                let (plugin, cmd) = self.activation_command(registry, plugin, &target.version)?;
+                let vars = vars.with_plugin(plugin.name());
+                self.run_hooks(HookStage::PreActivate, &vars)?;
+                self.activate_with_command(&plugin, &target.version, &cmd)?;
+                self.setup_package_manager(&requirements, Some(&plugin), &target.version)?;
+                self.check_abi(path, plugin.node_dir(&target.version), &target.version)?;
+                self.run_hooks(HookStage::PostActivate, &vars)?;
+            }
+            Ok(None) => {
+                // Version not installed - handle auto-install
+                let project = project_root(path, &target);
+                if let Some(plugin) =
+                    self.handle_missing_version(registry, &target, &project, &vars)?
+                {
+                    self.setup_package_manager(&requirements, None, &target.version)?;
+                    self.check_abi(path, None, &target.version)?;
+                    self.run_hooks(HookStage::PostActivate, &vars.with_plugin(plugin.name()))?;
+                }
             }
             Err(e) => {
-                return Err(ActivationError::PluginError {
-                    plugin: "version_file".to_string(),
-                    source: e,
-                });
+                return Err(ActivationError::plugin("unknown", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the version file for `path` and resolves the version to activate
+    ///
+    /// Writes nothing to the shell, so `anvs which` and shims can reuse it.
+    ///
+    /// # Returns
+    /// `None` if no version file is found
+    pub fn find_target(&self, path: &Path) -> ActivationResult<Option<ActivationTarget>> {
+        let strategy = self.config.workspace_strategy;
+        let pins = match WorkspacePins::locate(path, self.config) {
+            Ok(Some(pins)) => pins,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                return Err(ActivationError::plugin("version_file", e));
             }
         };
+        let version_file = pins.selected().clone();
 
         info!("Found version file: {}", version_file.path.display());
         info!("Node.js version: {}", version_file.version);
 
-        // Resolve semver range if from package.json
-        let version_to_use = if version_file.source == VersionFileSource::PackageJson {
-            // Use smart engines resolver for package.json engines.node
-            let engines_resolver = EnginesResolver::new(self.registry, self.config);
-            match engines_resolver.resolve_smart(&version_file.version) {
-                Ok(smart_version) => {
-                    if smart_version != version_file.version {
-                        info!(
-                            "Smart engines resolution: '{}' → '{}'",
-                            version_file.version, smart_version
-                        );
-                    }
-                    smart_version
-                }
+        // Resolve semver range if from package.json, and aliases like lts/hydrogen
+        let version = if version_file.source == VersionFileSource::PackageJson {
+            self.resolve_requirement(&version_file.version)
+        } else {
+            self.resolve_alias(&version_file.version)
+                .unwrap_or_else(|| version_file.version.clone())
+        };
+
+        if strategy == WorkspaceStrategy::RootSatisfiesNearest && pins.root.is_some() {
+            self.check_workspace_pin(&version, &version_file, &pins.nearest)?;
+        }
+
+        let plugin = path
+            .canonicalize()
+            .ok()
+            .and_then(|dir| self.config.rule_for(&dir)?.plugin.clone());
+
+        Ok(Some(ActivationTarget {
+            version_file,
+            version,
+            plugin,
+        }))
+    }
+
+    /// Fails if the workspace root's version doesn't satisfy the nearest range
+    fn check_workspace_pin(
+        &self,
+        version: &str,
+        root: &VersionFile,
+        nearest: &VersionFile,
+    ) -> ActivationResult<()> {
+        // Aliases like lts/* aren't ranges, so there's nothing to check
+        let Ok(range) = VersionRange::parse(&nearest.version) else {
+            return Ok(());
+        };
+        if scan::version_satisfies(version, &range, self.index()) {
+            return Ok(());
+        }
+
+        Err(ActivationError::WorkspaceMismatch {
+            version: version.to_string(),
+            root: root.path.display().to_string(),
+            nearest: nearest.path.display().to_string(),
+            range: nearest.version.clone(),
+        })
+    }
+
+    /// Release index, loaded on first use
+    fn index(&self) -> &ReleaseIndex {
+        self.index.get_or_init(ReleaseIndex::load_default)
+    }
+
+    /// Resolves an alias (`node`, `lts/*`, `lts/-1`, `lts/hydrogen`) to a version
+    ///
+    /// Prefers the newest installed version the alias covers, and otherwise
+    /// the newest release in the release index.
+    ///
+    /// # Returns
+    /// `None` if `version` is not an alias
+    fn resolve_alias(&self, version: &str) -> Option<String> {
+        let range = self.index().alias_range(version)?;
+
+        let installed = self
+            .registry
+            .plugins()
+            .iter()
+            .filter_map(|plugin| match plugin.list_versions() {
+                Ok(versions) => Some(versions),
                 Err(e) => {
-                    warn!("Failed smart engines resolution: {e}, falling back to semver resolver");
-                    // Fallback to original semver resolution
-                    if let Some(plugin) = self.registry.plugins().first() {
-                        let resolver = SemverResolver::new(plugin.as_ref());
-                        match resolver.resolve(&version_file.version) {
-                            Ok(resolved) => {
-                                if resolved != version_file.version {
-                                    info!(
-                                        "Fallback semver resolution: '{}' → '{}'",
-                                        version_file.version, resolved
-                                    );
-                                }
-                                resolved
-                            }
-                            Err(e) => {
-                                warn!("Failed fallback semver resolution: {e}");
-                                version_file.version.clone()
-                            }
-                        }
-                    } else {
-                        version_file.version.clone()
-                    }
+                    warn!("Failed to list versions from {}: {e}", plugin.name());
+                    None
                 }
-            }
-        } else {
-            version_file.version.clone()
+            })
+            .flatten()
+            .filter_map(|installed| parse_version(&installed))
+            .filter(|installed| range.matches(installed))
+            .max();
+
+        let resolved = match installed {
+            Some(installed) => installed.to_string(),
+            None => self.index().resolve_alias(version)?,
         };
+        info!("Resolved alias '{version}' → '{resolved}'");
+        Some(resolved)
+    }
 
-        // 2. Try to find a plugin with this version installed
-        match self.registry.find_plugin_with_version(&version_to_use) {
-            Ok(Some(plugin)) => {
-                // Version is already installed - activate it
-                self.activate_existing_version(&plugin, &version_to_use)?;
+    /// Fails if the activation deadline has passed
+    ///
+    /// Called before anything is written to the shell, so a slow version
+    /// manager leaves the shell untouched.
+    fn check_deadline(&self) -> ActivationResult<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => {
+                warn!("Activation deadline exceeded");
+                Err(ActivationError::DeadlineExceeded {
+                    timeout_ms: self
+                        .config
+                        .activation_timeout()
+                        .map_or(0, |timeout| timeout.as_millis() as u64),
+                })
             }
-            Ok(None) => {
-                // Version not installed - handle auto-install
-                self.handle_missing_version(&version_to_use)?;
+            _ => Ok(()),
+        }
+    }
+
+    /// Warns about end-of-life, near-EOL and outdated versions
+    ///
+    /// Fails with `EndOfLife` instead of warning when `eol_severity` is `block`.
+    fn check_support(&self, version: &str) -> ActivationResult<()> {
+        if self.config.eol_severity == EolSeverity::Ignore {
+            return Ok(());
+        }
+        let Some(parsed) = parse_version(version) else {
+            return Ok(());
+        };
+        let today = chrono::Local::now().date_naive();
+        let Some(advisory) = self.index().advisory(&parsed, today) else {
+            return Ok(());
+        };
+
+        match advisory {
+            Advisory::EndOfLife { eol, .. } if self.config.eol_severity == EolSeverity::Block => {
+                Err(ActivationError::EndOfLife {
+                    version: version.to_string(),
+                    eol: eol.to_string(),
+                })
             }
-            Err(e) => {
-                return Err(ActivationError::PluginError {
-                    plugin: "unknown".to_string(),
-                    source: e,
-                });
+            advisory => {
+                self.warning(&advisory.message(version));
+                Ok(())
             }
         }
+    }
+
+    /// Checks the version against package.json engines.node next to the version file
+    ///
+    /// Prints a diagnostic, or fails with `EnginesMismatch` when
+    /// `version_check` is `strict`.
+    fn check_engines(&self, target: &ActivationTarget) -> ActivationResult<()> {
+        let file = &target.version_file;
+        if self.config.version_check == VersionCheck::Off
+            || matches!(
+                file.source,
+                VersionFileSource::PackageJson | VersionFileSource::Rule
+            )
+        {
+            return Ok(());
+        }
+        let Some(dir) = file.path.parent() else {
+            return Ok(());
+        };
+        let Some(engines) = scan::unsatisfied_engines(dir, &target.version, self.index()) else {
+            return Ok(());
+        };
 
+        let error = ActivationError::EnginesMismatch {
+            version: target.version.clone(),
+            file: file.path.display().to_string(),
+            range: engines.version,
+        };
+        if self.config.version_check == VersionCheck::Strict {
+            return Err(error);
+        }
+        self.warning(&error.to_string());
         Ok(())
     }
 
+    /// Resolves a package.json engines.node requirement to a version
+    ///
+    /// Serves the result from the resolution cache when available, so repeated
+    /// activations don't spawn the version manager.
+    fn resolve_requirement(&self, requirement: &str) -> String {
+        let Some(cache) = &self.cache else {
+            return self.resolve_requirement_uncached(requirement);
+        };
+
+        // The smart resolver prefers the default/current version, so include it in the key
+        let active_node = self
+            .active_node()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let key = format!(
+            "{requirement}|{}|{active_node}|{}|{:?}|{:?}",
+            self.config.default_version.as_deref().unwrap_or(""),
+            self.index().updated().unwrap_or(""),
+            self.config.remote_version_policy,
+            self.config.range_strategy
+        );
+        let fingerprint = self.registry.fingerprint();
+
+        if let Some(version) = cache.get_resolution(&key, &fingerprint) {
+            info!("Cached engines resolution: '{requirement}' → '{version}'");
+            return version;
+        }
+
+        let version = self.resolve_requirement_uncached(requirement);
+        if let Err(e) = cache.put_resolution(&key, &fingerprint, &version) {
+            warn!("Failed to cache resolution for '{requirement}': {e}");
+        }
+
+        version
+    }
+
+    /// Resolves a requirement using the engines resolver (falls back to semver resolver)
+    fn resolve_requirement_uncached(&self, requirement: &str) -> String {
+        // Use smart engines resolver for package.json engines.node
+        let mut engines_resolver =
+            EnginesResolver::new(self.registry, self.config).with_index(self.index());
+        if let Some(shell) = &self.shell {
+            engines_resolver = engines_resolver.with_current_version(shell.current_version.clone());
+        }
+        match engines_resolver.resolve_smart(requirement) {
+            Ok(smart_version) => {
+                if smart_version != requirement {
+                    info!("Smart engines resolution: '{requirement}' → '{smart_version}'");
+                }
+                smart_version
+            }
+            Err(e) => {
+                warn!("Failed smart engines resolution: {e}, falling back to semver resolver");
+                // Fallback to original semver resolution
+                if let Some(plugin) = self.registry.plugins().first() {
+                    let resolver = SemverResolver::new(plugin.as_ref())
+                        .with_policy(self.config.remote_version_policy);
+                    match resolver.resolve(requirement) {
+                        Ok(resolved) => {
+                            if resolved != requirement {
+                                info!("Fallback semver resolution: '{requirement}' → '{resolved}'");
+                            }
+                            resolved
+                        }
+                        Err(e) => {
+                            warn!("Failed fallback semver resolution: {e}");
+                            requirement.to_string()
+                        }
+                    }
+                } else {
+                    requirement.to_string()
+                }
+            }
+        }
+    }
+
     /// Activates an already-installed version
     fn activate_existing_version(
         &mut self,
         plugin: &Arc<dyn VersionManagerPlugin>,
         version: &str,
     ) -> ActivationResult<()> {
-        info!("Using plugin: {}", plugin.name());
-
         let cmd = plugin
             .activate_command(version)
-            .map_err(|e| ActivationError::PluginError {
-                plugin: plugin.name().to_string(),
-                source: e,
-            })?;
+            .map_err(|e| ActivationError::plugin(plugin.name(), e))?;
+        self.activate_with_command(plugin, version, &cmd)
+    }
+
+    /// Command activating `version` with `plugin`, falling back to the other
+    /// plugins in `registry` that have the version installed
+    ///
+    /// The activate commands are chained with `||`, so the shell tries the
+    /// next plugin if one fails. A plugin that can't generate its command is
+    /// left out of the chain.
+    ///
+    /// # Returns
+    /// The first plugin in the chain, and the chained command
+    fn activation_command(
+        &self,
+        registry: &PluginRegistry,
+        plugin: Arc<dyn VersionManagerPlugin>,
+        version: &str,
+    ) -> ActivationResult<(Arc<dyn VersionManagerPlugin>, String)> {
+        let mut chain: Vec<(Arc<dyn VersionManagerPlugin>, String)> = Vec::new();
+        let mut error = None;
+        let mut remaining = registry.without(plugin.name());
+        let mut next = Some(plugin);
+
+        while let Some(plugin) = next {
+            remaining = remaining.without(plugin.name());
+            match plugin.activate_command(version) {
+                Ok(cmd) => chain.push((plugin, cmd)),
+                Err(e) => {
+                    let e = ActivationError::plugin(plugin.name(), e);
+                    if chain.is_empty() {
+                        self.warning(&format!(
+                            "{} can't activate {version} ({})",
+                            plugin.name(),
+                            fallback_reason(&e)
+                        ));
+                    }
+                    error.get_or_insert(e);
+                }
+            }
+            next = remaining
+                .find_plugin_with_version(version)
+                .unwrap_or_else(|e| {
+                    debug!("Not looking for more plugins with {version}: {e:#}");
+                    None
+                });
+        }
+
+        let Some((first, _)) = chain.first() else {
+            return Err(error.unwrap_or(ActivationError::NoPluginsAvailable));
+        };
+        let first = Arc::clone(first);
+        let commands: Vec<String> = chain.into_iter().map(|(_, cmd)| cmd).collect();
+        Ok((first, commands.join(" || ")))
+    }
+
+    /// Writes the activate command `cmd` generated by `plugin`
+    fn activate_with_command(
+        &mut self,
+        plugin: &Arc<dyn VersionManagerPlugin>,
+        version: &str,
+        cmd: &str,
+    ) -> ActivationResult<()> {
+        info!("Using plugin: {}", plugin.name());
+        self.record.plugin = Some(plugin.name().to_string());
+        self.record.version = Some(version.to_string());
 
         info!("Activation command: {cmd}");
 
         // Write command to FD:3
-        self.command_writer.write_command(&cmd)?;
+        self.emit(cmd)?;
 
         // Print success message to stdout
         output::switched(version, plugin.name());
@@ -163,26 +655,272 @@ impl<'a> Orchestrator<'a> {
         Ok(())
     }
 
+    /// Writes a command to the shell and records it in the activation history
+    ///
+    /// Pending environment snapshot commands are written first.
+    fn emit(&mut self, cmd: &str) -> std::io::Result<()> {
+        let snapshot = std::mem::take(&mut self.snapshot_commands);
+        for cmd in snapshot.iter().map(String::as_str).chain([cmd]) {
+            self.sink.write_command(cmd)?;
+            self.record.commands.push(cmd.to_string());
+        }
+        Ok(())
+    }
+
+    /// `node` on the shell's PATH
+    fn active_node(&self) -> Option<PathBuf> {
+        match &self.shell {
+            Some(shell) => shell.node_path.clone(),
+            None => which::which("node").ok(),
+        }
+    }
+
+    /// Version of the shell's active `node`
+    fn current_version(&self) -> Option<String> {
+        match &self.shell {
+            Some(shell) => shell.current_version.clone(),
+            None => hooks::active_node_version(),
+        }
+    }
+
+    /// Prints a warning, keeping it for `messages`
+    fn warning(&self, message: &str) {
+        output::warning(message);
+        self.messages.borrow_mut().push(message.to_string());
+    }
+
+    /// Prints a notice, keeping it for `messages`
+    fn info(&self, message: &str) {
+        output::info(message);
+        self.messages.borrow_mut().push(message.to_string());
+    }
+
+    /// Prints the install message, keeping it for `messages`
+    fn installing(&self, version: &str, plugin: &str) {
+        output::installing(version, plugin);
+        self.messages
+            .borrow_mut()
+            .push(format!("Installing Node.js {version} using {plugin}"));
+    }
+
+    /// Whether any user or project hooks are configured
+    fn has_hooks(&self) -> bool {
+        !self.config.hooks.is_empty() || self.config.project_hooks.is_some()
+    }
+
+    /// Hooks of the project named by the shell's active key, i.e. the one being left
+    ///
+    /// # Returns
+    /// The project's directory, and its .anvs.yaml hooks if it has any
+    fn left_project(&self) -> (Option<PathBuf>, Option<ProjectHooks>) {
+        let Some(root) = self.active_key.as_deref().and_then(hooks::key_project_root) else {
+            return (None, None);
+        };
+        let project_hooks = match Config::load_project_hooks(&root) {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                warn!("Failed to load config of {}: {e:#}", root.display());
+                None
+            }
+        };
+        (Some(root), project_hooks)
+    }
+
+    /// The project the shell is switching away from, if it isn't `project`
+    ///
+    /// Only set when there are on_leave hooks to run for it.
+    fn switched_from(&self, project: &Path) -> Option<(PathBuf, Option<ProjectHooks>)> {
+        let (root, project_hooks) = self.left_project();
+        let root = root?;
+        let canonical = |dir: &Path| dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        if canonical(&root) == canonical(project) {
+            return None;
+        }
+        let project_on_leave = project_hooks
+            .as_ref()
+            .is_some_and(|project| !project.hooks.on_leave.is_empty());
+        if self.config.hooks.on_leave.is_empty() && !project_on_leave {
+            return None;
+        }
+        Some((root, project_hooks))
+    }
+
+    /// Template variables for hooks around activating `target`
+    fn hook_vars(&self, path: &Path, target: &ActivationTarget) -> HookVars {
+        if !self.has_hooks() && self.leaving.is_none() {
+            return HookVars::default();
+        }
+        HookVars {
+            version: target.version.clone(),
+            previous_version: self.current_version().unwrap_or_default(),
+            project_root: project_root(path, target).display().to_string(),
+            plugin: String::new(),
+        }
+    }
+
+    /// Writes the hooks for `stage` to the shell
+    ///
+    /// The on_leave hooks of a project being switched away from go before
+    /// the first pre_activate hooks.
+    fn run_hooks(&mut self, stage: HookStage, vars: &HookVars) -> ActivationResult<()> {
+        if stage == HookStage::PreActivate {
+            if let Some((root, project_hooks)) = self.leaving.take() {
+                let vars = HookVars {
+                    project_root: root.display().to_string(),
+                    ..vars.clone()
+                };
+                self.run_hooks_for(project_hooks.as_ref(), HookStage::OnLeave, &vars)?;
+            }
+        }
+        if !self.has_hooks() {
+            return Ok(());
+        }
+        let project = self.config.project_hooks.clone();
+        self.run_hooks_for(project.as_ref(), stage, vars)
+    }
+
+    /// Writes the user's and `project`'s hooks for `stage` to the shell
+    fn run_hooks_for(
+        &mut self,
+        project: Option<&ProjectHooks>,
+        stage: HookStage,
+        vars: &HookVars,
+    ) -> ActivationResult<()> {
+        self.ran_hooks = true;
+        let trust = self
+            .trust
+            .get_or_init(|| TrustStore::load_default().unwrap_or_default());
+        for cmd in hooks::commands(&self.config.hooks, project, trust, stage, vars) {
+            info!("Hook command ({}): {cmd}", stage.key());
+            self.emit(&cmd)?;
+        }
+        Ok(())
+    }
+
+    /// Compares the project's node_modules with the activated version's ABI
+    ///
+    /// Warns on a mismatch, or with `abi_check: rebuild` writes the rebuild
+    /// command after the activation command.
+    fn check_abi(
+        &mut self,
+        path: &Path,
+        node_dir: Option<PathBuf>,
+        version: &str,
+    ) -> ActivationResult<()> {
+        if self.config.abi_check == AbiCheck::Off {
+            return Ok(());
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let Some(mismatch) = abi::check(&path, version, node_dir.as_deref(), self.cache.as_deref())
+        else {
+            return Ok(());
+        };
+
+        if self.config.abi_check == AbiCheck::Rebuild {
+            let cmd = mismatch.rebuild_command();
+            info!("Rebuild command: {cmd}");
+            self.emit(&cmd)?;
+            self.info(&format!(
+                "Rebuilding native modules for NODE_MODULE_VERSION {}",
+                mismatch.active
+            ));
+        } else {
+            self.warning(&mismatch.message());
+        }
+        Ok(())
+    }
+
+    /// Package manager requirements for `path`, unless `package_manager` is off
+    fn package_manager_requirements(&self, path: &Path) -> Requirements {
+        if self.config.package_manager == PackageManagerMode::Off {
+            return Requirements::default();
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        Requirements::find(&path)
+    }
+
+    /// Checks the bundled npm and prepares the project's package manager
+    ///
+    /// npm is only checked when the plugin knows where `version` is
+    /// installed. With `package_manager: corepack`, pnpm and yarn pins are
+    /// prepared by a corepack command written after the activation command.
+    fn setup_package_manager(
+        &mut self,
+        requirements: &Requirements,
+        plugin: Option<&Arc<dyn VersionManagerPlugin>>,
+        version: &str,
+    ) -> ActivationResult<()> {
+        if requirements.is_empty() {
+            return Ok(());
+        }
+
+        let node_dir = plugin.and_then(|plugin| plugin.node_dir(version));
+        match node_dir
+            .as_deref()
+            .and_then(package_manager::bundled_npm_version)
+        {
+            Some(npm) => {
+                for problem in requirements.npm_problems(&npm) {
+                    self.warning(&problem);
+                }
+            }
+            None => debug!("Bundled npm version unknown for {version}, skipping npm check"),
+        }
+
+        if self.config.package_manager != PackageManagerMode::Corepack {
+            return Ok(());
+        }
+        let Some(spec) = requirements
+            .package_manager
+            .as_ref()
+            .filter(|spec| spec.uses_corepack())
+        else {
+            return Ok(());
+        };
+        if !package_manager::bundles_corepack(version) {
+            self.warning(&format!(
+                "Node.js {version} doesn't include corepack, so {} wasn't prepared",
+                spec.raw
+            ));
+            return Ok(());
+        }
+
+        let cmd = package_manager::corepack_command(spec, node_dir.as_deref());
+        info!("Package manager command: {cmd}");
+        self.emit(&cmd)?;
+        self.info(&format!("Preparing {} with corepack", spec.raw));
+
+        Ok(())
+    }
+
     /// Handles the case where the required version is not installed
     ///
-    /// This will be implemented in M4.2
-    fn handle_missing_version(&mut self, version: &str) -> ActivationResult<()> {
+    /// Answers to the install prompt are remembered for `project` when a
+    /// prompt state is set.
+    ///
+    /// # Returns
+    /// The plugin installing the version (`None` if the user declined)
+    fn handle_missing_version(
+        &mut self,
+        registry: &PluginRegistry,
+        target: &ActivationTarget,
+        project: &Path,
+        vars: &HookVars,
+    ) -> ActivationResult<Option<Arc<dyn VersionManagerPlugin>>> {
+        let version = target.version.as_str();
         info!("Version {version} not installed");
 
-        // Find first available plugin to use for installation
-        let plugin = self
-            .registry
-            .find_available_plugin()
-            .map_err(|e| ActivationError::PluginError {
-                plugin: "unknown".to_string(),
-                source: e,
-            })?
+        // Plugins to install with, in the order to try them
+        let candidates = self.install_candidates(registry, target.plugin.as_deref());
+        let plugin = candidates
+            .first()
+            .map(Arc::clone)
             .ok_or(ActivationError::NoPluginsAvailable)?;
 
         info!("Will use plugin {} for installation", plugin.name());
 
         // Check auto-install mode
-        match self.config.auto_install {
+        match self.auto_install_mode(registry, version) {
             AutoInstallMode::Never => {
                 // Show error and exit
                 Err(ActivationError::VersionNotInstalled {
@@ -199,66 +937,266 @@ impl<'a> Orchestrator<'a> {
             }
             AutoInstallMode::Always => {
                 // Install without prompting
-                self.install_and_activate(&plugin, version)
+                self.install_and_activate(&candidates, version, vars)
+                    .map(Some)
             }
             AutoInstallMode::Prompt => {
-                // Prompt user for confirmation
-                let message = output::install_prompt(version, plugin.name());
+                let project = project
+                    .canonicalize()
+                    .unwrap_or_else(|_| project.to_path_buf());
+                let now = chrono::Local::now().fixed_offset();
+                let remembered = self
+                    .prompt_state
+                    .as_ref()
+                    .and_then(|state| state.remembered(&project, version, now));
+
+                let answer = match remembered {
+                    Some(Remembered::Always) => {
+                        debug!("Installs are always accepted in {}", project.display());
+                        InstallAnswer::Yes
+                    }
+                    Some(Remembered::Never | Remembered::Declined) => {
+                        self.info(&format!(
+                            "Install of {version} was declined for this project. \
+                             Run 'anvs prompt reset' to be asked again."
+                        ));
+                        self.show_version_mismatch(version)?;
+                        return Ok(None);
+                    }
+                    None => {
+                        // Prompt user for confirmation
+                        let message = output::install_prompt(version, plugin.name());
+                        let answer = self
+                            .user_prompt
+                            .confirm_install(&message)
+                            .map_err(ActivationError::IoError)?;
+                        self.remember_answer(&project, version, answer, now);
+                        answer
+                    }
+                };
 
-                match self.user_prompt.confirm(&message) {
-                    Ok(true) => {
+                match answer {
+                    InstallAnswer::Yes | InstallAnswer::AlwaysForProject => {
                         // User confirmed - install
-                        self.install_and_activate(&plugin, version)
+                        self.install_and_activate(&candidates, version, vars)
+                            .map(Some)
                     }
-                    Ok(false) => {
+                    InstallAnswer::No | InstallAnswer::NeverForProject => {
                         // User declined - show mismatch
-                        output::info("Install declined.");
+                        self.info("Install declined.");
                         self.show_version_mismatch(version)?;
-                        Ok(())
+                        Ok(None)
                     }
-                    Err(e) => Err(ActivationError::IoError(e)),
                 }
             }
         }
     }
 
-    /// Installs and activates a Node.js version
+    /// Saves an install prompt answer, logging (not returning) failures
+    fn remember_answer(
+        &mut self,
+        project: &Path,
+        version: &str,
+        answer: InstallAnswer,
+        now: chrono::DateTime<chrono::FixedOffset>,
+    ) {
+        let Some(state) = self.prompt_state.as_mut() else {
+            return;
+        };
+        if let Err(e) = state.remember(project, version, answer, now) {
+            warn!("Failed to save install prompt answer: {e:#}");
+        }
+    }
+
+    /// Auto-install mode for `version`: the first matching
+    /// `auto_install_rules` entry, or `auto_install`
+    fn auto_install_mode(&self, registry: &PluginRegistry, version: &str) -> AutoInstallMode {
+        if self.config.auto_install_rules.is_empty() {
+            return self.config.auto_install;
+        }
+
+        let installed: Vec<String> = registry
+            .plugins()
+            .iter()
+            .filter_map(|plugin| plugin.list_versions().ok())
+            .flatten()
+            .collect();
+        let today = chrono::Local::now().date_naive();
+        let facts = VersionFacts::gather(version, &installed, self.index(), today);
+        debug!("Auto-install facts for {version}: {facts:?}");
+
+        install_policy::mode_for(self.config, &facts)
+    }
+
+    /// Available plugins to install with, in the order to try them
+    ///
+    /// A path rule's plugin comes first, then `install_plugin`, then the
+    /// others in priority order.
+    fn install_candidates(
+        &self,
+        registry: &PluginRegistry,
+        rule_plugin: Option<&str>,
+    ) -> Vec<Arc<dyn VersionManagerPlugin>> {
+        let mut candidates = registry.available_plugins();
+        if let Some(name) = rule_plugin.or(self.config.install_plugin.as_deref()) {
+            candidates.sort_by_key(|plugin| plugin.name() != name);
+        }
+        candidates
+    }
+
+    /// Installs and activates a Node.js version with the first of
+    /// `candidates` that succeeds
+    ///
+    /// A plugin that can't generate its commands falls back to the next one.
+    /// With `install_mode: process`, so does a plugin whose install fails.
+    /// With `install_mode: shell`, the shell runs each candidate's install in
+    /// turn until one succeeds.
     ///
-    /// This will be fully implemented in M4.2
+    /// # Returns
+    /// The plugin that installed the version (for the shell, the first one tried)
     fn install_and_activate(
+        &mut self,
+        candidates: &[Arc<dyn VersionManagerPlugin>],
+        version: &str,
+        vars: &HookVars,
+    ) -> ActivationResult<Arc<dyn VersionManagerPlugin>> {
+        if self.config.install_mode == InstallMode::Shell {
+            return self.install_in_shell(candidates, version, vars);
+        }
+
+        let mut candidates = candidates.iter().peekable();
+        while let Some(plugin) = candidates.next() {
+            let error = match self.install_with(plugin, version, vars) {
+                Ok(()) => return Ok(Arc::clone(plugin)),
+                Err(e) => e,
+            };
+            match candidates.peek() {
+                Some(next) if error.allows_fallback() => {
+                    self.warning(&format!(
+                        "Installing {version} with {} failed ({}), trying {}",
+                        plugin.name(),
+                        fallback_reason(&error),
+                        next.name()
+                    ));
+                }
+                _ => return Err(error),
+            }
+        }
+        Err(ActivationError::NoPluginsAvailable)
+    }
+
+    /// Installs and activates a Node.js version with `plugin` in a child process
+    fn install_with(
         &mut self,
         plugin: &Arc<dyn VersionManagerPlugin>,
         version: &str,
+        vars: &HookVars,
     ) -> ActivationResult<()> {
-        // Generate install command
-        let install_cmd =
-            plugin
-                .install_command(version)
-                .map_err(|e| ActivationError::PluginError {
-                    plugin: plugin.name().to_string(),
-                    source: e,
-                })?;
-
-        // Generate activate command
-        let activate_cmd =
-            plugin
-                .activate_command(version)
-                .map_err(|e| ActivationError::PluginError {
-                    plugin: plugin.name().to_string(),
-                    source: e,
-                })?;
+        let (install_cmd, activate_cmd) = install_commands(plugin, version)?;
 
+        self.record.plugin = Some(plugin.name().to_string());
         info!("Install command: {install_cmd}");
         info!("Activate command: {activate_cmd}");
 
-        // Write both commands to FD:3 (chained with &&)
-        let combined_cmd = format!("{install_cmd} && {activate_cmd}");
-        self.command_writer.write_command(&combined_cmd)?;
+        self.install_then_activate(plugin, version, &install_cmd, &activate_cmd, vars)
+    }
+
+    /// Writes the install and activate commands of `candidates` to the shell
+    ///
+    /// Each candidate's commands are chained with `&&`, and the candidates
+    /// with `||`, so the shell moves on to the next one if an install fails.
+    /// `pre_activate` hooks are written before the install commands.
+    fn install_in_shell(
+        &mut self,
+        candidates: &[Arc<dyn VersionManagerPlugin>],
+        version: &str,
+        vars: &HookVars,
+    ) -> ActivationResult<Arc<dyn VersionManagerPlugin>> {
+        let mut chain = Vec::new();
+        let mut error = None;
+        for plugin in candidates {
+            match install_commands(plugin, version) {
+                Ok((install_cmd, activate_cmd)) => {
+                    chain.push((plugin, format!("{install_cmd} && {activate_cmd}")));
+                }
+                Err(e) if e.allows_fallback() => {
+                    self.warning(&format!(
+                        "{} can't install {version} ({})",
+                        plugin.name(),
+                        fallback_reason(&e)
+                    ));
+                    error.get_or_insert(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let Some((plugin, _)) = chain.first() else {
+            return Err(error.unwrap_or(ActivationError::NoPluginsAvailable));
+        };
+        let plugin = Arc::clone(plugin);
+
+        // Write all commands to FD:3 as one chain
+        let combined_cmd = match chain.as_slice() {
+            [(_, cmd)] => cmd.clone(),
+            _ => chain
+                .iter()
+                .map(|(_, cmd)| format!("{{ {cmd}; }}"))
+                .collect::<Vec<_>>()
+                .join(" || "),
+        };
+
+        self.record.plugin = Some(plugin.name().to_string());
+        info!("Install command: {combined_cmd}");
+
+        self.run_hooks(HookStage::PreActivate, &vars.with_plugin(plugin.name()))?;
+        self.emit(&combined_cmd)?;
 
         // Print message to stdout
-        output::installing(version, plugin.name());
+        self.installing(version, plugin.name());
 
-        Ok(())
+        Ok(plugin)
+    }
+
+    /// Installs in a child process, then writes only the activate command
+    ///
+    /// Nothing reaches the shell unless the install succeeds, so a failed or
+    /// cancelled install leaves the previous version active.
+    fn install_then_activate(
+        &mut self,
+        plugin: &Arc<dyn VersionManagerPlugin>,
+        version: &str,
+        install_cmd: &str,
+        activate_cmd: &str,
+        vars: &HookVars,
+    ) -> ActivationResult<()> {
+        self.installing(version, plugin.name());
+        let outcome = self
+            .installer
+            .install(plugin.as_ref(), version)
+            .map_err(|e| ActivationError::plugin(plugin.name(), e))?;
+
+        match outcome {
+            InstallOutcome::Installed => {
+                info!("Installed {version} with {}", plugin.name());
+                self.run_hooks(HookStage::PreActivate, &vars.with_plugin(plugin.name()))?;
+                self.emit(activate_cmd)?;
+                output::switched(version, plugin.name());
+                Ok(())
+            }
+            InstallOutcome::Failed { code, output } => Err(ActivationError::InstallFailed {
+                version: version.to_string(),
+                code,
+                output,
+                command: install_cmd.to_string(),
+                previous: self.current_version(),
+            }),
+            InstallOutcome::Cancelled => Err(ActivationError::InstallCancelled {
+                version: version.to_string(),
+                command: install_cmd.to_string(),
+                previous: self.current_version(),
+            }),
+        }
     }
 
     /// Shows version mismatch message
@@ -267,8 +1205,12 @@ impl<'a> Orchestrator<'a> {
     fn show_version_mismatch(&self, required_version: &str) -> ActivationResult<()> {
         use std::process::Command;
 
-        // Get current Node.js version
-        let output = Command::new("node").arg("--version").output();
+        // Get current Node.js version, from the shell's PATH
+        let mut command = Command::new("node");
+        if let Some(path) = self.shell.as_ref().and_then(|shell| shell.path.as_deref()) {
+            command.env("PATH", path);
+        }
+        let output = command.arg("--version").output();
 
         match output {
             Ok(output) if output.status.success() => {
@@ -288,22 +1230,106 @@ impl<'a> Orchestrator<'a> {
         Ok(())
     }
 
-    /// Activates the default Node.js version from the version manager
+    /// Queues commands that snapshot the current environment, if not yet captured
+    ///
+    /// They're written just before the activation's first command, so a failed
+    /// activation leaves the shell untouched.
+    fn capture_environment(&mut self) {
+        self.snapshot_commands = self.env_snapshot.capture_commands();
+        for cmd in &self.snapshot_commands {
+            debug!("Snapshot command: {cmd}");
+        }
+    }
+
+    /// Leaves the project: runs `on_leave` hooks, then switches to the default
+    /// version or restores the pre-anvs environment (`leave_action`)
+    ///
+    /// Restoring falls back to the default version if no snapshot exists
+    /// (e.g., the shell was started before `leave_action: restore` was configured).
+    fn leave_project(&mut self) -> ActivationResult<()> {
+        let restore = self.config.leave_action == LeaveAction::Restore;
+        if restore && !self.env_snapshot.is_captured() {
+            debug!("No environment snapshot found, falling back to default version");
+        }
+        let restore = restore && self.env_snapshot.is_captured();
+        let default = if restore {
+            None
+        } else {
+            self.find_default_version()?
+        };
+
+        let (project_root, project_hooks) = self.left_project();
+        if !self.config.hooks.on_leave.is_empty() || project_hooks.is_some() {
+            let version = match &default {
+                Some((_, version)) => Some(version.clone()),
+                None if restore => self
+                    .env_snapshot
+                    .saved("PATH")
+                    .and_then(hooks::node_version_on),
+                None => None,
+            };
+            let vars = HookVars {
+                version: version.unwrap_or_default(),
+                previous_version: self.current_version().unwrap_or_default(),
+                project_root: project_root
+                    .map(|root| root.display().to_string())
+                    .unwrap_or_default(),
+                plugin: default
+                    .as_ref()
+                    .map(|(plugin, _)| plugin.name().to_string())
+                    .unwrap_or_default(),
+            };
+            self.run_hooks_for(project_hooks.as_ref(), HookStage::OnLeave, &vars)?;
+        }
+
+        if restore {
+            return self.restore_previous_environment();
+        }
+        match default {
+            Some((plugin, version)) => {
+                self.check_deadline()?;
+                self.activate_existing_version(&plugin, &version)
+            }
+            None => {
+                // No default version found in any plugin - this is not an error
+                debug!("No default version configured in any plugin");
+                Ok(())
+            }
+        }
+    }
+
+    /// Restores the environment captured before anvs first activated in this shell
+    fn restore_previous_environment(&mut self) -> ActivationResult<()> {
+        info!("Restoring environment from before first activation");
+
+        for cmd in self.env_snapshot.restore_commands() {
+            debug!("Restore command: {cmd}");
+            self.emit(&cmd)?;
+        }
+
+        output::restored();
+
+        Ok(())
+    }
+
+    /// Finds the default Node.js version from the version managers
     ///
     /// This is called when leaving a project directory (no version file found)
     /// and use_default is enabled in config.
-    fn activate_default_version(&mut self) -> ActivationResult<()> {
-        debug!("Attempting to activate default version");
+    ///
+    /// # Returns
+    /// The first plugin with a default version, and that version
+    fn find_default_version(
+        &self,
+    ) -> ActivationResult<Option<(Arc<dyn VersionManagerPlugin>, String)>> {
+        debug!("Attempting to find default version");
 
         // Try each plugin in priority order to find one with a default version
         for plugin in self.registry.plugins() {
             // Check if plugin is available
             let available = plugin
                 .is_available()
-                .map_err(|e| ActivationError::PluginError {
-                    plugin: plugin.name().to_string(),
-                    source: e,
-                })?;
+                .map_err(|e| ActivationError::plugin(plugin.name(), e))?;
 
             if !available {
                 debug!("Plugin {} not available, skipping", plugin.name());
@@ -319,12 +1345,14 @@ impl<'a> Orchestrator<'a> {
                         plugin.name()
                     );
 
-                    // Activate the default version
-                    return self.activate_existing_version(plugin, &version);
+                    return Ok(Some((Arc::clone(plugin), version)));
                 }
                 Ok(None) => {
                     debug!("Plugin {} has no default version configured", plugin.name());
                 }
+                Err(e) if crate::plugins::CommandTimeout::find(&e).is_some() => {
+                    return Err(ActivationError::plugin(plugin.name(), e));
+                }
                 Err(e) => {
                     warn!(
                         "Failed to get default version from plugin {}: {}",
@@ -335,18 +1363,53 @@ impl<'a> Orchestrator<'a> {
             }
         }
 
-        // No default version found in any plugin - this is not an error
-        debug!("No default version configured in any plugin");
-        Ok(())
+        Ok(None)
+    }
+}
+
+/// Install and activate commands of `plugin` for `version`
+fn install_commands(
+    plugin: &Arc<dyn VersionManagerPlugin>,
+    version: &str,
+) -> ActivationResult<(String, String)> {
+    let install_cmd = plugin
+        .install_command(version)
+        .map_err(|e| ActivationError::plugin(plugin.name(), e))?;
+    let activate_cmd = plugin
+        .activate_command(version)
+        .map_err(|e| ActivationError::plugin(plugin.name(), e))?;
+    Ok((install_cmd, activate_cmd))
+}
+
+/// Why a plugin failed, for the message about falling back to the next one
+fn fallback_reason(error: &ActivationError) -> String {
+    match error {
+        ActivationError::PluginError { source, .. } => format!("{source:#}"),
+        other => other.to_string(),
+    }
+}
+
+/// Directory of the project `target` belongs to
+///
+/// Rules have no version file, so the project is the activated directory.
+fn project_root(path: &Path, target: &ActivationTarget) -> PathBuf {
+    match target.version_file.source {
+        VersionFileSource::Rule => path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        _ => target
+            .version_file
+            .path
+            .parent()
+            .map_or_else(|| path.to_path_buf(), Path::to_path_buf),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::activation::MockUserPrompt;
+    use crate::activation::{MockInstaller, MockUserPrompt};
     use crate::config::AutoInstallMode;
     use crate::plugins::MockPlugin;
+    use crate::shell::{CommandWriter, RecordingSink};
     use std::sync::Arc;
     use tempfile::TempDir;
 
@@ -357,6 +1420,7 @@ mod tests {
             version_files: vec![".nvmrc".to_string()],
             use_default: true,
             default_version: None,
+            ..Config::default()
         }
     }
 
@@ -429,6 +1493,309 @@ mod tests {
         // Command verification omitted (FD:3)
     }
 
+    #[test]
+    fn test_dry_run_records_install_chain() {
+        let config = create_test_config(AutoInstallMode::Prompt);
+        let mock_plugin = MockPlugin::new("mock").with_availability(true);
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_user_prompt(Box::new(crate::activation::DryRunPrompt));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+
+        assert_eq!(
+            writer.commands(),
+            ["mock install 18.20.0 && mock use 18.20.0"]
+        );
+    }
+
+    #[test]
+    fn test_process_install_activates_after_success() {
+        let config = Config {
+            install_mode: InstallMode::Process,
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let mock_plugin = MockPlugin::new("mock").with_availability(true);
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_installer(Box::new(MockInstaller::new(InstallOutcome::Installed)));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+
+        // Only the activate command reaches the shell
+        assert_eq!(writer.commands(), ["mock use 18.20.0"]);
+    }
+
+    #[test]
+    fn test_install_falls_back_to_next_plugin() {
+        let config = Config {
+            plugins: vec!["nvm".to_string(), "fnm".to_string()],
+            install_mode: InstallMode::Process,
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let registry = PluginRegistry::with_plugins(vec![
+            Arc::new(MockPlugin::new("nvm")),
+            Arc::new(MockPlugin::new("fnm")),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let activate = |config: &Config, installer: MockInstaller| {
+            let mut writer = RecordingSink::new();
+            let mut orchestrator = Orchestrator::new(config, &registry, &mut writer)
+                .with_installer(Box::new(installer));
+            let result = orchestrator.activate(temp_dir.path(), false);
+            drop(orchestrator);
+            (result, writer.commands().to_vec())
+        };
+
+        let installer = MockInstaller::new(InstallOutcome::Installed).failing_for("nvm");
+        let (result, commands) = activate(&config, installer);
+        assert!(result.is_ok());
+        assert_eq!(commands, ["fnm use 18.20.0"]);
+
+        // install_plugin goes first; the last failure is reported
+        let preferring_fnm = Config {
+            install_plugin: Some("fnm".to_string()),
+            ..config.clone()
+        };
+        let installer = MockInstaller::new(InstallOutcome::Installed).failing_for("fnm");
+        let (result, commands) = activate(&preferring_fnm, installer);
+        assert!(result.is_ok());
+        assert_eq!(commands, ["nvm use 18.20.0"]);
+
+        let installer = MockInstaller::new(InstallOutcome::Installed)
+            .failing_for("nvm")
+            .failing_for("fnm");
+        let (result, commands) = activate(&preferring_fnm, installer);
+        match result {
+            Err(ActivationError::InstallFailed { output, .. }) => {
+                assert_eq!(output, ["nvm install failed"]);
+            }
+            other => panic!("Expected InstallFailed, got {other:?}"),
+        }
+        assert!(commands.is_empty());
+
+        // Ctrl-C stops the whole chain
+        let (result, _) = activate(&config, MockInstaller::new(InstallOutcome::Cancelled));
+        assert!(matches!(
+            result,
+            Err(ActivationError::InstallCancelled { .. })
+        ));
+    }
+
+    #[test]
+    fn test_shell_install_prefers_install_plugin() {
+        let config = Config {
+            plugins: vec!["nvm".to_string(), "fnm".to_string()],
+            install_plugin: Some("fnm".to_string()),
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let registry = PluginRegistry::with_plugins(vec![
+            Arc::new(MockPlugin::new("nvm")),
+            Arc::new(MockPlugin::new("fnm").broken()),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+        // fnm can't generate its commands, so nvm installs
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+        assert_eq!(
+            writer.commands(),
+            ["nvm install 18.20.0 && nvm use 18.20.0"]
+        );
+    }
+
+    #[test]
+    fn test_shell_install_chains_fallbacks() {
+        let config = Config {
+            plugins: vec!["nvm".to_string(), "fnm".to_string()],
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let registry = PluginRegistry::with_plugins(vec![
+            Arc::new(MockPlugin::new("nvm")),
+            Arc::new(MockPlugin::new("fnm")),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut writer = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+
+        // The shell tries fnm if nvm's install (or use) fails
+        assert_eq!(
+            writer.commands(),
+            ["{ nvm install 18.20.0 && nvm use 18.20.0; } || { fnm install 18.20.0 && fnm use 18.20.0; }"]
+        );
+    }
+
+    #[test]
+    fn test_activate_falls_back_to_next_plugin() {
+        let config = create_test_config(AutoInstallMode::Never);
+        let registry = PluginRegistry::with_plugins(vec![
+            Arc::new(MockPlugin::new("nvm").with_version("18.20.0").broken()),
+            Arc::new(MockPlugin::new("fnm")),
+            Arc::new(MockPlugin::new("n").with_version("18.20.0")),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut writer = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+        assert_eq!(writer.commands(), ["n use 18.20.0"]);
+
+        // Every plugin with the version is tried in the shell, in priority order
+        let registry = PluginRegistry::with_plugins(vec![
+            Arc::new(MockPlugin::new("nvm").with_version("18.20.0")),
+            Arc::new(MockPlugin::new("fnm")),
+            Arc::new(MockPlugin::new("n").with_version("18.20.0")),
+        ]);
+        let mut writer = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+        assert_eq!(writer.commands(), ["nvm use 18.20.0 || n use 18.20.0"]);
+
+        // Without another plugin that has the version, the error stands
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(
+            MockPlugin::new("nvm").with_version("18.20.0").broken(),
+        )]);
+        let mut writer = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+        assert!(matches!(
+            orchestrator.activate(temp_dir.path(), false),
+            Err(ActivationError::PluginError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_process_install_failure_leaves_shell_unchanged() {
+        let config = Config {
+            install_mode: InstallMode::Process,
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let mock_plugin = MockPlugin::new("mock").with_availability(true);
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+        let failed = InstallOutcome::Failed {
+            code: Some(1),
+            output: vec!["Version '18.20.0' not found".to_string()],
+        };
+
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_installer(Box::new(MockInstaller::new(failed)));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let result = orchestrator.activate(temp_dir.path(), false);
+        drop(orchestrator);
+
+        match result {
+            Err(ActivationError::InstallFailed {
+                code,
+                output,
+                command,
+                ..
+            }) => {
+                assert_eq!(code, Some(1));
+                assert_eq!(output, vec!["Version '18.20.0' not found"]);
+                assert_eq!(command, "mock install 18.20.0");
+            }
+            other => panic!("Expected InstallFailed, got {other:?}"),
+        }
+        assert!(writer.commands().is_empty());
+    }
+
+    /// Activates a directory pinning `nvmrc` under a typical auto-install
+    /// policy, with 24.10.0 installed
+    fn activate_with_policy(
+        nvmrc: &str,
+        prompt: MockUserPrompt,
+    ) -> (ActivationResult<()>, Vec<String>) {
+        let config = Config {
+            auto_install_rules: serde_yaml::from_str(
+                "- eol: true\n  auto_install: never\n\
+                 - prerelease: true\n  auto_install: never\n\
+                 - installed_major: true\n  auto_install: always\n",
+            )
+            .unwrap(),
+            eol_severity: EolSeverity::Ignore,
+            ..create_test_config(AutoInstallMode::Prompt)
+        };
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["24.10.0".to_string()],
+            ..MockPlugin::new("mock")
+        };
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_user_prompt(Box::new(prompt));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), nvmrc).unwrap();
+
+        let result = orchestrator.activate(temp_dir.path(), false);
+        drop(orchestrator);
+        (result, writer.commands().to_vec())
+    }
+
+    #[test]
+    fn test_policy_installs_patch_of_installed_major() {
+        // The prompt would decline, so installing proves it wasn't asked
+        let (result, commands) = activate_with_policy("24.11.0", MockUserPrompt::new(vec![]));
+        assert!(result.is_ok());
+        assert_eq!(commands, ["mock install 24.11.0 && mock use 24.11.0"]);
+    }
+
+    #[test]
+    fn test_policy_prompts_for_new_major() {
+        let (result, commands) = activate_with_policy("26.1.0", MockUserPrompt::new(vec![true]));
+        assert!(result.is_ok());
+        assert_eq!(commands, ["mock install 26.1.0 && mock use 26.1.0"]);
+
+        let (result, commands) = activate_with_policy("26.1.0", MockUserPrompt::new(vec![false]));
+        assert!(result.is_ok());
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn test_policy_never_installs_eol_or_prerelease() {
+        for version in ["16.20.2", "26.0.0-rc.1"] {
+            let (result, commands) = activate_with_policy(version, MockUserPrompt::new(vec![true]));
+            match result {
+                Err(ActivationError::VersionNotInstalled { version: v, .. }) => {
+                    assert_eq!(v, version)
+                }
+                other => panic!("Expected VersionNotInstalled for {version}, got {other:?}"),
+            }
+            assert!(commands.is_empty());
+        }
+    }
+
     #[test]
     fn test_auto_install_prompt_yes() {
         // Test that user confirmation triggers install
@@ -463,17 +1830,56 @@ mod tests {
         let mut writer = CommandWriter::new().unwrap();
         let mock_prompt = MockUserPrompt::new(vec![false]);
 
-        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
-            .with_user_prompt(Box::new(mock_prompt));
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_user_prompt(Box::new(mock_prompt));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let result = orchestrator.activate(temp_dir.path(), false);
+        assert!(result.is_ok());
+
+        // Verify no commands written (user declined)
+        // No commands written (verified by logic)
+    }
+
+    #[test]
+    fn test_remembered_install_answers() {
+        let config = create_test_config(AutoInstallMode::Prompt);
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(MockPlugin::new("mock"))]);
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("prompts.json");
+        let app = temp_dir.path().join("app");
+        let other = temp_dir.path().join("other");
+        for project in [&app, &other] {
+            std::fs::create_dir(project).unwrap();
+            std::fs::write(project.join(".nvmrc"), "22.1.0").unwrap();
+        }
+
+        let activate = |project: &Path, prompt: MockUserPrompt| {
+            let mut writer = RecordingSink::new();
+            let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+                .with_user_prompt(Box::new(prompt))
+                .with_prompt_state(PromptState::load(&state_path));
+            orchestrator.activate(project, false).unwrap();
+            drop(orchestrator);
+            writer.commands().to_vec()
+        };
+        let install = ["mock install 22.1.0 && mock use 22.1.0"];
 
-        let temp_dir = TempDir::new().unwrap();
-        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+        // A decline isn't asked again (the prompt would say yes)
+        let no = MockUserPrompt::with_install_answers(vec![InstallAnswer::No]);
+        assert!(activate(&app, no).is_empty());
+        assert!(activate(&app, MockUserPrompt::new(vec![true])).is_empty());
+        assert_eq!(activate(&other, MockUserPrompt::new(vec![true])), install);
 
-        let result = orchestrator.activate(temp_dir.path(), false);
-        assert!(result.is_ok());
+        // "Always" installs without asking (the prompt would say no)
+        let always = MockUserPrompt::with_install_answers(vec![InstallAnswer::AlwaysForProject]);
+        assert_eq!(activate(&other, always), install);
+        assert_eq!(activate(&other, MockUserPrompt::new(vec![false])), install);
 
-        // Verify no commands written (user declined)
-        // No commands written (verified by logic)
+        PromptState::load(&state_path).reset(Some(&app)).unwrap();
+        assert_eq!(activate(&app, MockUserPrompt::new(vec![true])), install);
     }
 
     #[test]
@@ -580,26 +1986,489 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    const TEST_INDEX: &str = r#"{
+        "releases": [
+            {"version": "22.11.0", "lts": "Jod"},
+            {"version": "20.18.1", "lts": "Iron"},
+            {"version": "18.20.5", "lts": "Hydrogen"},
+            {"version": "18.12.0", "lts": "Hydrogen"}
+        ]
+    }"#;
+
     #[test]
     fn test_activate_with_lts_version() {
-        // Test LTS version strings
+        // LTS aliases resolve to the newest installed version of the line
         let config = create_test_config(AutoInstallMode::Never);
-        let mock_plugin = MockPlugin::new("mock")
-            .with_availability(true)
-            .with_version("lts/hydrogen");
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["18.19.0".to_string(), "v18.20.0".to_string()],
+            ..MockPlugin::new("mock").with_versions(&["18.19.0", "18.20.0"])
+        };
 
         let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
         let mut writer = CommandWriter::new().unwrap();
 
-        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_index(ReleaseIndex::from_json(TEST_INDEX).unwrap());
 
         let temp_dir = TempDir::new().unwrap();
         std::fs::write(temp_dir.path().join(".nvmrc"), "lts/hydrogen").unwrap();
 
+        let target = orchestrator.find_target(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(target.version, "18.20.0");
+        assert_eq!(target.version_file.version, "lts/hydrogen");
+
         let result = orchestrator.activate(temp_dir.path(), false);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_uninstalled_alias_resolves_from_index() {
+        let config = create_test_config(AutoInstallMode::Never);
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["18.20.0".to_string()],
+            ..MockPlugin::new("mock").with_version("18.20.0")
+        };
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = CommandWriter::new().unwrap();
+
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_index(ReleaseIndex::from_json(TEST_INDEX).unwrap());
+
+        let temp_dir = TempDir::new().unwrap();
+        let target_for = |alias: &str| {
+            std::fs::write(temp_dir.path().join(".nvmrc"), alias).unwrap();
+            orchestrator
+                .find_target(temp_dir.path())
+                .unwrap()
+                .unwrap()
+                .version
+        };
+
+        assert_eq!(target_for("lts/*"), "22.11.0");
+        assert_eq!(target_for("lts/-1"), "20.18.1");
+        // Installed versions win, like `nvm use node`
+        assert_eq!(target_for("node"), "18.20.0");
+        // Unknown aliases and plain versions pass through
+        assert_eq!(target_for("lts/unknown"), "lts/unknown");
+        assert_eq!(target_for("16"), "16");
+
+        // The concrete version is what gets installed
+        std::fs::write(temp_dir.path().join(".nvmrc"), "lts/iron").unwrap();
+        let result = orchestrator.activate(temp_dir.path(), false);
+        assert!(matches!(
+            result,
+            Err(ActivationError::VersionNotInstalled { ref version, .. }) if version == "20.18.1"
+        ));
+    }
+
+    #[test]
+    fn test_workspace_strategy() {
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(MockPlugin::new("mock"))]);
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let package = root.join("packages/api");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join(".nvmrc"), "18.20.0").unwrap();
+        std::fs::write(package.join(".nvmrc"), ">=20").unwrap();
+
+        let find = |workspace_strategy: WorkspaceStrategy| {
+            let config = Config {
+                workspace_strategy,
+                ..create_test_config(AutoInstallMode::Never)
+            };
+            let mut writer = CommandWriter::new().unwrap();
+            Orchestrator::new(&config, &registry, &mut writer)
+                .with_index(ReleaseIndex::from_json(TEST_INDEX).unwrap())
+                .find_target(&package)
+                .map(|target| target.unwrap().version)
+        };
+
+        assert_eq!(find(WorkspaceStrategy::Nearest).unwrap(), ">=20");
+        assert_eq!(find(WorkspaceStrategy::Root).unwrap(), "18.20.0");
+        assert!(matches!(
+            find(WorkspaceStrategy::RootSatisfiesNearest),
+            Err(ActivationError::WorkspaceMismatch { ref version, ref range, .. })
+                if version == "18.20.0" && range == ">=20"
+        ));
+
+        std::fs::write(package.join(".nvmrc"), ">=18").unwrap();
+        assert_eq!(
+            find(WorkspaceStrategy::RootSatisfiesNearest).unwrap(),
+            "18.20.0"
+        );
+    }
+
+    #[test]
+    fn test_path_rule_target() {
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(MockPlugin::new("mock"))]);
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = temp_dir.path().canonicalize().unwrap();
+
+        let config = Config {
+            rules: vec![crate::config::PathRule {
+                path: format!("{}/**", legacy.display()),
+                version: Some("lts/hydrogen".to_string()),
+                plugin: Some("fnm".to_string()),
+            }],
+            ..create_test_config(AutoInstallMode::Never)
+        };
+        let mut writer = CommandWriter::new().unwrap();
+        let orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_index(ReleaseIndex::from_json(TEST_INDEX).unwrap());
+
+        let target = orchestrator.find_target(&legacy).unwrap().unwrap();
+        assert_eq!(target.version_file.source, VersionFileSource::Rule);
+        assert_eq!(target.version, "18.20.5");
+        assert_eq!(target.plugin.as_deref(), Some("fnm"));
+    }
+
+    #[test]
+    fn test_version_check_against_engines() {
+        let mock_plugin = MockPlugin::new("mock").with_version("16.20.2");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "16.20.2").unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"engines": {"node": ">=18"}}"#,
+        )
+        .unwrap();
+
+        let activate = |version_check: VersionCheck| {
+            let config = Config {
+                version_check,
+                eol_severity: EolSeverity::Ignore,
+                ..create_test_config(AutoInstallMode::Never)
+            };
+            let mut writer = CommandWriter::new().unwrap();
+            Orchestrator::new(&config, &registry, &mut writer)
+                .with_index(ReleaseIndex::from_json(TEST_INDEX).unwrap())
+                .activate(temp_dir.path(), false)
+        };
+
+        assert!(activate(VersionCheck::Warn).is_ok());
+        assert!(activate(VersionCheck::Off).is_ok());
+        assert!(matches!(
+            activate(VersionCheck::Strict),
+            Err(ActivationError::EnginesMismatch { ref version, ref range, .. })
+                if version == "16.20.2" && range == ">=18"
+        ));
+    }
+
+    #[test]
+    fn test_package_manager_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_dir = temp_dir.path().join("node");
+        let npm_dir = node_dir.join("lib/node_modules/npm");
+        std::fs::create_dir_all(&npm_dir).unwrap();
+        std::fs::write(npm_dir.join("package.json"), r#"{"version": "9.8.1"}"#).unwrap();
+
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join(".nvmrc"), "20.11.0").unwrap();
+        std::fs::write(
+            project.join("package.json"),
+            r#"{"packageManager": "pnpm@8.15.4", "engines": {"npm": ">=10"}}"#,
+        )
+        .unwrap();
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_version("20.11.0")
+            .with_node_dir(&node_dir);
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+
+        for mode in [
+            PackageManagerMode::Off,
+            PackageManagerMode::Check,
+            PackageManagerMode::Corepack,
+        ] {
+            let config = Config {
+                package_manager: mode,
+                eol_severity: EolSeverity::Ignore,
+                ..create_test_config(AutoInstallMode::Never)
+            };
+            let mut writer = CommandWriter::new().unwrap();
+            let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+            let requirements = orchestrator.package_manager_requirements(&project);
+            assert_eq!(requirements.is_empty(), mode == PackageManagerMode::Off);
+
+            // A mismatched npm only warns
+            assert!(orchestrator.activate(&project, false).is_ok());
+            assert!(orchestrator
+                .setup_package_manager(&requirements, None, "14.21.3")
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().canonicalize().unwrap().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join(".nvmrc"), "20.11.0").unwrap();
+
+        let mock_plugin = MockPlugin::new("mock").with_version("20.11.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let config = Config {
+            hooks: crate::config::Hooks {
+                post_activate: vec!["echo {version} {project_root}".to_string()],
+                ..Default::default()
+            },
+            project_hooks: Some(crate::config::ProjectHooks {
+                path: project.join(".anvs.yaml"),
+                hooks: crate::config::Hooks {
+                    pre_activate: vec!["npm rebuild".to_string()],
+                    ..Default::default()
+                },
+            }),
+            eol_severity: EolSeverity::Ignore,
+            ..create_test_config(AutoInstallMode::Always)
+        };
+
+        let mut writer = CommandWriter::new().unwrap();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_trust_store(TrustStore::load(temp_dir.path().join("trusted.json")));
+
+        let target = orchestrator.find_target(&project).unwrap().unwrap();
+        let vars = orchestrator.hook_vars(&project, &target);
+        assert_eq!(vars.version, "20.11.0");
+        assert_eq!(vars.project_root, project.display().to_string());
+
+        // Untrusted project hooks are skipped rather than failing activation
+        assert!(orchestrator.activate(&project, false).is_ok());
+
+        std::fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+        assert!(orchestrator.activate(&project, false).is_ok());
+    }
+
+    #[test]
+    fn test_project_on_leave_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let project = root.join("project");
+        let outside = root.join("outside");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(project.join(".nvmrc"), "20.11.0").unwrap();
+        let hook = "echo left {project_root} for {version} via {plugin}";
+        std::fs::write(
+            project.join(".anvs.yaml"),
+            format!("hooks:\n  on_leave:\n    - {hook}\n"),
+        )
+        .unwrap();
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_version("18.20.0")
+            .with_default("18.20.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let config = create_test_config(AutoInstallMode::Never);
+        let key = format!("{}:20.11.0", project.join(".nvmrc").display());
+
+        let leave = |trust: TrustStore| {
+            let mut writer = RecordingSink::new();
+            let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+                .with_active_key(&key)
+                .with_trust_store(trust);
+            assert!(orchestrator.activate(&outside, true).is_ok());
+            drop(orchestrator);
+            writer.commands().to_vec()
+        };
+
+        // Untrusted hooks of the project being left are skipped
+        let mut trust = TrustStore::load(root.join("trusted.json"));
+        assert_eq!(leave(trust.clone()), ["mock use 18.20.0"]);
+
+        trust
+            .trust(&crate::config::ProjectHooks {
+                path: project.join(".anvs.yaml"),
+                hooks: crate::config::Hooks {
+                    on_leave: vec![hook.to_string()],
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+        assert_eq!(
+            leave(trust),
+            [
+                format!("echo left {} for 18.20.0 via mock", project.display()),
+                "mock use 18.20.0".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_on_leave_hooks_when_switching_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let left = root.join("left");
+        let entered = root.join("entered");
+        std::fs::create_dir(&left).unwrap();
+        std::fs::create_dir(&entered).unwrap();
+        std::fs::write(left.join(".nvmrc"), "20.11.0").unwrap();
+        std::fs::write(entered.join(".nvmrc"), "18.20.0").unwrap();
+        let hook = "echo left {project_root} for {version}";
+        std::fs::write(
+            left.join(".anvs.yaml"),
+            format!("hooks:\n  on_leave:\n    - {hook}\n"),
+        )
+        .unwrap();
+
+        let mut trust = TrustStore::load(root.join("trusted.json"));
+        trust
+            .trust(&crate::config::ProjectHooks {
+                path: left.join(".anvs.yaml"),
+                hooks: crate::config::Hooks {
+                    on_leave: vec![hook.to_string()],
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_version("18.20.0")
+            .with_version("20.11.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let config = Config {
+            hooks: crate::config::Hooks {
+                pre_activate: vec!["echo entering {project_root}".to_string()],
+                ..Default::default()
+            },
+            ..create_test_config(AutoInstallMode::Never)
+        };
+        let key = format!("{}:20.11.0", left.join(".nvmrc").display());
+
+        let activate = |path: &Path| {
+            let mut writer = RecordingSink::new();
+            let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+                .with_active_key(&key)
+                .with_trust_store(trust.clone());
+            assert!(orchestrator.activate(path, false).is_ok());
+            drop(orchestrator);
+            writer.commands().to_vec()
+        };
+
+        // The project being left goes first
+        assert_eq!(
+            activate(&entered),
+            [
+                format!("echo left {} for 18.20.0", left.display()),
+                format!("echo entering {}", entered.display()),
+                "mock use 18.20.0".to_string(),
+            ]
+        );
+
+        // Re-activating the same project doesn't leave it
+        assert_eq!(
+            activate(&left),
+            [
+                format!("echo entering {}", left.display()),
+                "mock use 20.11.0".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abi_check() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("node_modules")).unwrap();
+        std::fs::write(temp_dir.path().join("node_modules/.anvs-abi"), "108").unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "20.11.0").unwrap();
+
+        let mock_plugin = MockPlugin::new("mock").with_version("20.11.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+
+        for abi_check in [AbiCheck::Off, AbiCheck::Warn, AbiCheck::Rebuild] {
+            let config = Config {
+                abi_check,
+                eol_severity: EolSeverity::Ignore,
+                ..create_test_config(AutoInstallMode::Never)
+            };
+            let mut writer = CommandWriter::new().unwrap();
+            let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+            // A mismatch never fails activation
+            assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+            assert!(orchestrator
+                .check_abi(temp_dir.path(), None, "18.20.0")
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_activation_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join(".nvmrc"), "20.11.0").unwrap();
+        let log = ActivationLog::new(temp_dir.path().join("activations.jsonl"));
+
+        let mock_plugin = MockPlugin::new("mock").with_version("20.11.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let config = Config {
+            eol_severity: EolSeverity::Ignore,
+            ..create_test_config(AutoInstallMode::Never)
+        };
+
+        let mut writer = CommandWriter::new().unwrap();
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_history(log.clone());
+        orchestrator.activate(&project, false).unwrap();
+
+        std::fs::write(project.join(".nvmrc"), "22.0.0").unwrap();
+        assert!(orchestrator.activate(&project, false).is_err());
+
+        let records = log.read();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].version.as_deref(), Some("20.11.0"));
+        assert_eq!(records[0].plugin.as_deref(), Some("mock"));
+        assert_eq!(records[0].commands, vec!["mock use 20.11.0"]);
+        assert_eq!(records[0].version_file, Some(project.join(".nvmrc")));
+        assert_eq!(records[0].error, None);
+        assert_eq!(records[1].commands, Vec::<String>::new());
+        assert!(records[1].error.as_deref().unwrap().contains("22.0.0"));
+    }
+
+    #[test]
+    fn test_end_of_life_severity() {
+        let index = r#"{
+            "releases": [{"version": "16.20.2", "lts": "Gallium"}],
+            "lines": [{"major": 16, "codename": "Gallium", "eol": "2023-09-11"}]
+        }"#;
+        let mock_plugin = MockPlugin::new("mock").with_version("16.20.2");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "16.20.2").unwrap();
+
+        let activate = |eol_severity: EolSeverity| {
+            let config = Config {
+                eol_severity,
+                ..create_test_config(AutoInstallMode::Never)
+            };
+            let mut writer = CommandWriter::new().unwrap();
+            Orchestrator::new(&config, &registry, &mut writer)
+                .with_index(ReleaseIndex::from_json(index).unwrap())
+                .activate(temp_dir.path(), false)
+        };
+
+        assert!(activate(EolSeverity::Warn).is_ok());
+        assert!(activate(EolSeverity::Ignore).is_ok());
+        assert!(matches!(
+            activate(EolSeverity::Block),
+            Err(ActivationError::EndOfLife { ref version, ref eol })
+                if version == "16.20.2" && eol == "2023-09-11"
+        ));
+    }
+
     #[test]
     fn test_install_with_v_prefix() {
         // Test version with v prefix
@@ -1029,4 +2898,294 @@ mod tests {
         let result = orchestrator.activate(temp_dir.path(), false);
         assert!(result.is_err(), "Should propagate registry errors");
     }
+
+    #[test]
+    fn test_leave_restores_previous_environment() {
+        // Test that leave_action=restore restores the snapshot instead of the default
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.leave_action = LeaveAction::Restore;
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_availability(true)
+            .with_default("20.0.0");
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let snapshot = EnvSnapshot::from_lookup(|key| match key {
+            "ANVS_ENV_SAVED" => Some("1".to_string()),
+            "ANVS_SAVED_PATH" => Some("/usr/bin".to_string()),
+            "ANVS_SAVED_NVM_BIN" => Some("/nvm/v16/bin".to_string()),
+            _ => None,
+        });
+
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_env_snapshot(snapshot);
+
+        let temp_dir = TempDir::new().unwrap();
+        assert!(orchestrator.activate(temp_dir.path(), true).is_ok());
+        drop(orchestrator);
+
+        assert_eq!(
+            writer.commands(),
+            [
+                "export PATH=/usr/bin",
+                "export NVM_BIN=/nvm/v16/bin",
+                "unset NVM_INC",
+                "unset FNM_MULTISHELL_PATH",
+                "unset ANVS_SAVED_PATH",
+                "unset ANVS_SAVED_NVM_BIN",
+                "unset ANVS_SAVED_NVM_INC",
+                "unset ANVS_SAVED_FNM_MULTISHELL_PATH",
+                "unset ANVS_ENV_SAVED",
+                "hash -r",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leave_restore_without_snapshot_falls_back_to_default() {
+        // Test that restore mode without a snapshot still activates the default version
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.leave_action = LeaveAction::Restore;
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_availability(true)
+            .with_default("20.0.0")
+            .with_version("20.0.0");
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer)
+            .with_env_snapshot(EnvSnapshot::from_lookup(|_| None));
+
+        let temp_dir = TempDir::new().unwrap();
+        assert!(orchestrator.activate(temp_dir.path(), true).is_ok());
+        drop(orchestrator);
+
+        assert_eq!(writer.commands(), ["mock use 20.0.0"]);
+    }
+
+    #[test]
+    fn test_activate_captures_snapshot_in_restore_mode() {
+        // Test that entering a project in restore mode saves the environment first
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.leave_action = LeaveAction::Restore;
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_availability(true)
+            .with_version("18.20.0");
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let snapshot = EnvSnapshot::from_lookup(|key| match key {
+            "PATH" => Some("/usr/bin:/bin".to_string()),
+            "NVM_BIN" => Some("/nvm/v16/bin".to_string()),
+            _ => None,
+        });
+
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_env_snapshot(snapshot);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        drop(orchestrator);
+
+        assert_eq!(
+            writer.commands(),
+            [
+                "export ANVS_SAVED_PATH='/usr/bin:/bin'",
+                "export ANVS_SAVED_NVM_BIN=/nvm/v16/bin",
+                "export ANVS_ENV_SAVED=1",
+                "mock use 18.20.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_activation_does_not_capture_snapshot() {
+        // A missing version with auto_install: never writes nothing, not even the snapshot
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.leave_action = LeaveAction::Restore;
+
+        let mock_plugin = MockPlugin::new("mock").with_availability(true);
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = RecordingSink::new();
+
+        let snapshot = EnvSnapshot::from_lookup(|key| match key {
+            "PATH" => Some("/usr/bin:/bin".to_string()),
+            _ => None,
+        });
+
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_env_snapshot(snapshot);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        assert!(orchestrator.activate(temp_dir.path(), false).is_err());
+        drop(orchestrator);
+
+        assert!(writer.commands().is_empty());
+    }
+
+    #[test]
+    fn test_engines_resolution_is_cached() {
+        // Test that package.json resolutions are stored in the on-disk cache
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.version_files = vec!["package.json".to_string()];
+
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["18.20.0".to_string(), "20.11.0".to_string()],
+            ..MockPlugin::new("mock").with_versions(&["18.20.0", "20.11.0"])
+        };
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = CommandWriter::new().unwrap();
+
+        let cache_dir = TempDir::new().unwrap();
+        let cache = Arc::new(ResolutionCache::new(cache_dir.path()));
+
+        let mut orchestrator =
+            Orchestrator::new(&config, &registry, &mut writer).with_cache(Arc::clone(&cache));
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"engines": {"node": ">=20.0.0"}}"#,
+        )
+        .unwrap();
+
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        assert_eq!(cache.status().unwrap().resolutions, 1);
+
+        // Second activation is served from the cache
+        assert!(orchestrator.activate(temp_dir.path(), false).is_ok());
+        assert_eq!(cache.status().unwrap().resolutions, 1);
+    }
+
+    #[test]
+    fn test_missing_range_installs_concrete_version() {
+        // Nothing installed satisfies the range: install a concrete version, not the range
+        let mut config = create_test_config(AutoInstallMode::Never);
+        config.version_files = vec!["package.json".to_string()];
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_version("16.20.2")
+            .with_remote_versions(&[("18.20.0", Some("Hydrogen")), ("21.6.0", None)]);
+
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(mock_plugin)]);
+        let mut writer = CommandWriter::new().unwrap();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"engines": {"node": ">=18.0.0"}}"#,
+        )
+        .unwrap();
+
+        let result = orchestrator.activate(temp_dir.path(), false);
+        match result {
+            Err(ActivationError::VersionNotInstalled { version, hint }) => {
+                assert_eq!(version, "18.20.0");
+                assert!(hint.contains("mock install 18.20.0"));
+            }
+            other => panic!("expected VersionNotInstalled, got {other:?}"),
+        }
+    }
+
+    /// Plugin whose version lookup is slow, or times out
+    #[derive(Debug)]
+    struct SlowPlugin {
+        delay: std::time::Duration,
+        timed_out: bool,
+    }
+
+    impl VersionManagerPlugin for SlowPlugin {
+        fn name(&self) -> &str {
+            "slow"
+        }
+        fn version_files(&self) -> Vec<&str> {
+            vec![".nvmrc"]
+        }
+        fn is_available(&self) -> anyhow::Result<bool> {
+            Ok(true)
+        }
+        fn has_version(&self, _version: &str) -> anyhow::Result<bool> {
+            std::thread::sleep(self.delay);
+            if self.timed_out {
+                return Err(crate::plugins::CommandTimeout {
+                    command: "slow which".to_string(),
+                    timeout: self.delay,
+                }
+                .into());
+            }
+            Ok(true)
+        }
+        fn activate_command(&self, version: &str) -> anyhow::Result<String> {
+            Ok(format!("slow use {version}"))
+        }
+        fn install_command(&self, version: &str) -> anyhow::Result<String> {
+            Ok(format!("slow install {version}"))
+        }
+        fn list_versions(&self) -> anyhow::Result<Vec<String>> {
+            Ok(vec![])
+        }
+        fn default_version(&self) -> anyhow::Result<Option<String>> {
+            Ok(None)
+        }
+        fn current_version(&self) -> anyhow::Result<Option<String>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_activation_deadline_exceeded() {
+        let config = Config {
+            activation_timeout_ms: 20,
+            ..create_test_config(AutoInstallMode::Always)
+        };
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(SlowPlugin {
+            delay: std::time::Duration::from_millis(100),
+            timed_out: false,
+        })]);
+        let mut writer = CommandWriter::new().unwrap();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let result = orchestrator.activate(temp_dir.path(), false);
+        assert!(matches!(
+            result,
+            Err(ActivationError::DeadlineExceeded { timeout_ms: 20 })
+        ));
+    }
+
+    #[test]
+    fn test_plugin_command_timeout() {
+        let config = create_test_config(AutoInstallMode::Always);
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(SlowPlugin {
+            delay: std::time::Duration::from_millis(1),
+            timed_out: true,
+        })]);
+        let mut writer = CommandWriter::new().unwrap();
+        let mut orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        // A hung lookup must not be mistaken for "not installed" (which would install)
+        let result = orchestrator.activate(temp_dir.path(), false);
+        let err = result.unwrap_err();
+        assert!(
+            matches!(err, ActivationError::CommandTimeout { ref command, .. } if command == "slow which")
+        );
+        assert!(err.hint().is_some());
+    }
 }
@@ -0,0 +1,278 @@
+//! Remembered answers to install prompts
+//!
+//! Declining an install is remembered for the project and version for
+//! `DECLINE_EXPIRY_DAYS`, so every `cd` doesn't ask again. "Always" and
+//! "never" answers cover every version in the project until
+//! `anvs prompt reset`. Answers are stored in `~/.anvs/prompts.json`, keyed
+//! by the directory containing the version file.
+
+use super::InstallAnswer;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How long a plain "no" is remembered
+pub const DECLINE_EXPIRY_DAYS: i64 = 7;
+
+/// Standing answer for every version in a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectChoice {
+    Always,
+    Never,
+}
+
+/// Remembered answer that applies to an install prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Remembered {
+    /// Install without asking
+    Always,
+    /// Don't install or ask, for any version
+    Never,
+    /// This version was declined recently
+    Declined,
+}
+
+/// Answers for one project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ProjectAnswers {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    choice: Option<ProjectChoice>,
+    /// Declined versions and when each decline expires (RFC 3339)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    declined: BTreeMap<String, String>,
+}
+
+impl ProjectAnswers {
+    fn is_empty(&self) -> bool {
+        self.choice.is_none() && self.declined.is_empty()
+    }
+}
+
+/// Install prompt answers, per project
+#[derive(Debug, Clone, Default)]
+pub struct PromptState {
+    path: PathBuf,
+    projects: BTreeMap<PathBuf, ProjectAnswers>,
+}
+
+impl PromptState {
+    /// Default location: ~/.anvs/prompts.json
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".anvs").join("prompts.json"))
+    }
+
+    /// Loads answers from `path` (a missing or unreadable file is empty)
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let projects = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(projects) => Some(projects),
+                Err(e) => {
+                    warn!("Ignoring unreadable prompt state {}: {e}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, projects }
+    }
+
+    /// Loads answers from the default location
+    pub fn load_default() -> Result<Self> {
+        Ok(Self::load(Self::default_path()?))
+    }
+
+    /// Path of the state file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Remembered answer for installing `version` in `project`, as of `now`
+    pub fn remembered(
+        &self,
+        project: &Path,
+        version: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Option<Remembered> {
+        let answers = self.projects.get(project)?;
+        match answers.choice {
+            Some(ProjectChoice::Always) => return Some(Remembered::Always),
+            Some(ProjectChoice::Never) => return Some(Remembered::Never),
+            None => {}
+        }
+
+        let expires = answers.declined.get(version)?;
+        let active = DateTime::parse_from_rfc3339(expires).is_ok_and(|expires| expires > now);
+        active.then_some(Remembered::Declined)
+    }
+
+    /// Remembers `answer` to the prompt for `version` in `project`
+    ///
+    /// A plain "yes" isn't remembered (the version will be installed).
+    pub fn remember(
+        &mut self,
+        project: &Path,
+        version: &str,
+        answer: InstallAnswer,
+        now: DateTime<FixedOffset>,
+    ) -> Result<()> {
+        let answers = self.projects.entry(project.to_path_buf()).or_default();
+        match answer {
+            InstallAnswer::Yes => return Ok(()),
+            InstallAnswer::No => {
+                let expires = now + Duration::days(DECLINE_EXPIRY_DAYS);
+                answers
+                    .declined
+                    .insert(version.to_string(), expires.to_rfc3339());
+            }
+            InstallAnswer::AlwaysForProject => answers.choice = Some(ProjectChoice::Always),
+            InstallAnswer::NeverForProject => answers.choice = Some(ProjectChoice::Never),
+        }
+        debug!(
+            "Remembering {answer:?} for {version} in {}",
+            project.display()
+        );
+
+        self.prune(now);
+        self.save()
+    }
+
+    /// Forgets answers for every project, or only for projects containing or
+    /// inside `dir`
+    ///
+    /// # Returns
+    /// How many projects had answers
+    pub fn reset(&mut self, dir: Option<&Path>) -> Result<usize> {
+        let before = self.projects.len();
+        match dir {
+            Some(dir) => self
+                .projects
+                .retain(|project, _| !dir.starts_with(project) && !project.starts_with(dir)),
+            None => self.projects.clear(),
+        }
+        let removed = before - self.projects.len();
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Drops expired declines (and projects left with no answers)
+    fn prune(&mut self, now: DateTime<FixedOffset>) {
+        for answers in self.projects.values_mut() {
+            answers.declined.retain(|_, expires| {
+                DateTime::parse_from_rfc3339(expires).is_ok_and(|expires| expires > now)
+            });
+        }
+        self.projects.retain(|_, answers| !answers.is_empty());
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(&self.projects)?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn at(time: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(time).unwrap()
+    }
+
+    #[test]
+    fn test_decline_expires() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("anvs/prompts.json");
+        let app = Path::new("/work/app");
+        let now = at("2026-10-18T12:00:00+00:00");
+
+        let mut state = PromptState::load(&path);
+        assert_eq!(state.remembered(app, "22.1.0", now), None);
+        state
+            .remember(app, "22.1.0", InstallAnswer::No, now)
+            .unwrap();
+
+        let state = PromptState::load(&path);
+        assert_eq!(
+            state.remembered(app, "22.1.0", now),
+            Some(Remembered::Declined)
+        );
+        // Only that version, and only for a while
+        assert_eq!(state.remembered(app, "22.2.0", now), None);
+        assert_eq!(
+            state.remembered(app, "22.1.0", at("2026-10-26T12:00:00+00:00")),
+            None
+        );
+        assert_eq!(
+            state.remembered(Path::new("/work/other"), "22.1.0", now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_project_choices() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("prompts.json");
+        let now = at("2026-10-18T12:00:00+00:00");
+        let app = Path::new("/work/app");
+        let legacy = Path::new("/work/legacy");
+
+        let mut state = PromptState::load(&path);
+        state
+            .remember(app, "22.1.0", InstallAnswer::AlwaysForProject, now)
+            .unwrap();
+        state
+            .remember(legacy, "14.21.3", InstallAnswer::NeverForProject, now)
+            .unwrap();
+        state
+            .remember(app, "22.1.0", InstallAnswer::Yes, now)
+            .unwrap();
+
+        let state = PromptState::load(&path);
+        assert_eq!(
+            state.remembered(app, "24.0.0", now),
+            Some(Remembered::Always)
+        );
+        assert_eq!(
+            state.remembered(legacy, "16.20.2", now),
+            Some(Remembered::Never)
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("prompts.json");
+        let now = at("2026-10-18T12:00:00+00:00");
+
+        let mut state = PromptState::load(&path);
+        for project in ["/work/app", "/work/legacy", "/other"] {
+            state
+                .remember(Path::new(project), "22.1.0", InstallAnswer::No, now)
+                .unwrap();
+        }
+
+        // A directory inside a project, or containing projects
+        assert_eq!(state.reset(Some(Path::new("/work/app/src"))).unwrap(), 1);
+        assert_eq!(state.reset(Some(Path::new("/work"))).unwrap(), 1);
+        assert_eq!(state.reset(None).unwrap(), 1);
+        assert_eq!(state.reset(None).unwrap(), 0);
+
+        let state = PromptState::load(&path);
+        assert_eq!(state.remembered(Path::new("/other"), "22.1.0", now), None);
+    }
+}
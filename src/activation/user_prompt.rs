@@ -1,10 +1,32 @@
 use std::io::{self, Write};
 
+/// Answer to an install prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallAnswer {
+    Yes,
+    No,
+    /// Install, and install without asking in this project from now on
+    AlwaysForProject,
+    /// Don't install, and stop asking in this project
+    NeverForProject,
+}
+
 /// Abstraction for user prompts (allows testing without stdin)
 pub trait UserPrompt {
     /// Ask user a yes/no question
     /// Returns: true if user confirms, false if user declines
     fn confirm(&mut self, message: &str) -> io::Result<bool>;
+
+    /// Ask whether to install a missing version
+    ///
+    /// Default implementation asks a yes/no question with `confirm`.
+    fn confirm_install(&mut self, message: &str) -> io::Result<InstallAnswer> {
+        Ok(if self.confirm(message)? {
+            InstallAnswer::Yes
+        } else {
+            InstallAnswer::No
+        })
+    }
 }
 
 /// Production implementation that reads from stdin
@@ -42,12 +64,50 @@ impl UserPrompt for StdinUserPrompt {
         let response = response.trim().to_lowercase();
         Ok(response.is_empty() || response.starts_with('y'))
     }
+
+    fn confirm_install(&mut self, message: &str) -> io::Result<InstallAnswer> {
+        write!(
+            self.stdout,
+            "{message} [Y]es, [n]o, [a]lways for this project, ne[v]er for this project: "
+        )?;
+        self.stdout.flush()?;
+
+        let mut response = String::new();
+        self.stdin.read_line(&mut response)?;
+        Ok(parse_install_answer(&response))
+    }
+}
+
+/// Parses an answer to the install prompt (Enter means yes)
+fn parse_install_answer(response: &str) -> InstallAnswer {
+    match response.trim().to_lowercase().as_str() {
+        "" | "y" | "yes" => InstallAnswer::Yes,
+        "a" | "always" => InstallAnswer::AlwaysForProject,
+        "v" | "never" => InstallAnswer::NeverForProject,
+        _ => InstallAnswer::No,
+    }
+}
+
+/// Prompt for `anvs activate --dry-run`: shows the question and assumes yes
+///
+/// Never reads stdin, so dry runs work in scripts and show the commands an
+/// accepted prompt would lead to.
+pub struct DryRunPrompt;
+
+impl UserPrompt for DryRunPrompt {
+    fn confirm(&mut self, message: &str) -> io::Result<bool> {
+        println!("{message} [Y/n]: y (dry run)");
+        Ok(true)
+    }
 }
 
 /// Mock implementation for testing
 #[cfg(test)]
 pub struct MockUserPrompt {
     pub responses: Vec<bool>,
+    /// Answers for install prompts (popped like `responses`); when empty,
+    /// install prompts fall back to `responses`
+    pub install_answers: Vec<InstallAnswer>,
     pub prompts_received: Vec<String>,
 }
 
@@ -56,9 +116,17 @@ impl MockUserPrompt {
     pub fn new(responses: Vec<bool>) -> Self {
         Self {
             responses,
+            install_answers: Vec::new(),
             prompts_received: Vec::new(),
         }
     }
+
+    pub fn with_install_answers(answers: Vec<InstallAnswer>) -> Self {
+        Self {
+            install_answers: answers,
+            ..Self::new(Vec::new())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -67,6 +135,20 @@ impl UserPrompt for MockUserPrompt {
         self.prompts_received.push(message.to_string());
         Ok(self.responses.pop().unwrap_or(false))
     }
+
+    fn confirm_install(&mut self, message: &str) -> io::Result<InstallAnswer> {
+        match self.install_answers.pop() {
+            Some(answer) => {
+                self.prompts_received.push(message.to_string());
+                Ok(answer)
+            }
+            None => Ok(if self.confirm(message)? {
+                InstallAnswer::Yes
+            } else {
+                InstallAnswer::No
+            }),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +180,19 @@ mod tests {
         assert_eq!(prompt.prompts_received.len(), 2);
     }
 
+    #[test]
+    fn test_parse_install_answer() {
+        assert_eq!(parse_install_answer("\n"), InstallAnswer::Yes);
+        assert_eq!(parse_install_answer("Y\n"), InstallAnswer::Yes);
+        assert_eq!(parse_install_answer("n"), InstallAnswer::No);
+        assert_eq!(parse_install_answer("a"), InstallAnswer::AlwaysForProject);
+        assert_eq!(
+            parse_install_answer("never"),
+            InstallAnswer::NeverForProject
+        );
+        assert_eq!(parse_install_answer("maybe"), InstallAnswer::No);
+    }
+
     #[test]
     fn test_mock_user_prompt_default_false() {
         let mut prompt = MockUserPrompt::new(vec![]);
@@ -0,0 +1,720 @@
+//! Persistent resolution cache
+//!
+//! Stores installed version lists per plugin and resolved (requirement → version)
+//! results under `~/.anvs/cache`, so repeated activations don't have to spawn
+//! the version manager at all.
+//!
+//! # Invalidation
+//! - Version lists are keyed by the modification time of the plugin's versions
+//!   directory (e.g., `~/.nvm/versions/node`). Installing or uninstalling a
+//!   version changes that mtime, which invalidates the entry.
+//! - Answers to "is this requirement installed?" for aliases and partial
+//!   versions (e.g., `lts/iron`, `20`) are keyed the same way, per plugin.
+//! - Plugin availability is keyed by the path and mtime of the plugin's
+//!   executable, so upgrading or removing it invalidates the entry.
+//! - The ABI of each native addon in `node_modules` is keyed by the addon's
+//!   path and mtime, so `npm rebuild` invalidates it.
+//! - Resolutions are keyed by a fingerprint of all plugins' versions directories,
+//!   so any install or uninstall invalidates every resolution.
+//! - Shim resolutions (directory → Node.js `bin` directory) use the same
+//!   fingerprint, plus the mtimes of the directory, its parents, the version
+//!   file and the config files they were resolved from.
+
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// File holding cached resolutions
+const RESOLUTIONS_FILE: &str = "resolutions.json";
+
+/// File holding cached shim resolutions
+const SHIMS_FILE: &str = "shims.json";
+
+/// Shim resolutions kept before the file is started afresh
+const MAX_SHIM_ENTRIES: usize = 256;
+
+/// Prefix for per-plugin version list files (e.g., `versions-nvm.json`)
+const VERSIONS_PREFIX: &str = "versions-";
+
+/// Prefix for per-plugin requirement answer files (e.g., `installed-nvm.json`)
+const INSTALLED_PREFIX: &str = "installed-";
+
+/// File holding cached plugin availability
+const AVAILABILITY_FILE: &str = "availability.json";
+
+/// File holding the ABIs of native addons
+const ADDONS_FILE: &str = "addons.json";
+
+/// Addon ABIs kept before the file is started afresh
+const MAX_ADDON_ENTRIES: usize = 1024;
+
+/// Cached list of installed versions for one plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct VersionsEntry {
+    /// Versions directory the list was read for
+    dir: PathBuf,
+    /// Modification time of `dir` when the list was cached (nanoseconds)
+    mtime: u128,
+    /// Installed versions
+    versions: Vec<String>,
+}
+
+/// Cached answers to `has_version` for one plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct InstalledEntry {
+    /// Versions directory the answers were given for
+    dir: PathBuf,
+    /// Modification time of `dir` when the answers were cached (nanoseconds)
+    mtime: u128,
+    /// Raw requirement → whether it was installed
+    requirements: BTreeMap<String, bool>,
+}
+
+/// Cached availability of one plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AvailabilityEntry {
+    /// Executable the plugin runs
+    binary: PathBuf,
+    /// Modification time of `binary` when checked (nanoseconds)
+    mtime: u128,
+    /// Whether the plugin was available
+    available: bool,
+}
+
+/// Cached ABI of one native addon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AddonEntry {
+    /// Modification time of the addon when read (nanoseconds)
+    mtime: u128,
+    /// NODE_MODULE_VERSION it was built for (None = N-API or unknown)
+    abi: Option<u32>,
+}
+
+/// Cached resolution of a requirement to a concrete version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ResolutionEntry {
+    /// Fingerprint of the plugins' versions directories when resolved
+    fingerprint: String,
+    /// Resolved version
+    version: String,
+}
+
+/// Cached shim resolution for one directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ShimEntry {
+    /// Fingerprint of the plugins' versions directories when resolved
+    fingerprint: String,
+    /// Paths the resolution depended on, with their mtimes (0 if missing)
+    watched: BTreeMap<PathBuf, u128>,
+    /// Directory holding the Node.js binaries to run
+    bin_dir: PathBuf,
+}
+
+/// Summary of the cache contents (for `anvs cache status`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheStatus {
+    /// Cache directory
+    pub dir: PathBuf,
+    /// Plugins with a cached version list
+    pub version_lists: Vec<String>,
+    /// Number of cached resolutions
+    pub resolutions: usize,
+    /// Total size of cache files in bytes
+    pub size_bytes: u64,
+}
+
+/// On-disk cache for installed versions and resolutions
+#[derive(Debug, Clone)]
+pub struct ResolutionCache {
+    dir: PathBuf,
+}
+
+impl ResolutionCache {
+    /// Create a cache stored in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default cache directory: ~/.anvs/cache
+    pub fn default_dir() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".anvs").join("cache"))
+    }
+
+    /// Open the cache in the default directory
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(Self::default_dir()?))
+    }
+
+    /// Cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get cached installed versions for a plugin, if still valid
+    ///
+    /// Returns `None` if there is no entry, or if `versions_dir` has been
+    /// modified since the list was cached.
+    pub fn get_versions(&self, plugin: &str, versions_dir: &Path) -> Option<Vec<String>> {
+        let entry: VersionsEntry = self.read_json(&self.versions_path(plugin))?;
+        let mtime = dir_mtime(versions_dir)?;
+
+        if entry.dir == versions_dir && entry.mtime == mtime {
+            trace!("Version list cache hit for {plugin}");
+            Some(entry.versions)
+        } else {
+            debug!("Version list cache stale for {plugin}");
+            None
+        }
+    }
+
+    /// Store installed versions for a plugin
+    pub fn put_versions(
+        &self,
+        plugin: &str,
+        versions_dir: &Path,
+        versions: &[String],
+    ) -> Result<()> {
+        let mtime = dir_mtime(versions_dir)
+            .with_context(|| format!("cannot read mtime of {}", versions_dir.display()))?;
+
+        let entry = VersionsEntry {
+            dir: versions_dir.to_path_buf(),
+            mtime,
+            versions: versions.to_vec(),
+        };
+
+        self.write_json(&self.versions_path(plugin), &entry)
+    }
+
+    /// Get a cached `has_version` answer for a requirement, if still valid
+    ///
+    /// Returns `None` if there is no answer, or if `versions_dir` has been
+    /// modified since it was cached.
+    pub fn get_installed(
+        &self,
+        plugin: &str,
+        versions_dir: &Path,
+        requirement: &str,
+    ) -> Option<bool> {
+        let entry: InstalledEntry = self.read_json(&self.installed_path(plugin))?;
+        let mtime = dir_mtime(versions_dir)?;
+
+        if entry.dir != versions_dir || entry.mtime != mtime {
+            debug!("Installed cache stale for {plugin}");
+            return None;
+        }
+
+        let installed = entry.requirements.get(requirement).copied();
+        if installed.is_some() {
+            trace!("Installed cache hit for {plugin} {requirement}");
+        }
+        installed
+    }
+
+    /// Store a `has_version` answer for a requirement
+    ///
+    /// Answers cached for an older `versions_dir` mtime are dropped.
+    pub fn put_installed(
+        &self,
+        plugin: &str,
+        versions_dir: &Path,
+        requirement: &str,
+        installed: bool,
+    ) -> Result<()> {
+        let path = self.installed_path(plugin);
+        let mtime = dir_mtime(versions_dir)
+            .with_context(|| format!("cannot read mtime of {}", versions_dir.display()))?;
+
+        let mut entry = self
+            .read_json::<InstalledEntry>(&path)
+            .filter(|entry| entry.dir == versions_dir && entry.mtime == mtime)
+            .unwrap_or_else(|| InstalledEntry {
+                dir: versions_dir.to_path_buf(),
+                mtime,
+                requirements: BTreeMap::new(),
+            });
+        entry
+            .requirements
+            .insert(requirement.to_string(), installed);
+
+        self.write_json(&path, &entry)
+    }
+
+    /// Get the cached availability of a plugin, if its executable is unchanged
+    pub fn get_available(&self, plugin: &str, binary: &Path) -> Option<bool> {
+        let entries: BTreeMap<String, AvailabilityEntry> =
+            self.read_json(&self.dir.join(AVAILABILITY_FILE))?;
+        let entry = entries.get(plugin)?;
+
+        if entry.binary == binary && Some(entry.mtime) == dir_mtime(binary) {
+            trace!("Availability cache hit for {plugin}");
+            Some(entry.available)
+        } else {
+            debug!("Availability cache stale for {plugin}");
+            None
+        }
+    }
+
+    /// Store the availability of a plugin
+    pub fn put_available(&self, plugin: &str, binary: &Path, available: bool) -> Result<()> {
+        let path = self.dir.join(AVAILABILITY_FILE);
+        let mtime = dir_mtime(binary)
+            .with_context(|| format!("cannot read mtime of {}", binary.display()))?;
+
+        let mut entries: BTreeMap<String, AvailabilityEntry> =
+            self.read_json(&path).unwrap_or_default();
+        entries.insert(
+            plugin.to_string(),
+            AvailabilityEntry {
+                binary: binary.to_path_buf(),
+                mtime,
+                available,
+            },
+        );
+
+        self.write_json(&path, &entries)
+    }
+
+    /// Get the cached ABI of a native addon, if the file is unchanged
+    ///
+    /// Returns `Some(None)` for addons known not to be ABI-specific.
+    pub fn get_addon_abi(&self, addon: &Path) -> Option<Option<u32>> {
+        let entries: BTreeMap<PathBuf, AddonEntry> = self.read_json(&self.dir.join(ADDONS_FILE))?;
+        let entry = entries.get(addon)?;
+
+        (Some(entry.mtime) == dir_mtime(addon)).then_some(entry.abi)
+    }
+
+    /// Store the ABIs of native addons (None = not ABI-specific)
+    pub fn put_addon_abis(&self, addons: &[(PathBuf, Option<u32>)]) -> Result<()> {
+        let path = self.dir.join(ADDONS_FILE);
+        let mut entries: BTreeMap<PathBuf, AddonEntry> = self.read_json(&path).unwrap_or_default();
+
+        if entries.len() + addons.len() > MAX_ADDON_ENTRIES {
+            entries.clear();
+        }
+        for (addon, abi) in addons {
+            if let Some(mtime) = dir_mtime(addon) {
+                entries.insert(addon.clone(), AddonEntry { mtime, abi: *abi });
+            }
+        }
+
+        self.write_json(&path, &entries)
+    }
+
+    /// Get a cached resolution, if the fingerprint still matches
+    pub fn get_resolution(&self, key: &str, fingerprint: &str) -> Option<String> {
+        let entries: BTreeMap<String, ResolutionEntry> =
+            self.read_json(&self.dir.join(RESOLUTIONS_FILE))?;
+
+        match entries.get(key) {
+            Some(entry) if entry.fingerprint == fingerprint => {
+                trace!("Resolution cache hit for {key}");
+                Some(entry.version.clone())
+            }
+            Some(_) => {
+                debug!("Resolution cache stale for {key}");
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a resolution
+    pub fn put_resolution(&self, key: &str, fingerprint: &str, version: &str) -> Result<()> {
+        let path = self.dir.join(RESOLUTIONS_FILE);
+        let mut entries: BTreeMap<String, ResolutionEntry> =
+            self.read_json(&path).unwrap_or_default();
+
+        // Drop entries from older fingerprints so the file doesn't grow forever
+        entries.retain(|_, entry| entry.fingerprint == fingerprint);
+
+        entries.insert(
+            key.to_string(),
+            ResolutionEntry {
+                fingerprint: fingerprint.to_string(),
+                version: version.to_string(),
+            },
+        );
+
+        self.write_json(&path, &entries)
+    }
+
+    /// Get the cached `bin` directory for shims run in `dir`, if still valid
+    pub fn get_shim(&self, dir: &Path, fingerprint: &str) -> Option<PathBuf> {
+        let entries: BTreeMap<PathBuf, ShimEntry> = self.read_json(&self.dir.join(SHIMS_FILE))?;
+        let entry = entries.get(dir)?;
+
+        let unchanged = entry
+            .watched
+            .iter()
+            .all(|(path, mtime)| dir_mtime(path).unwrap_or(0) == *mtime);
+        if entry.fingerprint == fingerprint && unchanged {
+            trace!("Shim cache hit for {}", dir.display());
+            Some(entry.bin_dir.clone())
+        } else {
+            debug!("Shim cache stale for {}", dir.display());
+            None
+        }
+    }
+
+    /// Store the `bin` directory for shims run in `dir`
+    ///
+    /// The entry is invalidated when any `watched` path changes (or appears).
+    pub fn put_shim(
+        &self,
+        dir: &Path,
+        fingerprint: &str,
+        watched: &[PathBuf],
+        bin_dir: &Path,
+    ) -> Result<()> {
+        let path = self.dir.join(SHIMS_FILE);
+        let mut entries: BTreeMap<PathBuf, ShimEntry> = self.read_json(&path).unwrap_or_default();
+
+        entries.retain(|_, entry| entry.fingerprint == fingerprint);
+        if entries.len() >= MAX_SHIM_ENTRIES {
+            entries.clear();
+        }
+
+        entries.insert(
+            dir.to_path_buf(),
+            ShimEntry {
+                fingerprint: fingerprint.to_string(),
+                watched: watched
+                    .iter()
+                    .map(|path| (path.clone(), dir_mtime(path).unwrap_or(0)))
+                    .collect(),
+                bin_dir: bin_dir.to_path_buf(),
+            },
+        );
+
+        self.write_json(&path, &entries)
+    }
+
+    /// Remove all cache files
+    ///
+    /// # Returns
+    /// Number of files removed
+    pub fn clear(&self) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?;
+                removed += 1;
+            }
+        }
+
+        debug!("Removed {removed} cache files");
+        Ok(removed)
+    }
+
+    /// Summarize the cache contents
+    pub fn status(&self) -> Result<CacheStatus> {
+        let mut status = CacheStatus {
+            dir: self.dir.clone(),
+            version_lists: Vec::new(),
+            resolutions: 0,
+            size_bytes: 0,
+        };
+
+        if !self.dir.exists() {
+            return Ok(status);
+        }
+
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to read {}", self.dir.display()))?
+        {
+            let entry = entry?;
+            status.size_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(plugin) = name
+                .strip_prefix(VERSIONS_PREFIX)
+                .and_then(|rest| rest.strip_suffix(".json"))
+            {
+                status.version_lists.push(plugin.to_string());
+            }
+        }
+        status.version_lists.sort();
+
+        let resolutions: Option<BTreeMap<String, ResolutionEntry>> =
+            self.read_json(&self.dir.join(RESOLUTIONS_FILE));
+        status.resolutions = resolutions.map(|r| r.len()).unwrap_or(0);
+
+        Ok(status)
+    }
+
+    fn versions_path(&self, plugin: &str) -> PathBuf {
+        self.dir.join(format!("{VERSIONS_PREFIX}{plugin}.json"))
+    }
+
+    fn installed_path(&self, plugin: &str) -> PathBuf {
+        self.dir.join(format!("{INSTALLED_PREFIX}{plugin}.json"))
+    }
+
+    /// Read a JSON cache file (missing or corrupt files are treated as empty)
+    fn read_json<T: for<'de> Deserialize<'de>>(&self, path: &Path) -> Option<T> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                debug!("Ignoring corrupt cache file {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    fn write_json<T: Serialize>(&self, path: &Path, value: &T) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create {}", self.dir.display()))?;
+
+        let json = serde_json::to_string(value)?;
+
+        // Write to a temp file and rename, so concurrent shells never read a partial file
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, json).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// Modification time of a file or directory in nanoseconds since the epoch
+pub fn dir_mtime(path: &Path) -> Option<u128> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_versions_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let versions_dir = temp.path().join("versions");
+        fs::create_dir(&versions_dir).unwrap();
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        assert!(cache.get_versions("nvm", &versions_dir).is_none());
+
+        let versions = vec!["18.20.0".to_string(), "20.11.0".to_string()];
+        cache.put_versions("nvm", &versions_dir, &versions).unwrap();
+
+        assert_eq!(cache.get_versions("nvm", &versions_dir), Some(versions));
+        assert!(cache.get_versions("fnm", &versions_dir).is_none());
+    }
+
+    #[test]
+    fn test_versions_invalidated_by_mtime() {
+        let temp = TempDir::new().unwrap();
+        let versions_dir = temp.path().join("versions");
+        fs::create_dir(&versions_dir).unwrap();
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        cache
+            .put_versions("nvm", &versions_dir, &["18.20.0".to_string()])
+            .unwrap();
+
+        // Simulate an install: a new entry changes the directory mtime
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::create_dir(versions_dir.join("v20.11.0")).unwrap();
+        fs::File::open(&versions_dir)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        assert!(cache.get_versions("nvm", &versions_dir).is_none());
+    }
+
+    #[test]
+    fn test_installed_answers_invalidated_by_mtime() {
+        let temp = TempDir::new().unwrap();
+        let versions_dir = temp.path().join("versions");
+        fs::create_dir(&versions_dir).unwrap();
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        assert!(cache.get_installed("nvm", &versions_dir, "20").is_none());
+
+        cache
+            .put_installed("nvm", &versions_dir, "20", true)
+            .unwrap();
+        cache
+            .put_installed("nvm", &versions_dir, "lts/iron", false)
+            .unwrap();
+        assert_eq!(cache.get_installed("nvm", &versions_dir, "20"), Some(true));
+        assert_eq!(
+            cache.get_installed("nvm", &versions_dir, "lts/iron"),
+            Some(false)
+        );
+        assert!(cache.get_installed("fnm", &versions_dir, "20").is_none());
+
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::open(&versions_dir)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cache.get_installed("nvm", &versions_dir, "20").is_none());
+
+        // Answers from the old mtime are not carried over
+        cache
+            .put_installed("nvm", &versions_dir, "lts/iron", true)
+            .unwrap();
+        assert!(cache.get_installed("nvm", &versions_dir, "20").is_none());
+    }
+
+    #[test]
+    fn test_availability_invalidated_by_binary() {
+        let temp = TempDir::new().unwrap();
+        let binary = temp.path().join("fnm");
+        fs::write(&binary, "v1").unwrap();
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        assert!(cache.get_available("fnm", &binary).is_none());
+
+        cache.put_available("fnm", &binary, true).unwrap();
+        assert_eq!(cache.get_available("fnm", &binary), Some(true));
+        assert!(cache
+            .get_available("fnm", &temp.path().join("other"))
+            .is_none());
+
+        // Upgrading the executable invalidates the entry
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&binary)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cache.get_available("fnm", &binary).is_none());
+    }
+
+    #[test]
+    fn test_addon_abi_invalidated_by_mtime() {
+        let temp = TempDir::new().unwrap();
+        let addon = temp.path().join("addon.node");
+        let napi = temp.path().join("napi.node");
+        fs::write(&addon, "").unwrap();
+        fs::write(&napi, "").unwrap();
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        assert!(cache.get_addon_abi(&addon).is_none());
+
+        cache
+            .put_addon_abis(&[(addon.clone(), Some(108)), (napi.clone(), None)])
+            .unwrap();
+        assert_eq!(cache.get_addon_abi(&addon), Some(Some(108)));
+        assert_eq!(cache.get_addon_abi(&napi), Some(None));
+
+        // A rebuild rewrites the addon
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&addon)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cache.get_addon_abi(&addon).is_none());
+    }
+
+    #[test]
+    fn test_resolution_fingerprint() {
+        let temp = TempDir::new().unwrap();
+        let cache = ResolutionCache::new(temp.path());
+
+        cache.put_resolution(">=18", "fp1", "20.11.0").unwrap();
+        assert_eq!(
+            cache.get_resolution(">=18", "fp1"),
+            Some("20.11.0".to_string())
+        );
+        assert!(cache.get_resolution(">=18", "fp2").is_none());
+        assert!(cache.get_resolution("^20", "fp1").is_none());
+
+        // A new fingerprint drops stale entries
+        cache.put_resolution("^20", "fp2", "20.11.0").unwrap();
+        assert!(cache.get_resolution(">=18", "fp1").is_none());
+    }
+
+    #[test]
+    fn test_shim_invalidated_by_watched_paths() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("app");
+        fs::create_dir(&project).unwrap();
+        let nvmrc = project.join(".nvmrc");
+        fs::write(&nvmrc, "18.20.0").unwrap();
+        let bin = temp.path().join("v18.20.0/bin");
+
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+        let watched = [project.clone(), nvmrc.clone(), temp.path().join(".anvsrc")];
+        cache.put_shim(&project, "fp", &watched, &bin).unwrap();
+        assert_eq!(cache.get_shim(&project, "fp"), Some(bin.clone()));
+        assert!(cache.get_shim(&project, "fp2").is_none());
+        assert!(cache.get_shim(temp.path(), "fp").is_none());
+
+        // Editing the version file invalidates the entry
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&nvmrc)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cache.get_shim(&project, "fp").is_none());
+
+        // So does creating a watched file that was missing
+        cache.put_shim(&project, "fp", &watched, &bin).unwrap();
+        fs::write(temp.path().join(".anvsrc"), "plugins: [fnm]\n").unwrap();
+        assert!(cache.get_shim(&project, "fp").is_none());
+    }
+
+    #[test]
+    fn test_status_and_clear() {
+        let temp = TempDir::new().unwrap();
+        let versions_dir = temp.path().join("versions");
+        fs::create_dir(&versions_dir).unwrap();
+        let cache = ResolutionCache::new(temp.path().join("cache"));
+
+        let status = cache.status().unwrap();
+        assert!(status.version_lists.is_empty());
+        assert_eq!(status.resolutions, 0);
+
+        cache.put_versions("nvm", &versions_dir, &[]).unwrap();
+        cache.put_resolution(">=18", "fp", "20.11.0").unwrap();
+
+        let status = cache.status().unwrap();
+        assert_eq!(status.version_lists, vec!["nvm"]);
+        assert_eq!(status.resolutions, 1);
+        assert!(status.size_bytes > 0);
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.status().unwrap().resolutions, 0);
+    }
+
+    #[test]
+    fn test_corrupt_file_is_ignored() {
+        let temp = TempDir::new().unwrap();
+        let cache = ResolutionCache::new(temp.path());
+        fs::write(temp.path().join(RESOLUTIONS_FILE), "not json").unwrap();
+
+        assert!(cache.get_resolution(">=18", "fp").is_none());
+        cache.put_resolution(">=18", "fp", "20.11.0").unwrap();
+        assert_eq!(
+            cache.get_resolution(">=18", "fp"),
+            Some("20.11.0".to_string())
+        );
+    }
+}
@@ -108,6 +108,35 @@ pub enum Commands {
         /// directories.
         #[arg(long)]
         use_default: bool,
+
+        /// Print the commands that would be written to the shell instead
+        ///
+        /// Runs the full activation without prompting (installs are assumed
+        /// to be accepted) and without touching the shell, the daemon or the
+        /// activation history.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// How to hand commands to the shell (fd3 by default, json on Windows)
+        ///
+        /// Shell hooks pass it explicitly: fd3 for bash and zsh, json for
+        /// PowerShell and Nushell.
+        #[arg(long, value_enum)]
+        protocol: Option<crate::shell::OutputProtocol>,
+    },
+
+    /// Check whether the shell hook needs to activate (used by the shell hook)
+    ///
+    /// Prints "none" if no version file is found, "unchanged" if the version
+    /// file and version match the active key, or the new active key otherwise.
+    #[clap(hide = true)]
+    HookCheck {
+        /// Directory to check
+        path: PathBuf,
+
+        /// Active key from the previous activation (ANVS_ACTIVE_KEY)
+        #[arg(default_value = "")]
+        active_key: String,
     },
 
     /// Show configuration, installed plugins, and test activation
@@ -133,6 +162,201 @@ pub enum Commands {
         setting: Option<String>,
     },
 
+    /// Manage the resolution cache (~/.anvs/cache)
+    ///
+    /// anvs caches installed version lists and resolved package.json ranges so
+    /// repeated activations don't have to run the version manager. The cache is
+    /// invalidated automatically when versions are installed or removed.
+    ///
+    /// Examples:
+    ///   anvs cache status    Show what is cached
+    ///   anvs cache clear     Remove all cached data
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Manage the offline Node.js release index
+    ///
+    /// anvs bundles a list of Node.js releases (versions, LTS codenames, EOL
+    /// dates) used to resolve aliases like lts/hydrogen and package.json ranges
+    /// that no installed version satisfies. `update` refreshes it from
+    /// nodejs.org, a mirror, or a local index.json file.
+    ///
+    /// Examples:
+    ///   anvs index status                                    Show index contents
+    ///   anvs index update                                    Download from nodejs.org
+    ///   anvs index update https://npmmirror.com/mirrors/node Use a mirror
+    ///   anvs index update ./index.json                       Use a local file
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+
+    /// Report end-of-life Node.js versions pinned under a directory
+    ///
+    /// Scans every version file below PATH (skipping node_modules and hidden
+    /// directories) and checks the version it pins against the release index.
+    /// Exits with an error if eol_severity is 'block' and any pin is end-of-life.
+    ///
+    /// Examples:
+    ///   anvs audit            Audit the current directory
+    ///   anvs audit ~/code     Audit every project under ~/code
+    ///   anvs audit --json     Machine-readable output
+    Audit {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Output JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show which version file or path rule applies to a directory
+    ///
+    /// Prints the version file (or the `rules:` entry from ~/.anvsrc or .anvs.yaml) that
+    /// anvs would use, the version it resolves to, and the plugin a rule
+    /// prefers.
+    ///
+    /// Examples:
+    ///   anvs which                  Current directory
+    ///   anvs which ~/work/legacy    Another directory
+    Which {
+        /// Directory to inspect
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Show what anvs did on recent activations
+    ///
+    /// Every activation is recorded in ~/.anvs/activations.jsonl with the
+    /// directory, version file, resolved version, plugin, commands written
+    /// to the shell, duration and any error.
+    ///
+    /// Examples:
+    ///   anvs log                  Whole history
+    ///   anvs log --since 2h       The last two hours
+    ///   anvs log --since 2026-10-01 --json
+    Log {
+        /// Only show activations since a duration ago (30m, 2h, 1d, 1w),
+        /// a date (2026-10-01) or an RFC 3339 timestamp
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Print records as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Manage shims for processes that don't run the shell hook
+    ///
+    /// IDEs, cron jobs and git hooks don't run the cd hook, so they get
+    /// whatever node is first on their PATH. Shims in ~/.anvs/shims pick the
+    /// version for the directory they run in. Put that directory first on
+    /// PATH for those processes.
+    ///
+    /// Examples:
+    ///   anvs shims install    Create node, npm, npx and corepack shims
+    Shims {
+        #[command(subcommand)]
+        action: ShimsAction,
+    },
+
+    /// Manage remembered answers to install prompts
+    ///
+    /// Answering "no" to an install prompt is remembered for that project and
+    /// version for a week; "always" and "never" apply to every version in the
+    /// project. Answers are stored in ~/.anvs/prompts.json.
+    ///
+    /// Examples:
+    ///   anvs prompt reset           Forget every answer
+    ///   anvs prompt reset ~/app     Forget answers for one project
+    Prompt {
+        #[command(subcommand)]
+        action: PromptAction,
+    },
+
+    /// Allow a project's .anvs.yaml hooks to run
+    ///
+    /// Prints the project's hooks and records them as trusted. If the hooks
+    /// change later, they stop running until trusted again.
+    ///
+    /// Examples:
+    ///   anvs trust              Trust the current project's hooks
+    ///   anvs trust --revoke     Stop running them
+    Trust {
+        /// Directory inside the project
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Revoke trust instead of granting it
+        #[arg(long)]
+        revoke: bool,
+    },
+
+    /// Check that a project's version files agree with package.json engines
+    ///
+    /// Reads .nvmrc, .node-version, .tool-versions and package.json
+    /// engines.node / volta.node in the project root and verifies every pinned
+    /// version satisfies engines.node. With --strict (or version_check: strict)
+    /// disagreements make the command fail, e.g. in CI.
+    ///
+    /// Examples:
+    ///   anvs check            Check the current project
+    ///   anvs check --strict   Exit with an error on conflicts
+    Check {
+        /// Directory inside the project
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Fail if version sources disagree
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// List every Node.js version pinned under a directory
+    ///
+    /// Finds .nvmrc, .node-version, .tool-versions and package.json
+    /// engines.node / volta.node entries, skipping node_modules and anything
+    /// matched by .gitignore files. Flags pins that contradict each other, like
+    /// an .nvmrc that doesn't satisfy the sibling package.json engines.node.
+    ///
+    /// Examples:
+    ///   anvs scan                      Scan the current directory
+    ///   anvs scan ~/monorepo --json    Machine-readable output
+    ///   anvs scan --exclude fixtures   Skip directories named fixtures
+    Scan {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Extra .gitignore-style pattern to skip (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        exclude: Vec<String>,
+
+        /// Output JSON instead of a summary
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Manage the activation daemon
+    ///
+    /// The daemon keeps configuration, plugins and resolved versions in memory
+    /// and answers activation requests over a Unix socket, so directory changes
+    /// don't have to run the version manager. `anvs activate` uses it
+    /// automatically when it's running and falls back to normal activation
+    /// otherwise. Set ANVS_DAEMON_SOCKET to use a different socket path.
+    ///
+    /// Examples:
+    ///   anvs daemon start     Start the daemon in the background
+    ///   anvs daemon status    Show whether the daemon is running
+    ///   anvs daemon stop      Stop the daemon
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+
     /// Uninstall anvs completely
     ///
     /// Removes all anvs installations, configuration files, and shell integration.
@@ -151,6 +375,65 @@ pub enum Commands {
     },
 }
 
+/// Actions for `anvs cache`
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Remove all cached version lists and resolutions
+    Clear,
+    /// Show cache location and contents
+    Status,
+}
+
+/// Actions for `anvs shims`
+#[derive(Subcommand, Debug)]
+pub enum ShimsAction {
+    /// Create (or update) the shims in ~/.anvs/shims
+    Install,
+}
+
+/// Actions for `anvs prompt`
+#[derive(Subcommand, Debug)]
+pub enum PromptAction {
+    /// Forget remembered answers, so installs are asked about again
+    Reset {
+        /// Only forget answers for the project containing (or projects under)
+        /// this directory
+        path: Option<PathBuf>,
+    },
+}
+
+/// Actions for `anvs index`
+#[derive(Subcommand, Debug)]
+pub enum IndexAction {
+    /// Refresh the release index
+    Update {
+        /// Index URL, mirror base URL, or local file
+        #[arg(default_value = crate::release_index::DEFAULT_INDEX_URL)]
+        source: String,
+    },
+    /// Show which release index is in use
+    Status,
+}
+
+/// Actions for `anvs daemon`
+#[derive(Subcommand, Debug)]
+pub enum DaemonAction {
+    /// Start the daemon (in the background unless --foreground)
+    Start {
+        /// Run in the foreground instead of detaching
+        #[arg(long)]
+        foreground: bool,
+
+        /// Exit after this many seconds without requests
+        #[arg(long, default_value_t = crate::daemon::DEFAULT_IDLE_TIMEOUT.as_secs())]
+        idle_timeout: u64,
+    },
+    /// Stop the running daemon
+    Stop,
+    /// Show daemon status
+    Status,
+}
+
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
 
@@ -182,8 +465,15 @@ pub fn run() -> Result<()> {
 
             crate::init::init(true, false, false, force)
         }
-        Some(Commands::Activate { path, use_default }) => {
-            info!("Running activate command for path: {path:?} (use_default: {use_default})");
+        Some(Commands::Activate {
+            path,
+            use_default,
+            dry_run,
+            protocol,
+        }) => {
+            info!(
+                "Running activate command for path: {path:?} (use_default: {use_default}, dry_run: {dry_run})"
+            );
 
             // Check for installation conflicts and show warning if flagged
             if crate::installation_detector::InstallationDetector::should_warn() {
@@ -196,19 +486,81 @@ pub fn run() -> Result<()> {
             // Load config
             let config = crate::config::Config::load().context("failed to load configuration")?;
 
-            // Create plugin registry
-            let registry = crate::plugins::PluginRegistry::new(&config.plugins);
+            // Commands go to the shell's protocol (or are printed for a dry run)
+            let mut sink: Box<dyn crate::shell::CommandSink> = if dry_run {
+                Box::new(crate::shell::DryRunPrinter::default())
+            } else {
+                protocol
+                    .unwrap_or_else(crate::shell::OutputProtocol::detect)
+                    .sink()?
+            };
+
+            // Every real decision is recorded for `anvs log`
+            let history = if dry_run {
+                None
+            } else {
+                crate::activation::ActivationLog::open_default().ok()
+            };
+
+            // Ask the daemon first; it answers from memory when it can
+            if let Some(socket) = crate::daemon::socket_path().ok().filter(|_| !dry_run) {
+                if crate::daemon::try_activate(
+                    &socket,
+                    &config,
+                    &path,
+                    use_default,
+                    sink.as_mut(),
+                    history.as_ref(),
+                ) {
+                    sink.finish()?;
+                    return Ok(());
+                }
+            }
 
-            // Open FD:3 for writing commands
-            let mut fd3 = crate::shell::CommandWriter::new()?;
+            // Create plugin registry, backed by the on-disk cache when available
+            let cache = crate::cache::ResolutionCache::open_default()
+                .ok()
+                .map(std::sync::Arc::new);
+            let mut registry = crate::plugins::PluginRegistry::from_config(&config);
+            if let Some(cache) = &cache {
+                registry = registry.with_cache(std::sync::Arc::clone(cache));
+            }
 
             // Create orchestrator
             let mut orchestrator =
-                crate::activation::Orchestrator::new(&config, &registry, &mut fd3);
+                crate::activation::Orchestrator::new(&config, &registry, sink.as_mut());
+            if let Some(cache) = cache {
+                orchestrator = orchestrator.with_cache(cache);
+            }
+            if let Some(history) = history {
+                orchestrator = orchestrator.with_history(history);
+            }
+            if dry_run {
+                orchestrator = orchestrator
+                    .with_user_prompt(Box::new(crate::activation::DryRunPrompt))
+                    .with_installer(Box::new(crate::activation::DryRunInstaller));
+            } else if let Ok(state) = crate::activation::PromptState::load_default() {
+                orchestrator = orchestrator.with_prompt_state(state);
+            }
 
             // Run activation
-            match orchestrator.activate(&path, use_default) {
+            let result = orchestrator.activate(&path, use_default);
+            drop(orchestrator);
+            sink.finish()?;
+
+            match result {
                 Ok(()) => Ok(()),
+                Err(
+                    e @ (crate::activation::ActivationError::DeadlineExceeded { .. }
+                    | crate::activation::ActivationError::InstallCancelled { .. }),
+                ) => {
+                    // Nothing was written to the shell; leave it as it was
+                    crate::output::warning(&format!("{e}"));
+                    if let Some(hint) = e.hint() {
+                        crate::output::info(&hint);
+                    }
+                    Ok(())
+                }
                 Err(e) => {
                     // Print main error message
                     crate::output::error(&format!("{e}"));
@@ -224,6 +576,10 @@ pub fn run() -> Result<()> {
                 }
             }
         }
+        Some(Commands::HookCheck { path, active_key }) => {
+            info!("Running hook-check for path: {path:?}");
+            crate::commands::hook_check::hook_check(&path, &active_key)
+        }
         Some(Commands::Status) => {
             info!("Running status command");
 
@@ -231,6 +587,22 @@ pub fn run() -> Result<()> {
                 Ok(config) => {
                     crate::output::info(&format!("Plugins: {}", config.plugins.join(", ")));
                     crate::output::info(&format!("Auto-install: {:?}", config.auto_install));
+                    if !config.auto_install_rules.is_empty() {
+                        crate::output::info(&format!(
+                            "Auto-install rules: {}",
+                            config.auto_install_rules.len()
+                        ));
+                    }
+                    crate::output::info(&format!(
+                        "Install mode: {}",
+                        match config.install_mode {
+                            crate::config::InstallMode::Shell => "shell",
+                            crate::config::InstallMode::Process => "process (with progress)",
+                        }
+                    ));
+                    if let Some(plugin) = &config.install_plugin {
+                        crate::output::info(&format!("Install plugin: {plugin}"));
+                    }
                     crate::output::info(&format!(
                         "Version files: {}",
                         config.version_files.join(", ")
@@ -244,8 +616,115 @@ pub fn run() -> Result<()> {
                         }
                     ));
 
+                    crate::output::info(&format!(
+                        "Leave action: {}",
+                        match config.leave_action {
+                            crate::config::LeaveAction::Default => "switch to default",
+                            crate::config::LeaveAction::Restore => "restore previous environment",
+                        }
+                    ));
+
+                    crate::output::info(&format!(
+                        "Range strategy: {}",
+                        match config.range_strategy {
+                            crate::config::RangeStrategy::DefaultFirst => "default first",
+                            crate::config::RangeStrategy::Highest => "highest",
+                            crate::config::RangeStrategy::Lowest => "lowest",
+                            crate::config::RangeStrategy::HighestLts => "highest LTS",
+                            crate::config::RangeStrategy::CurrentIfSatisfies => {
+                                "current if it satisfies"
+                            }
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "Remote version policy: {}",
+                        match config.remote_version_policy {
+                            crate::config::RemoteVersionPolicy::Newest => "newest",
+                            crate::config::RemoteVersionPolicy::NewestLts => "newest LTS",
+                            crate::config::RemoteVersionPolicy::Lowest => "lowest",
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "End-of-life versions: {}",
+                        match config.eol_severity {
+                            crate::config::EolSeverity::Warn => "warn",
+                            crate::config::EolSeverity::Block => "block",
+                            crate::config::EolSeverity::Ignore => "ignore",
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "Version check: {}",
+                        match config.version_check {
+                            crate::config::VersionCheck::Warn => "warn",
+                            crate::config::VersionCheck::Strict => "strict",
+                            crate::config::VersionCheck::Off => "off",
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "Workspace strategy: {}",
+                        match config.workspace_strategy {
+                            crate::config::WorkspaceStrategy::Nearest => "nearest file",
+                            crate::config::WorkspaceStrategy::Root => "workspace root",
+                            crate::config::WorkspaceStrategy::RootSatisfiesNearest => {
+                                "workspace root, must satisfy nearest range"
+                            }
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "Package manager: {}",
+                        match config.package_manager {
+                            crate::config::PackageManagerMode::Off => "off",
+                            crate::config::PackageManagerMode::Check => "check npm",
+                            crate::config::PackageManagerMode::Corepack => "check npm, corepack",
+                        }
+                    ));
+                    crate::output::info(&format!(
+                        "Native module ABI check: {}",
+                        match config.abi_check {
+                            crate::config::AbiCheck::Off => "off",
+                            crate::config::AbiCheck::Warn => "warn",
+                            crate::config::AbiCheck::Rebuild => "rebuild",
+                        }
+                    ));
+                    if let Some(project) = &config.project_hooks {
+                        let trusted = crate::hooks::TrustStore::load_default()
+                            .is_ok_and(|store| store.is_trusted(project));
+                        crate::output::info(&format!(
+                            "Project hooks: {} ({})",
+                            project.path.display(),
+                            if trusted {
+                                "trusted"
+                            } else {
+                                "not trusted, run 'anvs trust'"
+                            }
+                        ));
+                    }
+                    if config.package_manager != crate::config::PackageManagerMode::Off {
+                        let cwd = std::env::current_dir().unwrap_or_default();
+                        let requirements = crate::package_manager::Requirements::find(&cwd);
+                        if let Some(spec) = &requirements.package_manager {
+                            crate::output::info(&format!("  packageManager: {}", spec.raw));
+                        }
+                        if let Some(range) = &requirements.npm_range {
+                            crate::output::info(&format!("  engines.npm: {range}"));
+                        }
+                    }
+
+                    let format_timeout = |ms: u64| {
+                        if ms == 0 {
+                            "no limit".to_string()
+                        } else {
+                            format!("{ms}ms")
+                        }
+                    };
+                    crate::output::info(&format!(
+                        "Timeouts: {} per command, {} per activation",
+                        format_timeout(config.command_timeout_ms),
+                        format_timeout(config.activation_timeout_ms)
+                    ));
+
                     // Try to show the default version from available plugins
-                    let registry = crate::plugins::PluginRegistry::new(&config.plugins);
+                    let registry = crate::plugins::PluginRegistry::from_config(&config);
                     for plugin in registry.plugins() {
                         if let Ok(true) = plugin.is_available() {
                             if let Ok(Some(default_version)) = plugin.default_version() {
@@ -270,6 +749,60 @@ pub fn run() -> Result<()> {
             info!("Running set command for setting: {setting:?}");
             crate::commands::set::set_config(setting)
         }
+        Some(Commands::Cache { action }) => {
+            info!("Running cache command: {action:?}");
+            crate::commands::cache::cache(action)
+        }
+        Some(Commands::Index { action }) => {
+            info!("Running index command: {action:?}");
+            crate::commands::index::index(action)
+        }
+        Some(Commands::Audit { path, json }) => {
+            info!("Running audit command: {}", path.display());
+            crate::commands::audit::audit(&path, json)
+        }
+        Some(Commands::Which { path }) => {
+            info!("Running which command: {}", path.display());
+            crate::commands::which::which(&path)
+        }
+        Some(Commands::Log { since, json }) => {
+            info!("Running log command (since: {since:?})");
+            crate::commands::log::log(since.as_deref(), json)
+        }
+        Some(Commands::Shims { action }) => {
+            info!("Running shims command: {action:?}");
+            crate::commands::shims::shims(action)
+        }
+        Some(Commands::Prompt { action }) => {
+            info!("Running prompt command: {action:?}");
+            crate::commands::prompt::prompt(action)
+        }
+        Some(Commands::Trust { path, revoke }) => {
+            info!(
+                "Running trust command: {} (revoke: {revoke})",
+                path.display()
+            );
+            crate::commands::trust::trust(&path, revoke)
+        }
+        Some(Commands::Check { path, strict }) => {
+            info!(
+                "Running check command: {} (strict: {strict})",
+                path.display()
+            );
+            crate::commands::check::check(&path, strict)
+        }
+        Some(Commands::Scan {
+            path,
+            exclude,
+            json,
+        }) => {
+            info!("Running scan command: {}", path.display());
+            crate::commands::scan::scan(&path, &exclude, json)
+        }
+        Some(Commands::Daemon { action }) => {
+            info!("Running daemon command: {action:?}");
+            crate::commands::daemon::daemon(action)
+        }
         Some(Commands::Uninstall { force }) => {
             info!("Running uninstall command (force: {force})");
             crate::commands::uninstall::uninstall(force)
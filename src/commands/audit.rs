@@ -0,0 +1,183 @@
+//! Audit command - report end-of-life Node.js versions pinned under a directory
+
+use crate::config::{Config, EolSeverity};
+use crate::output;
+use crate::release_index::ReleaseIndex;
+use crate::version_file::{parse_version, VersionFile, VersionRange};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::path::Path;
+
+/// Support status of one version file
+#[derive(Debug, Serialize)]
+struct AuditEntry {
+    /// Version file path, relative to the audited directory
+    path: String,
+    /// Version as written in the file
+    version: String,
+    /// Concrete version it resolves to, if known
+    resolved: Option<String>,
+    /// "ok", "eol", "near-eol", "outdated" or "unknown"
+    status: &'static str,
+    /// End-of-life date of the release line, if known
+    eol: Option<String>,
+    /// Newest release of the same major line
+    latest: Option<String>,
+    /// Human-readable details
+    message: Option<String>,
+}
+
+/// Scan `root` for version files and report their support status
+pub fn audit(root: &Path, json: bool) -> Result<()> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("cannot read directory: {}", root.display()))?;
+    let config = Config::load_for(&root)?;
+
+    let mut filenames = config.version_files.clone();
+    if !filenames.iter().any(|name| name == "package.json") {
+        filenames.push("package.json".to_string());
+    }
+
+    let index = ReleaseIndex::load_default();
+    let today = chrono::Local::now().date_naive();
+    let entries: Vec<AuditEntry> = VersionFile::find_all(&root, &filenames)?
+        .into_iter()
+        .map(|file| audit_file(&index, &root, file, today))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else if entries.is_empty() {
+        output::info(&format!("No version files found in {}", root.display()));
+    } else {
+        print_table(&entries);
+    }
+
+    let eol_pins = entries.iter().filter(|e| e.status == "eol").count();
+    if eol_pins > 0 && config.eol_severity == EolSeverity::Block {
+        anyhow::bail!("{eol_pins} version file(s) pin an end-of-life Node.js version");
+    }
+
+    Ok(())
+}
+
+/// Resolves a version file against the release index and checks its support status
+fn audit_file(
+    index: &ReleaseIndex,
+    root: &Path,
+    file: VersionFile,
+    today: NaiveDate,
+) -> AuditEntry {
+    let resolved = parse_version(&file.version)
+        .map(|version| version.to_string())
+        .or_else(|| index.resolve_alias(&file.version))
+        .or_else(|| {
+            let range = VersionRange::parse(&file.version).ok()?;
+            Some(index.best_match(&range)?.version.to_string())
+        });
+    let parsed = resolved.as_deref().and_then(parse_version);
+
+    let advisory = parsed
+        .as_ref()
+        .and_then(|version| index.advisory(version, today));
+    let status = match (&parsed, &advisory) {
+        (None, _) => "unknown",
+        (Some(_), Some(advisory)) => advisory.label(),
+        (Some(_), None) => "ok",
+    };
+    let latest = parsed.as_ref().and_then(|version| {
+        index
+            .releases()
+            .iter()
+            .find(|r| r.version.major == version.major)
+            .map(|r| r.version.to_string())
+    });
+    let eol = parsed
+        .as_ref()
+        .and_then(|version| index.line(version.major))
+        .and_then(|line| line.eol)
+        .map(|eol| eol.to_string());
+    let message = match (&advisory, &resolved) {
+        (Some(advisory), Some(resolved)) => Some(advisory.message(resolved)),
+        _ => None,
+    };
+
+    AuditEntry {
+        path: file
+            .path
+            .strip_prefix(root)
+            .unwrap_or(&file.path)
+            .display()
+            .to_string(),
+        version: file.version,
+        resolved,
+        status,
+        eol,
+        latest,
+        message,
+    }
+}
+
+/// Prints entries as an aligned table
+fn print_table(entries: &[AuditEntry]) {
+    let rows: Vec<Vec<&str>> = entries
+        .iter()
+        .map(|e| {
+            vec![
+                e.path.as_str(),
+                e.version.as_str(),
+                e.resolved.as_deref().unwrap_or("-"),
+                e.status,
+                e.eol.as_deref().unwrap_or("-"),
+            ]
+        })
+        .collect();
+    output::table(&["PATH", "VERSION", "RESOLVED", "STATUS", "EOL"], &rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version_file::VersionFileSource;
+
+    const TEST_INDEX: &str = r#"{
+        "releases": [
+            {"version": "22.11.0", "lts": "Jod"},
+            {"version": "16.20.2", "lts": "Gallium"},
+            {"version": "16.20.1", "lts": "Gallium"}
+        ],
+        "lines": [
+            {"major": 16, "codename": "Gallium", "eol": "2023-09-11"},
+            {"major": 22, "codename": "Jod", "eol": "2027-04-30"}
+        ]
+    }"#;
+
+    fn entry(version: &str) -> AuditEntry {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+        let root = Path::new("/project");
+        let file = VersionFile {
+            path: root.join("app/.nvmrc"),
+            version: version.to_string(),
+            source: VersionFileSource::Nvmrc,
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        audit_file(&index, root, file, today)
+    }
+
+    #[test]
+    fn test_audit_statuses() {
+        let eol = entry("16.20.1");
+        assert_eq!(eol.path, "app/.nvmrc");
+        assert_eq!(eol.status, "eol");
+        assert_eq!(eol.eol.as_deref(), Some("2023-09-11"));
+        assert_eq!(eol.latest.as_deref(), Some("16.20.2"));
+
+        // Aliases and partial versions resolve through the index
+        assert_eq!(entry("lts/gallium").resolved.as_deref(), Some("16.20.2"));
+        assert_eq!(entry("16").status, "eol");
+        assert_eq!(entry("v22.11.0").status, "ok");
+        assert_eq!(entry("lts/unknown").status, "unknown");
+    }
+}
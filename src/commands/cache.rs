@@ -0,0 +1,38 @@
+//! Cache command - inspect and clear the resolution cache
+
+use crate::cache::ResolutionCache;
+use crate::cli::CacheAction;
+use crate::output;
+use anyhow::Result;
+
+/// Run a cache action
+pub fn cache(action: CacheAction) -> Result<()> {
+    let cache = ResolutionCache::open_default()?;
+
+    match action {
+        CacheAction::Clear => {
+            let removed = cache.clear()?;
+            output::success(&format!(
+                "Cleared {removed} cache file{} from {}",
+                if removed == 1 { "" } else { "s" },
+                cache.dir().display()
+            ));
+        }
+        CacheAction::Status => {
+            let status = cache.status()?;
+            output::info(&format!("Cache directory: {}", status.dir.display()));
+            output::info(&format!(
+                "Cached version lists: {}",
+                if status.version_lists.is_empty() {
+                    "none".to_string()
+                } else {
+                    status.version_lists.join(", ")
+                }
+            ));
+            output::info(&format!("Cached resolutions: {}", status.resolutions));
+            output::info(&format!("Size: {} bytes", status.size_bytes));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Check command - verify a project's version files agree with each other
+
+use crate::config::{Config, VersionCheck};
+use crate::output;
+use crate::release_index::ReleaseIndex;
+use crate::scan;
+use crate::version_file::VersionFile;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Compare every Node.js pin in the project root containing `path`
+///
+/// Fails if any pins disagree and `strict` is set (or `version_check` is
+/// `strict`).
+pub fn check(path: &Path, strict: bool) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("cannot read directory: {}", path.display()))?;
+    let config = Config::load_for(&path)?;
+
+    // The project root is wherever activation would find its version file
+    let root = VersionFile::find(&path, &config.version_files)?
+        .and_then(|file| file.path.parent().map(Path::to_path_buf))
+        .unwrap_or(path);
+
+    let pins = scan::dir_pins(&root);
+    if pins.is_empty() {
+        output::info(&format!("No Node.js versions pinned in {}", root.display()));
+        return Ok(());
+    }
+
+    let summary: Vec<String> = pins
+        .iter()
+        .map(|pin| format!("  {}: {}", pin.source.label(), pin.version))
+        .collect();
+    output::info(&format!("{}\n{}", root.display(), summary.join("\n")));
+
+    let conflicts = scan::find_conflicts(&root, &pins, &ReleaseIndex::load_default());
+    if conflicts.is_empty() {
+        output::success("All version sources agree");
+        return Ok(());
+    }
+
+    for conflict in &conflicts {
+        output::warning(&conflict.message);
+    }
+    if strict || config.version_check == VersionCheck::Strict {
+        anyhow::bail!(
+            "{} version conflict(s) in {}",
+            conflicts.len(),
+            root.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Daemon command - start, stop and inspect the activation daemon
+
+use crate::cache::ResolutionCache;
+use crate::cli::DaemonAction;
+use crate::daemon::{self, DaemonClient, Request, Response, Server};
+use crate::output;
+use anyhow::{Context, Result};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long `anvs daemon start` waits for the background daemon to answer
+const START_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Run a daemon action
+pub fn daemon(action: DaemonAction) -> Result<()> {
+    let socket = daemon::socket_path()?;
+    let client = DaemonClient::new(&socket);
+
+    match action {
+        DaemonAction::Start {
+            foreground,
+            idle_timeout,
+        } => {
+            if client.is_running() {
+                output::info(&format!("Daemon already running on {}", socket.display()));
+                return Ok(());
+            }
+
+            if foreground {
+                let mut server = Server::new(&socket, Duration::from_secs(idle_timeout));
+                if let Ok(cache) = ResolutionCache::open_default() {
+                    server = server.with_cache(Arc::new(cache));
+                }
+                return server.run();
+            }
+
+            spawn_background(idle_timeout)?;
+            if !client.wait_until_running(START_TIMEOUT) {
+                anyhow::bail!(
+                    "daemon did not start (try 'anvs daemon start --foreground' to see errors)"
+                );
+            }
+            output::success(&format!("Daemon started on {}", socket.display()));
+        }
+        DaemonAction::Stop => {
+            if !client.is_running() {
+                output::info("Daemon is not running");
+                return Ok(());
+            }
+            client.request(&Request::Shutdown)?;
+            output::success("Daemon stopped");
+        }
+        DaemonAction::Status => match client.request(&Request::Status) {
+            Ok(Response::Status {
+                pid,
+                uptime_secs,
+                requests,
+                cached,
+                watched,
+            }) => {
+                output::info(&format!("Daemon running (pid {pid})"));
+                output::info(&format!("Socket: {}", socket.display()));
+                output::info(&format!("Uptime: {uptime_secs}s"));
+                output::info(&format!("Requests served: {requests}"));
+                output::info(&format!("Memoized activations: {cached}"));
+                output::info(&format!("Watched paths: {watched}"));
+            }
+            _ => output::info("Daemon is not running"),
+        },
+    }
+
+    Ok(())
+}
+
+/// Starts `anvs daemon start --foreground` detached from the terminal
+fn spawn_background(idle_timeout: u64) -> Result<()> {
+    let exe = std::env::current_exe().context("failed to locate the anvs binary")?;
+
+    // Never let the daemon inherit the shell's FD:3, or the shell hook would
+    // wait for it to close
+    unsafe {
+        if libc::fcntl(3, libc::F_GETFD) != -1 {
+            libc::fcntl(3, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+    }
+
+    let mut command = Command::new(exe);
+    command
+        .args(["daemon", "start", "--foreground", "--idle-timeout"])
+        .arg(idle_timeout.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // Start a new session so the daemon survives the terminal closing
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+
+    command.spawn().context("failed to start daemon")?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+//! Hook check command - fast idempotency check for the shell hook
+//!
+//! Provides `anvs hook-check <dir> <active-key>`, which the shell hook calls on
+//! every directory change. It reuses `WorkspacePins::locate` so the shell and the
+//! binary always agree about which version file wins.
+
+use crate::config::Config;
+use crate::version_file::{VersionFile, WorkspacePins};
+use anyhow::{Context, Result};
+use log::debug;
+use std::path::Path;
+
+/// Output printed when no version file is found
+pub const NO_VERSION_FILE: &str = "none";
+
+/// Output printed when the active key still matches
+pub const UNCHANGED: &str = "unchanged";
+
+/// Result of comparing the current directory against the active key
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookStatus {
+    /// No version file found (shell should leave the project, if any)
+    NoVersionFile,
+    /// Same version file and version as the active key (nothing to do)
+    Unchanged,
+    /// A different version file or version; contains the new active key
+    Changed(String),
+}
+
+impl HookStatus {
+    /// Text printed to stdout for the shell hook
+    pub fn as_output(&self) -> &str {
+        match self {
+            Self::NoVersionFile => NO_VERSION_FILE,
+            Self::Unchanged => UNCHANGED,
+            Self::Changed(key) => key,
+        }
+    }
+}
+
+/// Builds the active key for a version file
+///
+/// The key combines the file path with the version it declares, so edits that
+/// don't change the version (e.g., other package.json fields) don't re-activate.
+pub fn active_key(version_file: &VersionFile) -> String {
+    format!("{}:{}", version_file.path.display(), version_file.version)
+}
+
+/// Builds the active key for the version files of a workspace
+///
+/// Includes both the workspace root's pin and the nearest version file, so
+/// edits to either re-activate.
+fn workspace_key(pins: &WorkspacePins) -> String {
+    match &pins.root {
+        Some(root) => format!("{}|{}", active_key(root), active_key(&pins.nearest)),
+        None => active_key(&pins.nearest),
+    }
+}
+
+/// Compares the version file for `path` against the shell's active key
+pub fn check(path: &Path, config: &Config, active: &str) -> Result<HookStatus> {
+    let pins = match WorkspacePins::locate(path, config)? {
+        Some(pins) => pins,
+        None => return Ok(HookStatus::NoVersionFile),
+    };
+
+    let key = workspace_key(&pins);
+    debug!("Hook check key: {key} (active: {active})");
+
+    if key == active {
+        Ok(HookStatus::Unchanged)
+    } else {
+        Ok(HookStatus::Changed(key))
+    }
+}
+
+/// Run the hook check and print the result for the shell hook
+pub fn hook_check(path: &Path, active: &str) -> Result<()> {
+    let config = Config::load().context("failed to load configuration")?;
+    let status = check(path, &config, active)?;
+    println!("{}", status.as_output());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceStrategy;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn config(names: &[&str], workspace_strategy: WorkspaceStrategy) -> Config {
+        Config {
+            version_files: names.iter().map(|s| s.to_string()).collect(),
+            workspace_strategy,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_no_version_file() {
+        let temp = TempDir::new().unwrap();
+        let status = check(
+            temp.path(),
+            &config(&[".nvmrc"], WorkspaceStrategy::Nearest),
+            "",
+        )
+        .unwrap();
+        assert_eq!(status, HookStatus::NoVersionFile);
+        assert_eq!(status.as_output(), "none");
+    }
+
+    #[test]
+    fn test_changed_then_unchanged() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(".nvmrc"), "18.20.0").unwrap();
+
+        let status = check(
+            temp.path(),
+            &config(&[".nvmrc"], WorkspaceStrategy::Nearest),
+            "",
+        )
+        .unwrap();
+        let key = match status {
+            HookStatus::Changed(key) => key,
+            other => panic!("Expected Changed, got {other:?}"),
+        };
+        assert!(key.ends_with(".nvmrc:18.20.0"));
+
+        let status = check(
+            temp.path(),
+            &config(&[".nvmrc"], WorkspaceStrategy::Nearest),
+            &key,
+        )
+        .unwrap();
+        assert_eq!(status, HookStatus::Unchanged);
+    }
+
+    #[test]
+    fn test_version_edit_changes_key() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(".nvmrc"), "18.20.0").unwrap();
+        let first = check(
+            temp.path(),
+            &config(&[".nvmrc"], WorkspaceStrategy::Nearest),
+            "",
+        )
+        .unwrap();
+
+        fs::write(temp.path().join(".nvmrc"), "20.11.0").unwrap();
+        let HookStatus::Changed(key) = first else {
+            panic!("Expected Changed");
+        };
+        let second = check(
+            temp.path(),
+            &config(&[".nvmrc"], WorkspaceStrategy::Nearest),
+            &key,
+        )
+        .unwrap();
+        assert!(matches!(second, HookStatus::Changed(k) if k.ends_with(":20.11.0")));
+    }
+
+    #[test]
+    fn test_workspace_root_pin_key() {
+        let temp = TempDir::new().unwrap();
+        let package = temp.path().join("packages/api");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(temp.path().join("lerna.json"), "{}").unwrap();
+        fs::write(temp.path().join(".nvmrc"), "20.11.0").unwrap();
+        fs::write(package.join(".nvmrc"), ">=18").unwrap();
+
+        let status = check(&package, &config(&[".nvmrc"], WorkspaceStrategy::Root), "").unwrap();
+        let HookStatus::Changed(key) = status else {
+            panic!("Expected Changed");
+        };
+        assert!(key.contains(".nvmrc:20.11.0|"));
+        assert!(key.ends_with("api/.nvmrc:>=18"));
+    }
+
+    #[test]
+    fn test_package_json_without_engines_is_skipped() {
+        // The shell used to pick up any package.json; the binary skips ones without engines
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("package.json"), r#"{"name": "app"}"#).unwrap();
+        fs::write(temp.path().join(".node-version"), "20.11.0").unwrap();
+
+        let status = check(
+            temp.path(),
+            &config(
+                &["package.json", ".node-version"],
+                WorkspaceStrategy::Nearest,
+            ),
+            "",
+        )
+        .unwrap();
+        assert!(matches!(status, HookStatus::Changed(k) if k.ends_with(".node-version:20.11.0")));
+    }
+}
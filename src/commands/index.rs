@@ -0,0 +1,62 @@
+//! Index command - refresh and inspect the offline Node.js release index
+
+use crate::cli::IndexAction;
+use crate::output;
+use crate::release_index::ReleaseIndex;
+use anyhow::Result;
+
+/// Run an index action
+pub fn index(action: IndexAction) -> Result<()> {
+    let path = ReleaseIndex::default_path()?;
+
+    match action {
+        IndexAction::Update { source } => {
+            output::info(&format!("Fetching release index from {source}..."));
+            let mut index = ReleaseIndex::fetch(&source)?;
+            if index.releases().is_empty() {
+                anyhow::bail!("{source} contains no Node.js releases");
+            }
+
+            // Keep EOL dates the source doesn't provide
+            index.merge_lines(&ReleaseIndex::load_default());
+            index.save(&path)?;
+
+            output::success(&format!(
+                "Saved {} releases to {}",
+                index.releases().len(),
+                path.display()
+            ));
+            print_summary(&index);
+        }
+        IndexAction::Status => {
+            let index = ReleaseIndex::load_default();
+            if path.exists() {
+                output::info(&format!("Index: {}", path.display()));
+            } else {
+                output::info("Index: bundled (run 'anvs index update' to refresh)");
+            }
+            output::info(&format!("Releases: {}", index.releases().len()));
+            print_summary(&index);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the index date and newest releases
+fn print_summary(index: &ReleaseIndex) {
+    output::info(&format!(
+        "Updated: {}",
+        index.updated().unwrap_or("unknown")
+    ));
+    if let Some(latest) = index.latest() {
+        output::info(&format!("Latest: {}", latest.version));
+    }
+    if let Some(lts) = index.latest_lts() {
+        output::info(&format!(
+            "Latest LTS: {} ({})",
+            lts.version,
+            lts.lts.as_deref().unwrap_or_default()
+        ));
+    }
+}
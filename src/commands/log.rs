@@ -0,0 +1,187 @@
+//! Log command - show the activation history
+
+use crate::activation::{ActivationLog, ActivationRecord};
+use crate::output;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, TimeZone};
+
+/// Print recorded activations, optionally only those since `since`
+///
+/// `since` is a duration ago ("30m", "2h", "1d", "1w"), a date
+/// ("2026-10-01") or an RFC 3339 timestamp.
+pub fn log(since: Option<&str>, json: bool) -> Result<()> {
+    let since = since
+        .map(|since| parse_since(since, Local::now().fixed_offset()))
+        .transpose()?;
+
+    let log = ActivationLog::open_default()?;
+    let records: Vec<ActivationRecord> = log
+        .read()
+        .into_iter()
+        .filter(|record| match (since, record.time()) {
+            (Some(since), Some(time)) => time >= since,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .collect();
+
+    if json {
+        for record in &records {
+            println!("{}", serde_json::to_string(record)?);
+        }
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        output::info(&format!(
+            "No activations recorded in {}",
+            log.path().display()
+        ));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = records.iter().map(row).collect();
+    output::table(
+        &[
+            "TIME",
+            "DIRECTORY",
+            "VERSION FILE",
+            "VERSION",
+            "PLUGIN",
+            "MS",
+            "RESULT",
+        ],
+        &rows,
+    );
+    Ok(())
+}
+
+/// Table row for one record
+fn row(record: &ActivationRecord) -> Vec<String> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let tilde = |path: &std::path::Path| match path.strip_prefix(&home) {
+        Ok(rest) if !home.as_os_str().is_empty() => format!("~/{}", rest.display()),
+        _ => path.display().to_string(),
+    };
+
+    let result = match (&record.error, record.commands.is_empty()) {
+        (Some(error), _) => format!("error: {error}"),
+        (None, true) => "no change".to_string(),
+        (None, false) if record.daemon => "switched (daemon)".to_string(),
+        (None, false) => "switched".to_string(),
+    };
+
+    vec![
+        record
+            .time()
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| record.timestamp.clone()),
+        tilde(&record.cwd),
+        record
+            .version_file
+            .as_deref()
+            .map(tilde)
+            .unwrap_or_else(|| "-".to_string()),
+        match (&record.requirement, &record.version) {
+            (Some(requirement), Some(version)) if requirement != version => {
+                format!("{requirement} → {version}")
+            }
+            (_, Some(version)) => version.clone(),
+            _ => "-".to_string(),
+        },
+        record.plugin.clone().unwrap_or_else(|| "-".to_string()),
+        record.duration_ms.to_string(),
+        result,
+    ]
+}
+
+/// Parses `--since` relative to `now`
+fn parse_since(since: &str, now: DateTime<FixedOffset>) -> Result<DateTime<FixedOffset>> {
+    let since = since.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(since) {
+        return Ok(time);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+        return now
+            .timezone()
+            .from_local_datetime(&midnight)
+            .single()
+            .context("ambiguous local date");
+    }
+
+    let split = since.len() - since.chars().last().map_or(0, char::len_utf8);
+    let (amount, unit) = since.split_at(split);
+    let amount: i64 = amount.parse().with_context(|| {
+        format!("invalid --since '{since}' (expected e.g. 30m, 2h, 1d, 1w or 2026-10-01)")
+    })?;
+    let ago = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => anyhow::bail!("invalid --since unit '{unit}' (expected s, m, h, d or w)"),
+    };
+    ago.and_then(|ago| now.checked_sub_signed(ago))
+        .with_context(|| format!("--since '{since}' is out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        let now = DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00").unwrap();
+
+        assert_eq!(
+            parse_since("90m", now).unwrap().to_rfc3339(),
+            "2026-10-18T10:30:00+02:00"
+        );
+        assert_eq!(
+            parse_since("1w", now).unwrap().to_rfc3339(),
+            "2026-10-11T12:00:00+02:00"
+        );
+        assert_eq!(
+            parse_since("2026-10-01", now).unwrap().to_rfc3339(),
+            "2026-10-01T00:00:00+02:00"
+        );
+        assert_eq!(
+            parse_since("2026-10-17T08:00:00Z", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-10-17T08:00:00+00:00"
+        );
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("5y", now).is_err());
+
+        // Out-of-range durations are errors, not panics
+        assert!(parse_since("999999999d", now).is_err());
+        assert!(parse_since("9223372036854775807w", now).is_err());
+        assert!(parse_since("-999999999d", now).is_err());
+    }
+
+    #[test]
+    fn test_row() {
+        let record = ActivationRecord {
+            requirement: Some(">=18".to_string()),
+            version: Some("20.11.0".to_string()),
+            plugin: Some("nvm".to_string()),
+            commands: vec!["nvm use 20.11.0".to_string()],
+            duration_ms: 12,
+            ..ActivationRecord::start(std::path::Path::new("/work/app"))
+        };
+        let cells = row(&record);
+        assert_eq!(cells[1], "/work/app");
+        assert_eq!(cells[3], ">=18 → 20.11.0");
+        assert_eq!(cells[6], "switched");
+
+        let failed = ActivationRecord {
+            error: Some("Node.js 99 is not installed".to_string()),
+            ..record
+        };
+        assert_eq!(row(&failed)[6], "error: Node.js 99 is not installed");
+    }
+}
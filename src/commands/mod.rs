@@ -1,4 +1,16 @@
 //! Command implementations for anvs
 
+pub mod audit;
+pub mod cache;
+pub mod check;
+pub mod daemon;
+pub mod hook_check;
+pub mod index;
+pub mod log;
+pub mod prompt;
+pub mod scan;
 pub mod set;
+pub mod shims;
+pub mod trust;
 pub mod uninstall;
+pub mod which;
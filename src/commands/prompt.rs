@@ -0,0 +1,33 @@
+//! Prompt command - forget remembered install prompt answers
+
+use crate::activation::PromptState;
+use crate::cli::PromptAction;
+use crate::output;
+use anyhow::{Context, Result};
+
+/// Run a prompt action
+pub fn prompt(action: PromptAction) -> Result<()> {
+    let mut state = PromptState::load_default()?;
+
+    match action {
+        PromptAction::Reset { path } => {
+            let dir = path
+                .map(|path| {
+                    path.canonicalize()
+                        .with_context(|| format!("cannot access {}", path.display()))
+                })
+                .transpose()?;
+            let removed = state.reset(dir.as_deref())?;
+            if removed == 0 {
+                output::info("No remembered install answers to forget");
+            } else {
+                output::success(&format!(
+                    "Forgot install answers for {removed} project{}",
+                    if removed == 1 { "" } else { "s" }
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
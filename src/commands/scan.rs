@@ -0,0 +1,85 @@
+//! Scan command - map every Node.js version pinned under a directory
+
+use crate::output;
+use crate::release_index::ReleaseIndex;
+use crate::scan::{self, Excludes, ScanReport};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Scan `root` and print its pins, distinct versions and conflicts
+pub fn scan(root: &Path, exclude: &[String], json: bool) -> Result<()> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("cannot read directory: {}", root.display()))?;
+
+    let mut excludes = Excludes::default();
+    for pattern in exclude {
+        excludes.add(&root, pattern);
+    }
+
+    let report = scan::scan(&root, excludes, &ReleaseIndex::load_default())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&root, &report);
+    }
+
+    Ok(())
+}
+
+/// Prints the report as tables followed by a summary
+fn print_report(root: &Path, report: &ScanReport) {
+    if report.pins.is_empty() {
+        output::info(&format!("No Node.js versions pinned in {}", root.display()));
+        return;
+    }
+
+    let relative = |path: &Path| match path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => path.display().to_string(),
+    };
+
+    let rows: Vec<Vec<String>> = report
+        .pins
+        .iter()
+        .map(|pin| {
+            vec![
+                relative(&pin.path),
+                pin.source.label().to_string(),
+                pin.version.clone(),
+            ]
+        })
+        .collect();
+    output::table(&["PATH", "SOURCE", "VERSION"], &rows);
+    println!();
+
+    let versions = report.versions();
+    let rows: Vec<Vec<String>> = versions
+        .iter()
+        .map(|(version, count)| vec![version.to_string(), count.to_string()])
+        .collect();
+    output::table(&["VERSION", "PINS"], &rows);
+    println!();
+
+    for conflict in &report.conflicts {
+        output::warning(&format!(
+            "{}: {}",
+            relative(&conflict.dir),
+            conflict.message
+        ));
+    }
+
+    let summary = format!(
+        "{} pins, {} distinct versions, {} conflicts",
+        report.pins.len(),
+        versions.len(),
+        report.conflicts.len()
+    );
+    if report.conflicts.is_empty() {
+        output::success(&summary);
+    } else {
+        output::warning(&summary);
+    }
+}
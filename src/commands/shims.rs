@@ -0,0 +1,32 @@
+//! Shims command - install node, npm, npx and corepack shims
+
+use crate::cli::ShimsAction;
+use crate::output;
+use crate::shims;
+use anyhow::{Context, Result};
+
+/// Run a shims action
+pub fn shims(action: ShimsAction) -> Result<()> {
+    match action {
+        ShimsAction::Install => {
+            let dir = shims::shims_dir()?;
+            let exe = std::env::current_exe()
+                .and_then(std::fs::canonicalize)
+                .context("cannot locate the anvs binary")?;
+            let created = shims::install(&dir, &exe)?;
+
+            output::success(&format!(
+                "Installed {} shims in {}",
+                created.len(),
+                dir.display()
+            ));
+            output::info(&format!(
+                "Put it first on PATH for processes that don't run the shell hook \
+                 (IDEs, cron, git hooks), e.g. in ~/.profile:\n  export PATH=\"{}:$PATH\"",
+                dir.display()
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Trust command - allow or revoke a project's hooks
+
+use crate::config::Config;
+use crate::hooks::{HookStage, TrustStore};
+use crate::output;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Trust (or with `revoke`, untrust) the hooks in the .anvs.yaml above `path`
+pub fn trust(path: &Path, revoke: bool) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("cannot read directory: {}", path.display()))?;
+    let mut store = TrustStore::load_default()?;
+
+    if revoke {
+        let Some(config_path) = Config::find_project_config(&path)? else {
+            anyhow::bail!("no .anvs.yaml found above {}", path.display());
+        };
+        if store.revoke(&config_path)? {
+            output::success(&format!("Revoked trust for {}", config_path.display()));
+        } else {
+            output::info(&format!("{} was not trusted", config_path.display()));
+        }
+        return Ok(());
+    }
+
+    let config = Config::load_for(&path)?;
+    let Some(project) = &config.project_hooks else {
+        output::info(&format!("No project hooks apply to {}", path.display()));
+        return Ok(());
+    };
+
+    let mut lines = vec![format!("Hooks in {}:", project.path.display())];
+    for stage in [
+        HookStage::PreActivate,
+        HookStage::PostActivate,
+        HookStage::OnLeave,
+    ] {
+        for command in stage.commands(&project.hooks) {
+            lines.push(format!("  {}: {command}", stage.key()));
+        }
+    }
+    output::info(&lines.join("\n"));
+
+    store.trust(project)?;
+    output::success(&format!("Trusted {}", project.path.display()));
+    Ok(())
+}
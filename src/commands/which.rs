@@ -0,0 +1,63 @@
+//! Which command - show which version file or path rule applies to a directory
+
+use crate::activation::Orchestrator;
+use crate::config::Config;
+use crate::output;
+use crate::plugins::PluginRegistry;
+use crate::shell::CommandWriter;
+use crate::version_file::VersionFileSource;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Print the version source, resolved version and preferred plugin for `path`
+pub fn which(path: &Path) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("cannot read directory: {}", path.display()))?;
+    let config = Config::load_for(&path)?;
+    let registry = PluginRegistry::from_config(&config);
+    let mut writer = CommandWriter::disabled();
+    let orchestrator = Orchestrator::new(&config, &registry, &mut writer);
+
+    let rule = config.rule_for(&path);
+    let Some(target) = orchestrator.find_target(&path)? else {
+        output::info(&format!(
+            "No version file or rule applies to {}",
+            path.display()
+        ));
+        return Ok(());
+    };
+
+    let file = &target.version_file;
+    let mut lines = vec![if file.source == VersionFileSource::Rule {
+        let source = config
+            .rules_source
+            .as_deref()
+            .map_or("config".into(), Path::to_string_lossy);
+        format!("Rule: {} (from {source})", file.path.display())
+    } else {
+        format!("Version file: {}", file.path.display())
+    }];
+
+    if target.version == file.version {
+        lines.push(format!("Version: {}", file.version));
+    } else {
+        lines.push(format!("Version: {} → {}", file.version, target.version));
+    }
+
+    if let Some(plugin) = &target.plugin {
+        lines.push(format!("Plugin: {plugin} (from rule)"));
+    }
+
+    if let Some(rule) = rule.filter(|_| file.source != VersionFileSource::Rule) {
+        if rule.version.is_some() {
+            lines.push(format!(
+                "Rule {} also matches, but the version file takes precedence",
+                rule.path
+            ));
+        }
+    }
+
+    output::info(&lines.join("\n"));
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use super::schema::Config;
+use super::schema::{
+    AbiCheck, Config, EolSeverity, InstallMode, LeaveAction, PackageManagerMode, ProjectHooks,
+    RangeStrategy, RemoteVersionPolicy, RulePrecedence, VersionCheck, WorkspaceStrategy,
+    DEFAULT_ACTIVATION_TIMEOUT_MS, DEFAULT_COMMAND_TIMEOUT_MS,
+};
 use anyhow::{Context, Result};
 use log::debug;
 use std::fs;
@@ -8,21 +12,50 @@ impl Config {
     /// Load configuration from all sources with proper precedence
     /// Precedence: project config > user config > defaults
     pub fn load() -> Result<Self> {
-        debug!("Loading configuration");
+        let start_dir = std::env::current_dir().context("failed to get current directory")?;
+        Self::load_for(&start_dir)
+    }
+
+    /// Load configuration as seen from `start_dir` (project config is searched upward from it)
+    pub fn load_for(start_dir: &Path) -> Result<Self> {
+        Self::load_with(&Self::user_config_path()?, start_dir)
+    }
+
+    /// Load configuration as seen from `start_dir`, with the user config at `user_config_path`
+    fn load_with(user_config_path: &Path, start_dir: &Path) -> Result<Self> {
+        debug!("Loading configuration for {}", start_dir.display());
 
         let mut config = Self::default();
         debug!("Using default config: {config:?}");
 
         // 1. Load user config: ~/.anvsrc
-        if let Some(user_config) = Self::load_user_config()? {
+        if let Some(user_config) = Self::load_user_config(user_config_path)? {
             debug!("Merging user config: {user_config:?}");
+            if !user_config.rules.is_empty() {
+                config.rules_source = Some(user_config_path.to_path_buf());
+            }
             config = config.merge(user_config);
         }
 
         // 2. Load project config: walk up from cwd to find .anvs.yaml
-        if let Some(project_config) = Self::load_project_config()? {
+        // Project hooks are kept apart so they can be trust-checked, and
+        // abi_check is ignored: `rebuild` would run commands without trust
+        if let Some(mut project_config) = Self::load_project_config(start_dir)? {
             debug!("Merging project config: {project_config:?}");
+            let hooks = std::mem::take(&mut project_config.hooks);
+            if std::mem::take(&mut project_config.abi_check) != AbiCheck::default() {
+                debug!("Ignoring abi_check in project config, it is only read from ~/.anvsrc");
+            }
+            let has_rules = !project_config.rules.is_empty();
             config = config.merge(project_config);
+
+            let path = Self::find_project_config(start_dir)?;
+            if has_rules {
+                config.rules_source.clone_from(&path);
+            }
+            if !hooks.is_empty() {
+                config.project_hooks = path.map(|path| ProjectHooks { path, hooks });
+            }
         }
 
         // 3. Validate final configuration
@@ -32,27 +65,27 @@ impl Config {
         Ok(config)
     }
 
-    /// Load user configuration from ~/.anvsrc
-    fn load_user_config() -> Result<Option<Self>> {
+    /// Path to the user configuration file (~/.anvsrc)
+    pub fn user_config_path() -> Result<PathBuf> {
         let home =
             dirs::home_dir().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+        Ok(home.join(".anvsrc"))
+    }
 
-        let path = home.join(".anvsrc");
-
+    /// Load user configuration from `path` (normally ~/.anvsrc)
+    fn load_user_config(path: &Path) -> Result<Option<Self>> {
         if !path.exists() {
             debug!("User config not found at {path:?}");
             return Ok(None);
         }
 
         debug!("Loading user config from {path:?}");
-        Self::load_from_file(&path).map(Some)
+        Self::load_from_file(path).map(Some)
     }
 
     /// Load project configuration from .anvs.yaml (walk up directory tree)
-    fn load_project_config() -> Result<Option<Self>> {
-        let start_dir = std::env::current_dir().context("failed to get current directory")?;
-
-        let config_path = Self::find_project_config(&start_dir)?;
+    fn load_project_config(start_dir: &Path) -> Result<Option<Self>> {
+        let config_path = Self::find_project_config(start_dir)?;
 
         if let Some(path) = config_path {
             debug!("Loading project config from {path:?}");
@@ -63,8 +96,17 @@ impl Config {
         }
     }
 
+    /// Load the hooks of the project config above `start_dir`, if it defines any
+    pub fn load_project_hooks(start_dir: &Path) -> Result<Option<ProjectHooks>> {
+        let Some(path) = Self::find_project_config(start_dir)? else {
+            return Ok(None);
+        };
+        let hooks = Self::load_from_file(&path)?.hooks;
+        Ok((!hooks.is_empty()).then_some(ProjectHooks { path, hooks }))
+    }
+
     /// Find .anvs.yaml by walking up directory tree (stop at HOME)
-    fn find_project_config(start_dir: &Path) -> Result<Option<PathBuf>> {
+    pub(crate) fn find_project_config(start_dir: &Path) -> Result<Option<PathBuf>> {
         let home = dirs::home_dir().unwrap_or_default();
         let mut dir = start_dir.to_path_buf();
 
@@ -120,6 +162,70 @@ impl Config {
             self.version_files = other.version_files;
         }
 
+        if other.leave_action != LeaveAction::Default {
+            self.leave_action = other.leave_action;
+        }
+
+        if other.command_timeout_ms != DEFAULT_COMMAND_TIMEOUT_MS {
+            self.command_timeout_ms = other.command_timeout_ms;
+        }
+
+        if other.activation_timeout_ms != DEFAULT_ACTIVATION_TIMEOUT_MS {
+            self.activation_timeout_ms = other.activation_timeout_ms;
+        }
+
+        if other.remote_version_policy != RemoteVersionPolicy::default() {
+            self.remote_version_policy = other.remote_version_policy;
+        }
+
+        if other.range_strategy != RangeStrategy::default() {
+            self.range_strategy = other.range_strategy;
+        }
+
+        if other.eol_severity != EolSeverity::default() {
+            self.eol_severity = other.eol_severity;
+        }
+
+        if other.version_check != VersionCheck::default() {
+            self.version_check = other.version_check;
+        }
+
+        if other.workspace_strategy != WorkspaceStrategy::default() {
+            self.workspace_strategy = other.workspace_strategy;
+        }
+
+        if !other.rules.is_empty() {
+            self.rules = other.rules;
+        }
+
+        if other.rule_precedence != RulePrecedence::default() {
+            self.rule_precedence = other.rule_precedence;
+        }
+
+        if other.package_manager != PackageManagerMode::default() {
+            self.package_manager = other.package_manager;
+        }
+
+        if !other.auto_install_rules.is_empty() {
+            self.auto_install_rules = other.auto_install_rules;
+        }
+
+        if other.install_mode != InstallMode::default() {
+            self.install_mode = other.install_mode;
+        }
+
+        if other.install_plugin.is_some() {
+            self.install_plugin = other.install_plugin;
+        }
+
+        if other.abi_check != AbiCheck::default() {
+            self.abi_check = other.abi_check;
+        }
+
+        if !other.hooks.is_empty() {
+            self.hooks = other.hooks;
+        }
+
         self
     }
 }
@@ -177,6 +283,224 @@ mod tests {
         assert_eq!(merged.version_files, vec![".node-version", ".nvmrc"]);
     }
 
+    #[test]
+    fn test_merge_leave_action() {
+        let base = Config {
+            leave_action: LeaveAction::Restore,
+            ..Config::default()
+        };
+
+        // A config that doesn't set leave_action keeps the base value
+        let merged = base.clone().merge(Config::default());
+        assert_eq!(merged.leave_action, LeaveAction::Restore);
+
+        let merged = Config::default().merge(base);
+        assert_eq!(merged.leave_action, LeaveAction::Restore);
+    }
+
+    #[test]
+    fn test_merge_timeouts() {
+        let base = Config {
+            command_timeout_ms: 2_000,
+            ..Config::default()
+        };
+        let project = Config {
+            activation_timeout_ms: 0,
+            ..Config::default()
+        };
+
+        let merged = base.merge(project);
+        assert_eq!(merged.command_timeout_ms, 2_000);
+        assert_eq!(merged.activation_timeout(), None);
+        assert_eq!(
+            merged.command_timeout(),
+            Some(std::time::Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn test_merge_remote_version_policy() {
+        let base = Config {
+            remote_version_policy: RemoteVersionPolicy::Lowest,
+            ..Config::default()
+        };
+
+        let merged = base.clone().merge(Config::default());
+        assert_eq!(merged.remote_version_policy, RemoteVersionPolicy::Lowest);
+
+        let parsed: Config = serde_yaml::from_str("remote_version_policy: newest\n").unwrap();
+        let merged = base.merge(parsed);
+        assert_eq!(merged.remote_version_policy, RemoteVersionPolicy::Newest);
+    }
+
+    #[test]
+    fn test_project_config_sets_range_strategy() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(".anvs.yaml"),
+            "range_strategy: lowest\n",
+        )
+        .unwrap();
+
+        let base = Config {
+            range_strategy: RangeStrategy::Highest,
+            ..Config::default()
+        };
+        let project = Config::load_project_config(temp_dir.path())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(base.merge(project).range_strategy, RangeStrategy::Lowest);
+    }
+
+    #[test]
+    fn test_merge_eol_severity() {
+        let parsed: Config = serde_yaml::from_str("eol_severity: block\n").unwrap();
+        assert_eq!(parsed.eol_severity, EolSeverity::Block);
+
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.eol_severity, EolSeverity::Block);
+    }
+
+    #[test]
+    fn test_merge_package_manager() {
+        let parsed: Config = serde_yaml::from_str("package_manager: corepack\n").unwrap();
+        assert_eq!(parsed.package_manager, PackageManagerMode::Corepack);
+
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.package_manager, PackageManagerMode::Corepack);
+    }
+
+    #[test]
+    fn test_project_hooks_kept_separate() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(".anvs.yaml");
+        fs::write(
+            &config_path,
+            "hooks:\n  post_activate:\n    - npm rebuild\n",
+        )
+        .unwrap();
+
+        let user_config = temp_dir.path().join(".anvsrc");
+        let config = Config::load_with(&user_config, temp_dir.path()).unwrap();
+        let project = config.project_hooks.unwrap();
+        assert_eq!(project.path, config_path);
+        assert_eq!(project.hooks.post_activate, vec!["npm rebuild"]);
+        assert!(!config
+            .hooks
+            .post_activate
+            .contains(&"npm rebuild".to_string()));
+    }
+
+    #[test]
+    fn test_project_abi_check_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".anvs.yaml"), "abi_check: rebuild\n").unwrap();
+
+        let user_config = temp_dir.path().join(".anvsrc");
+        fs::write(&user_config, "plugins: [nvm]\nabi_check: warn\n").unwrap();
+
+        // Same as without a project config
+        let config = Config::load_with(&user_config, &project).unwrap();
+        assert_eq!(config.abi_check, AbiCheck::Warn);
+    }
+
+    #[test]
+    fn test_merge_auto_install_rules() {
+        let parsed: Config =
+            serde_yaml::from_str("auto_install_rules:\n  - eol: true\n    auto_install: never\n")
+                .unwrap();
+        assert_eq!(parsed.auto_install_rules.len(), 1);
+        assert_eq!(parsed.auto_install_rules[0].eol, Some(true));
+
+        let merged = Config::default().merge(parsed.clone());
+        assert_eq!(merged.auto_install_rules, parsed.auto_install_rules);
+
+        // A project without rules keeps the user's
+        let merged = merged.merge(Config::default());
+        assert_eq!(merged.auto_install_rules.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_install_mode() {
+        let parsed: Config = serde_yaml::from_str("install_mode: process\n").unwrap();
+        assert_eq!(parsed.install_mode, InstallMode::Process);
+
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.install_mode, InstallMode::Process);
+    }
+
+    #[test]
+    fn test_merge_install_plugin() {
+        let parsed: Config = serde_yaml::from_str("install_plugin: fnm\n").unwrap();
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.install_plugin.as_deref(), Some("fnm"));
+        assert!(merged.validate().is_ok());
+
+        // A project without one keeps the user's
+        let merged = merged.merge(Config::default());
+        assert_eq!(merged.install_plugin.as_deref(), Some("fnm"));
+
+        let unknown = Config {
+            install_plugin: Some("volta".to_string()),
+            ..Config::default()
+        };
+        assert!(unknown.validate().is_err());
+    }
+
+    #[test]
+    fn test_merge_abi_check() {
+        let parsed: Config = serde_yaml::from_str("abi_check: rebuild\n").unwrap();
+        assert_eq!(parsed.abi_check, AbiCheck::Rebuild);
+
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.abi_check, AbiCheck::Rebuild);
+    }
+
+    #[test]
+    fn test_merge_workspace_strategy() {
+        let parsed: Config =
+            serde_yaml::from_str("workspace_strategy: root-satisfies-nearest\n").unwrap();
+        let merged = Config::default().merge(parsed);
+        assert_eq!(
+            merged.workspace_strategy,
+            WorkspaceStrategy::RootSatisfiesNearest
+        );
+    }
+
+    #[test]
+    fn test_path_rules() {
+        let home = dirs::home_dir().unwrap();
+        let yaml = "rules:\n  \
+                    - path: ~/work/legacy/**\n    version: \"14\"\n  \
+                    - path: /srv/*/app\n    plugin: fnm\n\
+                    rule_precedence: rule\n";
+        let config = Config::default().merge(serde_yaml::from_str(yaml).unwrap());
+        assert_eq!(config.rule_precedence, RulePrecedence::Rule);
+        assert!(config.validate().is_ok());
+
+        let version_of = |dir: &Path| config.rule_for(dir).and_then(|r| r.version.as_deref());
+        assert_eq!(version_of(&home.join("work/legacy")), Some("14"));
+        assert_eq!(version_of(&home.join("work/legacy/a/b")), Some("14"));
+        assert_eq!(version_of(&home.join("work/other")), None);
+
+        let rule = config.rule_for(Path::new("/srv/site/app")).unwrap();
+        assert_eq!(rule.plugin.as_deref(), Some("fnm"));
+        assert!(config.rule_for(Path::new("/srv/site/app/src")).is_none());
+
+        let invalid: Config = serde_yaml::from_str("rules:\n  - path: /srv\n").unwrap();
+        assert!(Config::default().merge(invalid).validate().is_err());
+    }
+
+    #[test]
+    fn test_merge_version_check() {
+        let parsed: Config = serde_yaml::from_str("version_check: strict\n").unwrap();
+        let merged = Config::default().merge(parsed);
+        assert_eq!(merged.version_check, VersionCheck::Strict);
+    }
+
     #[test]
     fn test_merge_empty_plugins_preserves_base() {
         let base = Config {
@@ -1,4 +1,9 @@
 pub mod loader;
 mod schema;
 
-pub use schema::{AutoInstallMode, Config};
+pub use schema::{
+    AbiCheck, AutoInstallMode, AutoInstallRule, Config, EolSeverity, Hooks, InstallMode,
+    LeaveAction, PackageManagerMode, PathRule, ProjectHooks, RangeStrategy, RemoteVersionPolicy,
+    RulePrecedence, VersionCheck, WorkspaceStrategy, DEFAULT_ACTIVATION_TIMEOUT_MS,
+    DEFAULT_COMMAND_TIMEOUT_MS,
+};
@@ -1,4 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default per-command timeout for version manager commands
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 5_000;
+
+/// Default deadline for a whole activation
+pub const DEFAULT_ACTIVATION_TIMEOUT_MS: u64 = 15_000;
 
 /// Main configuration structure
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -10,6 +18,22 @@ pub struct Config {
     /// Auto-install behavior: "prompt", "always", "never"
     pub auto_install: AutoInstallMode,
 
+    /// Per-version overrides of `auto_install` (first matching rule wins),
+    /// e.g. "always" for patch updates of an installed major, "never" for
+    /// end-of-life versions
+    pub auto_install_rules: Vec<AutoInstallRule>,
+
+    /// How auto-installs run: "shell" (`install && use` in the shell) or
+    /// "process" (anvs runs the install with progress, and only switches
+    /// once it succeeded)
+    /// Default: "shell"
+    pub install_mode: InstallMode,
+
+    /// Plugin to install missing versions with (must be one of `plugins`);
+    /// the others are tried in order if it fails
+    /// Default: none (the first available plugin)
+    pub install_plugin: Option<String>,
+
     /// Version files to search for (in priority order)
     pub version_files: Vec<String>,
 
@@ -19,9 +43,191 @@ pub struct Config {
 
     /// Default Node.js version to use when no version file is present
     pub default_version: Option<String>,
+
+    /// What to do when leaving a project directory: "default" or "restore"
+    /// Default: "default" (switch to the version manager's default version)
+    pub leave_action: LeaveAction,
+
+    /// Time allowed for each version manager command, in milliseconds (0 = no limit)
+    /// Default: 5000
+    pub command_timeout_ms: u64,
+
+    /// Time allowed for a whole activation, in milliseconds (0 = no limit)
+    /// Default: 15000. Time spent waiting for an install prompt doesn't count.
+    pub activation_timeout_ms: u64,
+
+    /// Which version to install when no installed version satisfies a range:
+    /// "newest", "newest-lts" or "lowest"
+    /// Default: "newest-lts"
+    pub remote_version_policy: RemoteVersionPolicy,
+
+    /// How to pick an installed version for package.json engines.node ranges:
+    /// "default-first", "highest", "lowest", "highest-lts" or "current-if-satisfies"
+    /// Default: "default-first"
+    pub range_strategy: RangeStrategy,
+
+    /// What to do when the activated version is end-of-life: "warn", "block"
+    /// or "ignore". Near-EOL and outdated versions only ever warn.
+    /// Default: "warn"
+    pub eol_severity: EolSeverity,
+
+    /// What to do when the activated version doesn't satisfy package.json
+    /// engines.node in the same directory: "warn", "strict" (refuse to
+    /// activate) or "off"
+    /// Default: "warn"
+    pub version_check: VersionCheck,
+
+    /// Which version file wins inside a pnpm/yarn/npm/lerna/nx workspace:
+    /// "nearest", "root" or "root-satisfies-nearest"
+    /// Default: "nearest"
+    pub workspace_strategy: WorkspaceStrategy,
+
+    /// Versions or plugins for directories matching a path glob, for
+    /// checkouts without a version file (first matching rule wins)
+    pub rules: Vec<PathRule>,
+
+    /// The config file `rules` came from (~/.anvsrc or a project's .anvs.yaml)
+    #[serde(skip)]
+    pub rules_source: Option<PathBuf>,
+
+    /// Whether version files or rules win when both apply: "version-file"
+    /// or "rule"
+    /// Default: "version-file" (rules only apply without a version file)
+    pub rule_precedence: RulePrecedence,
+
+    /// What to do about package.json packageManager and engines.npm after
+    /// switching: "off", "check" (warn when the bundled npm doesn't match)
+    /// or "corepack" (also run corepack for pnpm/yarn)
+    /// Default: "off"
+    pub package_manager: PackageManagerMode,
+
+    /// What to do when the project's node_modules was built for a different
+    /// Node.js ABI than the activated version: "off", "warn" or "rebuild"
+    /// Default: "off" (only read from ~/.anvsrc)
+    pub abi_check: AbiCheck,
+
+    /// Shell commands to run around activation, with `{version}`,
+    /// `{previous_version}`, `{project_root}` and `{plugin}` substituted
+    pub hooks: Hooks,
+
+    /// Hooks from the project's .anvs.yaml, which only run once trusted
+    /// with `anvs trust`
+    #[serde(skip)]
+    pub project_hooks: Option<ProjectHooks>,
 }
 
+/// Shell commands run by the parent shell around activation
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run before switching to a project's version
+    pub pre_activate: Vec<String>,
+
+    /// Run after switching to a project's version (e.g., "npm rebuild")
+    pub post_activate: Vec<String>,
+
+    /// Run before switching away when leaving a project
+    pub on_leave: Vec<String>,
+}
+
+impl Hooks {
+    /// Whether no hooks are defined
+    pub fn is_empty(&self) -> bool {
+        self.pre_activate.is_empty() && self.post_activate.is_empty() && self.on_leave.is_empty()
+    }
+}
+
+/// Hooks defined in a project's .anvs.yaml
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectHooks {
+    /// The .anvs.yaml defining the hooks
+    pub path: PathBuf,
+
+    pub hooks: Hooks,
+}
+
+/// Auto-install mode for versions matching every condition given
+///
+/// Conditions left out match any version.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct AutoInstallRule {
+    /// Semver range the version must satisfy, e.g. "20" or ">=22"
+    #[serde(default)]
+    pub range: Option<String>,
+
+    /// Whether another version of the same major must (or must not) be installed
+    #[serde(default)]
+    pub installed_major: Option<bool>,
+
+    /// Whether the version's release line must (or must not) be end-of-life
+    #[serde(default)]
+    pub eol: Option<bool>,
+
+    /// Whether the version must (or must not) be a prerelease
+    #[serde(default)]
+    pub prerelease: Option<bool>,
+
+    /// Minimum days since the release (per the release index)
+    #[serde(default)]
+    pub min_age_days: Option<u32>,
+
+    /// Maximum days since the release (per the release index)
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+
+    /// Auto-install mode for matching versions
+    pub auto_install: AutoInstallMode,
+}
+
+/// Version (and/or plugin) for directories matching a path glob
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct PathRule {
+    /// Directory glob, e.g. "~/work/legacy/**" (`~` is the home directory)
+    pub path: String,
+
+    /// Node.js version, alias or range to activate
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Version manager to prefer for matching directories
+    #[serde(default)]
+    pub plugin: Option<String>,
+}
+
+impl PathRule {
+    /// Whether `dir` (an absolute path) matches this rule
+    ///
+    /// A trailing `/**` also matches the directory itself.
+    pub fn matches(&self, dir: &Path) -> bool {
+        let pattern = match self.path.strip_prefix("~/") {
+            Some(rest) => match dirs::home_dir() {
+                Some(home) => format!("{}/{rest}", home.display()),
+                None => return false,
+            },
+            None => self.path.clone(),
+        };
+        let pattern = pattern.trim_end_matches('/');
+        let dir = dir.to_string_lossy();
+
+        crate::glob::matches(pattern, &dir)
+            || pattern
+                .strip_suffix("/**")
+                .is_some_and(|base| crate::glob::matches(base, &dir))
+    }
+}
+
+/// Whether version files or path rules win when both apply
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RulePrecedence {
+    /// Version files win; rules apply only where none is found
+    #[default]
+    VersionFile,
+    /// Matching rules win over version files
+    Rule,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum AutoInstallMode {
     Prompt,
@@ -29,19 +235,170 @@ pub enum AutoInstallMode {
     Never,
 }
 
+/// Behavior when leaving all project directories
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaveAction {
+    /// Switch to the version manager's default version
+    #[default]
+    Default,
+    /// Restore the environment captured before anvs first activated in this shell
+    Restore,
+}
+
+/// Installed version to pick for a package.json engines.node range
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RangeStrategy {
+    /// Default version if it satisfies the range, otherwise the highest LTS
+    #[default]
+    DefaultFirst,
+    /// Highest satisfying version
+    Highest,
+    /// Lowest satisfying version (useful in CI to catch compatibility regressions)
+    Lowest,
+    /// Highest satisfying LTS version, or the highest version if none is LTS
+    HighestLts,
+    /// Currently active version if it satisfies the range, otherwise the highest LTS
+    CurrentIfSatisfies,
+}
+
+/// Version to pick from the available (remote) versions satisfying a range
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemoteVersionPolicy {
+    /// Newest satisfying version
+    Newest,
+    /// Newest satisfying LTS version, or the newest version if none is LTS
+    #[default]
+    NewestLts,
+    /// Lowest satisfying version
+    Lowest,
+}
+
+/// How to treat end-of-life Node.js versions
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EolSeverity {
+    /// Print a warning and activate anyway
+    #[default]
+    Warn,
+    /// Refuse to activate end-of-life versions
+    Block,
+    /// Skip support checks entirely
+    Ignore,
+}
+
+/// How to treat version files that contradict package.json engines.node
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionCheck {
+    /// Print a diagnostic and activate anyway
+    #[default]
+    Warn,
+    /// Refuse to activate (and make `anvs check` fail)
+    Strict,
+    /// Don't compare version files
+    Off,
+}
+
+/// Version file to use inside a workspace
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorkspaceStrategy {
+    /// Nearest version file wins, even if it's a sub-package's engines.node
+    #[default]
+    Nearest,
+    /// The workspace root's version file wins if it has one
+    Root,
+    /// The workspace root's version file wins, but must satisfy the nearest
+    /// version file's range
+    RootSatisfiesNearest,
+}
+
+/// How to handle a project's package manager after switching Node.js
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManagerMode {
+    /// Ignore packageManager and engines.npm
+    #[default]
+    Off,
+    /// Warn when the bundled npm doesn't satisfy engines.npm or packageManager
+    Check,
+    /// Check npm, and prepare pnpm/yarn from packageManager with corepack
+    Corepack,
+}
+
+/// How auto-installs run
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    /// Write `install && use` to the shell
+    #[default]
+    Shell,
+    /// Install in a child process, then write only the activate command
+    Process,
+}
+
+/// How to handle native modules built for another Node.js ABI
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiCheck {
+    /// Don't compare node_modules with the activated version
+    #[default]
+    Off,
+    /// Warn and print the rebuild command
+    Warn,
+    /// Rebuild native modules after switching
+    Rebuild,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             plugins: vec!["nvm".to_string(), "fnm".to_string()],
             auto_install: AutoInstallMode::Prompt,
+            auto_install_rules: Vec::new(),
+            install_mode: InstallMode::Shell,
+            install_plugin: None,
             version_files: vec![".nvmrc".to_string(), ".node-version".to_string()],
             use_default: true,
             default_version: None,
+            leave_action: LeaveAction::Default,
+            command_timeout_ms: DEFAULT_COMMAND_TIMEOUT_MS,
+            activation_timeout_ms: DEFAULT_ACTIVATION_TIMEOUT_MS,
+            remote_version_policy: RemoteVersionPolicy::NewestLts,
+            range_strategy: RangeStrategy::DefaultFirst,
+            eol_severity: EolSeverity::Warn,
+            version_check: VersionCheck::Warn,
+            workspace_strategy: WorkspaceStrategy::Nearest,
+            rules: Vec::new(),
+            rules_source: None,
+            rule_precedence: RulePrecedence::VersionFile,
+            package_manager: PackageManagerMode::Off,
+            abi_check: AbiCheck::Off,
+            hooks: Hooks::default(),
+            project_hooks: None,
         }
     }
 }
 
 impl Config {
+    /// Per-command timeout for version manager commands (None = no limit)
+    pub fn command_timeout(&self) -> Option<Duration> {
+        (self.command_timeout_ms > 0).then(|| Duration::from_millis(self.command_timeout_ms))
+    }
+
+    /// Deadline for a whole activation (None = no limit)
+    pub fn activation_timeout(&self) -> Option<Duration> {
+        (self.activation_timeout_ms > 0).then(|| Duration::from_millis(self.activation_timeout_ms))
+    }
+
+    /// First path rule matching `dir` (an absolute path)
+    pub fn rule_for(&self, dir: &Path) -> Option<&PathRule> {
+        self.rules.iter().find(|rule| rule.matches(dir))
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> anyhow::Result<()> {
         if self.plugins.is_empty() {
@@ -52,6 +409,20 @@ impl Config {
             anyhow::bail!("at least one version file must be configured");
         }
 
+        if let Some(name) = &self.install_plugin {
+            if !self.plugins.contains(name) {
+                anyhow::bail!("install_plugin '{name}' is not one of the configured plugins");
+            }
+        }
+
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| rule.version.is_none() && rule.plugin.is_none())
+        {
+            anyhow::bail!("rule for '{}' needs a version or a plugin", rule.path);
+        }
+
         Ok(())
     }
 }
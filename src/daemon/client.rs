@@ -0,0 +1,158 @@
+use super::protocol::{self, Request, Response};
+use crate::activation::{ActivationLog, ActivationRecord, ShellEnv};
+use crate::config::{Config, LeaveAction};
+use crate::output;
+use crate::shell::{CommandSink, EnvSnapshot};
+use anyhow::{Context, Result};
+use log::debug;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long `anvs activate` waits for the daemon before resolving in-process
+///
+/// A cold daemon may need to run the version manager; if it's slower than
+/// this, the client falls back and the daemon's answer is memoized for next time.
+const ACTIVATE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Client for the activation daemon
+pub struct DaemonClient {
+    socket_path: PathBuf,
+    timeout: Duration,
+}
+
+impl DaemonClient {
+    /// Creates a client for the daemon at `socket_path`
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Sets the read/write timeout for requests
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends a request and waits for the response
+    pub fn request(&self, request: &Request) -> Result<Response> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .with_context(|| format!("failed to connect to {}", self.socket_path.display()))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        stream.write_all(protocol::encode(request)?.as_bytes())?;
+
+        let mut line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut line)
+            .context("no response from daemon")?;
+        protocol::decode(&line)
+    }
+
+    /// Returns true if a daemon answers on the socket
+    pub fn is_running(&self) -> bool {
+        self.socket_path.exists()
+            && matches!(self.request(&Request::Status), Ok(Response::Status { .. }))
+    }
+
+    /// Waits up to `timeout` for a daemon to start answering
+    pub fn wait_until_running(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if self.is_running() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+}
+
+/// Tries to handle `anvs activate` through the daemon
+///
+/// Writes the activation command to the shell and prints the usual message
+/// when the daemon has an answer. Anything else (no daemon, a missing version,
+/// leaving a project with `use_default`) is left to the in-process flow.
+/// Handled activations are appended to `history`.
+///
+/// # Returns
+/// true if the activation was handled
+pub fn try_activate(
+    socket_path: &Path,
+    config: &Config,
+    path: &Path,
+    use_default: bool,
+    sink: &mut dyn CommandSink,
+    history: Option<&ActivationLog>,
+) -> bool {
+    // No socket means no daemon; don't pay for a connection attempt
+    if !socket_path.exists() {
+        return false;
+    }
+
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    // The daemon resolves for this shell, not its own environment
+    let shell = ShellEnv::from_env();
+    let request = Request::Activate {
+        path: path.clone(),
+        path_env: shell.path,
+        node_path: shell.node_path,
+        current_version: shell.current_version,
+        active_key: shell.active_key,
+    };
+
+    let started = Instant::now();
+    let mut record = ActivationRecord {
+        daemon: true,
+        ..ActivationRecord::start(&path)
+    };
+
+    let client = DaemonClient::new(socket_path).with_timeout(ACTIVATE_TIMEOUT);
+    let response = match client.request(&request) {
+        Ok(response) => response,
+        Err(e) => {
+            debug!("Daemon unavailable, activating in-process: {e:#}");
+            return false;
+        }
+    };
+    debug!("Daemon response: {response:?}");
+
+    match response {
+        Response::Activate {
+            commands: activation,
+            version,
+            plugin,
+        } => {
+            // The daemon can't see this shell's environment, so take the snapshot here
+            let mut commands = Vec::new();
+            if config.leave_action == LeaveAction::Restore {
+                commands.extend(EnvSnapshot::from_env().capture_commands());
+            }
+            commands.extend(activation);
+
+            for cmd in &commands {
+                if let Err(e) = sink.write_command(cmd) {
+                    debug!("Failed to write daemon command: {e}");
+                    return false;
+                }
+            }
+
+            output::switched(&version, &plugin);
+            record.version = Some(version);
+            record.plugin = Some(plugin);
+            record.commands = commands;
+        }
+        Response::NoVersionFile if !use_default => {}
+        _ => return false,
+    }
+
+    if let Some(history) = history {
+        record.finish(started.elapsed());
+        history.record(&record);
+    }
+    true
+}
@@ -0,0 +1,37 @@
+//! Daemon mode - warm activation over a Unix socket
+//!
+//! `anvs daemon start` runs a background process that keeps configuration,
+//! plugin registries and resolved activations in memory. `anvs activate` asks
+//! it first and falls back to in-process resolution when the daemon isn't
+//! running or can't answer (e.g., a version needs installing).
+//!
+//! Messages are single JSON lines (see [`Request`] and [`Response`]).
+
+mod client;
+mod protocol;
+mod server;
+mod watcher;
+
+pub use client::{try_activate, DaemonClient};
+pub use protocol::{Request, Response};
+pub use server::Server;
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Environment variable overriding the socket path
+pub const SOCKET_ENV: &str = "ANVS_DAEMON_SOCKET";
+
+/// Default idle timeout before the daemon exits (30 minutes)
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Socket path: $ANVS_DAEMON_SOCKET, or ~/.anvs/daemon.sock
+pub fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".anvs").join("daemon.sock"))
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Request sent to the daemon (one JSON object per line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Resolve the activation for a directory
+    Activate {
+        /// Absolute directory to activate for
+        path: PathBuf,
+        /// The client's PATH
+        path_env: Option<String>,
+        /// `node` currently on the client's PATH
+        node_path: Option<PathBuf>,
+        /// Version of that `node` (package.json ranges may prefer it)
+        current_version: Option<String>,
+        /// The client's active key (its on_leave hooks run when switching away)
+        active_key: Option<String>,
+    },
+    /// Report daemon statistics
+    Status,
+    /// Stop the daemon
+    Shutdown,
+}
+
+/// Response sent by the daemon (one JSON object per line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    /// The version is installed; the client should run `commands`
+    Activate {
+        commands: Vec<String>,
+        version: String,
+        plugin: String,
+    },
+    /// No version file found for the directory
+    NoVersionFile,
+    /// The daemon can't answer (e.g., the version isn't installed and may
+    /// need a prompt, or there's a warning to show); the client should
+    /// resolve in-process
+    Fallback { reason: String },
+    /// Daemon statistics
+    Status {
+        pid: u32,
+        uptime_secs: u64,
+        requests: u64,
+        cached: usize,
+        watched: usize,
+    },
+    /// The daemon is shutting down
+    Stopping,
+    /// The request could not be handled
+    Error { message: String },
+}
+
+/// Encodes a message as a single JSON line
+pub fn encode<T: Serialize>(message: &T) -> Result<String> {
+    let mut line = serde_json::to_string(message).context("failed to encode daemon message")?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Decodes a message from a JSON line
+pub fn decode<T: for<'de> Deserialize<'de>>(line: &str) -> Result<T> {
+    serde_json::from_str(line.trim()).context("failed to decode daemon message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = Request::Activate {
+            path: PathBuf::from("/work/app"),
+            path_env: Some("/nvm/v18.20.0/bin:/usr/bin".to_string()),
+            node_path: Some(PathBuf::from("/nvm/v18.20.0/bin/node")),
+            current_version: Some("18.20.0".to_string()),
+            active_key: Some("/work/app/.nvmrc:18.20.0".to_string()),
+        };
+        let line = encode(&request).unwrap();
+        assert!(line.ends_with('\n'));
+        assert!(line.contains(r#""type":"activate""#));
+        assert_eq!(decode::<Request>(&line).unwrap(), request);
+    }
+
+    #[test]
+    fn test_response_round_trip() {
+        let response = Response::Fallback {
+            reason: "18.20.0 is not installed".to_string(),
+        };
+        let line = encode(&response).unwrap();
+        assert_eq!(decode::<Response>(&line).unwrap(), response);
+        assert!(decode::<Response>("not json").is_err());
+    }
+}
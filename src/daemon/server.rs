@@ -0,0 +1,728 @@
+use super::protocol::{self, Request, Response};
+use super::watcher::Watcher;
+use crate::activation::{InstallOutcome, Installer, Orchestrator, ShellEnv, UserPrompt};
+use crate::cache::{dir_mtime, ResolutionCache};
+use crate::config::Config;
+use crate::plugins::{PluginRegistry, VersionManagerPlugin};
+use crate::shell::{EnvSnapshot, RecordingSink, SNAPSHOT_MARKER};
+use anyhow::{Context, Result};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest time between checks of the idle timeout and polled watches
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time allowed for a client to send its request
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Identifies the configuration files in effect for a directory
+///
+/// A memoized response is only reused while the stamp is unchanged, so edits
+/// to ~/.anvsrc or a project's .anvs.yaml take effect immediately.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ConfigStamp {
+    user: Option<u128>,
+    project: Option<(PathBuf, Option<u128>)>,
+}
+
+impl ConfigStamp {
+    fn for_dir(dir: &Path) -> Result<Self> {
+        let user = Config::user_config_path()
+            .ok()
+            .and_then(|path| dir_mtime(&path));
+        let project = Config::find_project_config(dir)?.map(|path| {
+            let mtime = dir_mtime(&path);
+            (path, mtime)
+        });
+        Ok(Self { user, project })
+    }
+}
+
+/// Memo key for activation responses: directory and the client's shell
+type ResponseKey = (PathBuf, ShellEnv);
+
+/// Registry key: everything `PluginRegistry::from_config` reads
+type RegistryKey = (Vec<String>, Option<Duration>);
+
+/// Activation daemon listening on a Unix socket
+///
+/// Keeps configuration, plugin registries and resolved activations in memory
+/// and answers `anvs activate` requests without spawning a version manager.
+/// Memoized answers are dropped whenever a watched version file directory or
+/// version manager directory changes.
+pub struct Server {
+    socket_path: PathBuf,
+    idle_timeout: Duration,
+    cache: Option<Arc<ResolutionCache>>,
+    config_override: Option<Config>,
+    registry_override: Option<Arc<PluginRegistry>>,
+    configs: HashMap<ConfigStamp, Config>,
+    registries: HashMap<RegistryKey, Arc<PluginRegistry>>,
+    responses: HashMap<ResponseKey, (ConfigStamp, Response)>,
+    watcher: Watcher,
+    started: Instant,
+    requests: u64,
+}
+
+impl Server {
+    /// Creates a server for `socket_path` that exits after `idle_timeout` without requests
+    pub fn new(socket_path: impl Into<PathBuf>, idle_timeout: Duration) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            idle_timeout,
+            cache: None,
+            config_override: None,
+            registry_override: None,
+            configs: HashMap::new(),
+            registries: HashMap::new(),
+            responses: HashMap::new(),
+            watcher: Watcher::new(),
+            started: Instant::now(),
+            requests: 0,
+        }
+    }
+
+    /// Backs registries and engines resolution with the on-disk cache
+    pub fn with_cache(mut self, cache: Arc<ResolutionCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Uses a fixed configuration instead of loading one per directory (for testing)
+    #[cfg(test)]
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config_override = Some(config);
+        self
+    }
+
+    /// Uses a fixed plugin registry (for testing)
+    #[cfg(test)]
+    pub fn with_registry(mut self, registry: PluginRegistry) -> Self {
+        self.registry_override = Some(Arc::new(registry));
+        self
+    }
+
+    /// Binds the socket and serves requests until shutdown or idle timeout
+    pub fn run(mut self) -> Result<()> {
+        let listener = self.bind()?;
+        listener
+            .set_nonblocking(true)
+            .context("failed to configure daemon socket")?;
+
+        info!(
+            "Daemon listening on {} (idle timeout {}s)",
+            self.socket_path.display(),
+            self.idle_timeout.as_secs()
+        );
+
+        let mut last_request = Instant::now();
+        let result = loop {
+            let idle = last_request.elapsed();
+            if idle >= self.idle_timeout {
+                info!("Daemon idle for {}s, exiting", idle.as_secs());
+                break Ok(());
+            }
+
+            self.wait(&listener, (self.idle_timeout - idle).min(POLL_INTERVAL));
+
+            if self.watcher.take_changed() {
+                debug!("Dropping {} memoized activations", self.responses.len());
+                self.responses.clear();
+            }
+
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    last_request = Instant::now();
+                    match self.handle_connection(stream) {
+                        Ok(true) => break Ok(()),
+                        Ok(false) => {}
+                        Err(e) => debug!("Daemon client error: {e:#}"),
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => break Err(e).context("failed to accept daemon connection"),
+            }
+        };
+
+        let _ = fs::remove_file(&self.socket_path);
+        result
+    }
+
+    /// Creates the socket, replacing a stale one left by a crashed daemon
+    fn bind(&self) -> Result<UnixListener> {
+        if self.socket_path.exists() {
+            if UnixStream::connect(&self.socket_path).is_ok() {
+                anyhow::bail!(
+                    "a daemon is already listening on {}",
+                    self.socket_path.display()
+                );
+            }
+            debug!("Removing stale socket {}", self.socket_path.display());
+            fs::remove_file(&self.socket_path)
+                .with_context(|| format!("failed to remove {}", self.socket_path.display()))?;
+        }
+
+        if let Some(parent) = self.socket_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        let listener = UnixListener::bind(&self.socket_path)
+            .with_context(|| format!("failed to bind {}", self.socket_path.display()))?;
+
+        // Only the owner may ask the daemon for shell commands
+        fs::set_permissions(&self.socket_path, fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("failed to secure {}", self.socket_path.display()))?;
+
+        Ok(listener)
+    }
+
+    /// Blocks until the socket or watcher is readable, or `timeout` passes
+    fn wait(&self, listener: &UnixListener, timeout: Duration) {
+        let mut fds = vec![libc::pollfd {
+            fd: listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+        if let Some(fd) = self.watcher.fd() {
+            fds.push(libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            });
+        }
+
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as libc::c_int;
+        unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+    }
+
+    /// Reads one request and writes its response
+    ///
+    /// # Returns
+    /// true if the client asked the daemon to shut down
+    fn handle_connection(&mut self, stream: UnixStream) -> Result<bool> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let request: Request = protocol::decode(&line)?;
+        debug!("Daemon request: {request:?}");
+
+        let shutdown = request == Request::Shutdown;
+        let response = self.handle(request);
+        (&stream).write_all(protocol::encode(&response)?.as_bytes())?;
+
+        Ok(shutdown)
+    }
+
+    /// Produces the response for a request
+    pub fn handle(&mut self, request: Request) -> Response {
+        self.requests += 1;
+
+        match request {
+            Request::Activate {
+                path,
+                path_env,
+                node_path,
+                current_version,
+                active_key,
+            } => self.activate(
+                path,
+                ShellEnv {
+                    path: path_env,
+                    node_path,
+                    current_version,
+                    active_key,
+                },
+            ),
+            Request::Status => Response::Status {
+                pid: std::process::id(),
+                uptime_secs: self.started.elapsed().as_secs(),
+                requests: self.requests,
+                cached: self.responses.len(),
+                watched: self.watcher.watched_count(),
+            },
+            Request::Shutdown => Response::Stopping,
+        }
+    }
+
+    /// Answers an activation request, from memory when possible
+    fn activate(&mut self, path: PathBuf, shell: ShellEnv) -> Response {
+        let stamp = match ConfigStamp::for_dir(&path) {
+            Ok(stamp) => stamp,
+            Err(e) => {
+                return Response::Error {
+                    message: format!("{e:#}"),
+                }
+            }
+        };
+
+        let key = (path, shell);
+        if let Some((cached_stamp, response)) = self.responses.get(&key) {
+            if *cached_stamp == stamp {
+                debug!("Memoized activation for {}", key.0.display());
+                return response.clone();
+            }
+        }
+
+        let config = match self.config_for(&key.0, &stamp) {
+            Ok(config) => config,
+            Err(e) => {
+                return Response::Error {
+                    message: format!("{e:#}"),
+                }
+            }
+        };
+
+        let response = self.resolve(&config, &key.0, &key.1);
+        if !matches!(response, Response::Error { .. }) {
+            self.responses.insert(key, (stamp, response.clone()));
+        }
+        response
+    }
+
+    /// Loads (or reuses) the configuration in effect for a directory
+    fn config_for(&mut self, dir: &Path, stamp: &ConfigStamp) -> Result<Config> {
+        if let Some(config) = &self.config_override {
+            return Ok(config.clone());
+        }
+        if let Some(config) = self.configs.get(stamp) {
+            return Ok(config.clone());
+        }
+
+        let config = Config::load_for(dir).context("failed to load configuration")?;
+        self.configs.insert(stamp.clone(), config.clone());
+        Ok(config)
+    }
+
+    /// Returns a warm registry for the configured plugins
+    ///
+    /// New registries have their versions directories watched, so installs and
+    /// uninstalls drop memoized activations.
+    fn registry_for(&mut self, config: &Config) -> Arc<PluginRegistry> {
+        if let Some(registry) = &self.registry_override {
+            return Arc::clone(registry);
+        }
+        let key = (config.plugins.clone(), config.command_timeout());
+        if let Some(registry) = self.registries.get(&key) {
+            return Arc::clone(registry);
+        }
+
+        let mut registry = PluginRegistry::from_config(config);
+        if let Some(cache) = &self.cache {
+            registry = registry.with_cache(Arc::clone(cache));
+        }
+        for plugin in registry.plugins() {
+            if let Some(dir) = plugin.versions_dir() {
+                self.watcher.watch(&dir);
+            }
+        }
+
+        let registry = Arc::new(registry);
+        self.registries.insert(key, Arc::clone(&registry));
+        registry
+    }
+
+    /// Resolves the activation for a directory without touching any shell
+    ///
+    /// Runs the activation for the client's shell and records its commands.
+    /// Only plain switches are answered; warnings, install prompts and extra
+    /// commands (corepack, rebuilds) are left to the client.
+    fn resolve(&mut self, config: &Config, path: &Path, shell: &ShellEnv) -> Response {
+        // Hooks are trust-checked against ~/.anvs/trusted.json, which memoized
+        // answers don't track
+        if !config.hooks.is_empty() || config.project_hooks.is_some() {
+            return Response::Fallback {
+                reason: "activation hooks are configured".to_string(),
+            };
+        }
+
+        let registry = self.registry_for(config);
+
+        let mut sink = RecordingSink::new();
+        let mut orchestrator = Orchestrator::new(config, &registry, &mut sink)
+            .with_shell_env(shell.clone())
+            // The client snapshots its own environment; a captured snapshot
+            // keeps the orchestrator from writing the daemon's
+            .with_env_snapshot(EnvSnapshot::from_lookup(|var| {
+                (var == SNAPSHOT_MARKER).then(|| "1".to_string())
+            }))
+            .with_user_prompt(Box::new(ClientOnly))
+            .with_installer(Box::new(ClientOnly));
+        if let Some(cache) = &self.cache {
+            orchestrator = orchestrator.with_cache(Arc::clone(cache));
+        }
+
+        let result = orchestrator.activate(path, false);
+        let record = orchestrator.record().clone();
+        let messages = orchestrator.messages();
+        let ran_hooks = orchestrator.ran_hooks();
+        drop(orchestrator);
+
+        // Watch every directory that was searched, so a version file created
+        // closer to `path` (or edited in place) is noticed
+        let found_in = record
+            .version_file
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+        for dir in path.ancestors() {
+            self.watcher.watch(dir);
+            if Some(dir) == found_in.as_deref() {
+                break;
+            }
+        }
+        if let Some(version_file) = &record.version_file {
+            self.watcher.watch(version_file);
+        }
+
+        if let Err(e) = result {
+            debug!("Activation needs the client: {e}");
+            return Response::Fallback {
+                reason: e.to_string(),
+            };
+        }
+        if let Some(message) = messages.into_iter().next() {
+            return Response::Fallback { reason: message };
+        }
+        // e.g. the on_leave hooks of the project the client is switching from
+        if ran_hooks {
+            return Response::Fallback {
+                reason: "activation hooks are configured".to_string(),
+            };
+        }
+        if record.version_file.is_none() {
+            return Response::NoVersionFile;
+        }
+
+        match (record.version, record.plugin) {
+            (Some(version), Some(plugin)) => Response::Activate {
+                commands: sink.commands().to_vec(),
+                version,
+                plugin,
+            },
+            _ => Response::Fallback {
+                reason: "nothing was activated".to_string(),
+            },
+        }
+    }
+}
+
+/// Prompt and installer for the daemon, which can't ask or install for a shell
+///
+/// Both fail, so activations that need them are left to the client.
+struct ClientOnly;
+
+impl UserPrompt for ClientOnly {
+    fn confirm(&mut self, _message: &str) -> io::Result<bool> {
+        Err(io::Error::other("the daemon can't prompt"))
+    }
+}
+
+impl Installer for ClientOnly {
+    fn install(
+        &mut self,
+        _plugin: &dyn VersionManagerPlugin,
+        version: &str,
+    ) -> Result<InstallOutcome> {
+        anyhow::bail!("the daemon doesn't install {version}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AbiCheck, EolSeverity, PackageManagerMode};
+    use crate::daemon::DaemonClient;
+    use crate::plugins::MockPlugin;
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn test_server(socket: &Path, idle_timeout: Duration) -> Server {
+        let plugin = MockPlugin::new("mock")
+            .with_version("18.20.0")
+            .with_version("20.11.0");
+        let config = Config {
+            plugins: vec!["mock".to_string()],
+            version_files: vec![".nvmrc".to_string()],
+            // The test versions are end-of-life in the bundled release index
+            eol_severity: EolSeverity::Ignore,
+            ..Config::default()
+        };
+
+        Server::new(socket, idle_timeout)
+            .with_config(config)
+            .with_registry(PluginRegistry::with_plugins(vec![Arc::new(plugin)]))
+    }
+
+    fn activate_request(path: &Path) -> Request {
+        Request::Activate {
+            path: path.to_path_buf(),
+            path_env: None,
+            node_path: None,
+            current_version: None,
+            active_key: None,
+        }
+    }
+
+    #[test]
+    fn test_resolves_and_memoizes() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        let expected = Response::Activate {
+            commands: vec!["mock use 18.20.0".to_string()],
+            version: "18.20.0".to_string(),
+            plugin: "mock".to_string(),
+        };
+        assert_eq!(server.handle(activate_request(&project)), expected);
+        assert_eq!(server.responses.len(), 1);
+        assert_eq!(server.handle(activate_request(&project)), expected);
+
+        // Missing versions are left to the in-process flow (it may prompt)
+        fs::write(project.join(".nvmrc"), "16.0.0").unwrap();
+        server.responses.clear();
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Fallback { .. }
+        ));
+
+        let outside = temp.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        assert_eq!(
+            server.handle(activate_request(&outside)),
+            Response::NoVersionFile
+        );
+    }
+
+    #[test]
+    fn test_resolves_for_the_client_shell() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(
+            project.join("package.json"),
+            r#"{"engines": {"node": ">=18"}}"#,
+        )
+        .unwrap();
+
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        server.config_override = Some(Config {
+            version_files: vec!["package.json".to_string()],
+            range_strategy: crate::config::RangeStrategy::CurrentIfSatisfies,
+            ..server.config_override.clone().unwrap()
+        });
+
+        // The range prefers the client's active version, not the daemon's
+        let request = |current: &str| Request::Activate {
+            path: project.clone(),
+            path_env: Some(format!("/nvm/v{current}/bin:/usr/bin")),
+            node_path: Some(PathBuf::from(format!("/nvm/v{current}/bin/node"))),
+            current_version: Some(current.to_string()),
+            active_key: None,
+        };
+        for current in ["18.20.0", "20.11.0"] {
+            assert!(matches!(
+                server.handle(request(current)),
+                Response::Activate { ref version, .. } if version == current
+            ));
+        }
+        assert_eq!(server.responses.len(), 2);
+    }
+
+    #[test]
+    fn test_end_of_life_versions_fall_back() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        server.config_override = Some(Config {
+            eol_severity: EolSeverity::Warn,
+            ..server.config_override.clone().unwrap()
+        });
+
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Fallback { ref reason } if reason.contains("end-of-life")
+        ));
+    }
+
+    #[test]
+    fn test_package_manager_requirements_fall_back() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+        fs::write(
+            project.join("package.json"),
+            r#"{"packageManager": "pnpm@8.15.4"}"#,
+        )
+        .unwrap();
+
+        // Off by default, so the daemon answers directly
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Activate { .. }
+        ));
+
+        server.responses.clear();
+        server.config_override = Some(Config {
+            package_manager: PackageManagerMode::Corepack,
+            ..server.config_override.clone().unwrap()
+        });
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Fallback { ref reason } if reason.contains("corepack")
+        ));
+    }
+
+    #[test]
+    fn test_registry_per_command_timeout() {
+        let temp = TempDir::new().unwrap();
+        let mut server = Server::new(temp.path().join("d.sock"), POLL_INTERVAL);
+        let config = |timeout_ms: u64| Config {
+            plugins: Vec::new(),
+            command_timeout_ms: timeout_ms,
+            ..Config::default()
+        };
+
+        let first = server.registry_for(&config(1000));
+        assert!(Arc::ptr_eq(&first, &server.registry_for(&config(1000))));
+        assert!(!Arc::ptr_eq(&first, &server.registry_for(&config(2000))));
+    }
+
+    #[test]
+    fn test_hooks_fall_back() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        server.config_override = Some(Config {
+            hooks: crate::config::Hooks {
+                post_activate: vec!["npm rebuild".to_string()],
+                ..Default::default()
+            },
+            ..server.config_override.clone().unwrap()
+        });
+
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Fallback { ref reason } if reason.contains("hooks")
+        ));
+    }
+
+    #[test]
+    fn test_abi_mismatch_falls_back() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("node_modules")).unwrap();
+        fs::write(project.join("node_modules/.anvs-abi"), "93").unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+
+        let mut server = test_server(&temp.path().join("d.sock"), POLL_INTERVAL);
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Activate { .. }
+        ));
+
+        server.responses.clear();
+        server.config_override = Some(Config {
+            abi_check: AbiCheck::Warn,
+            ..server.config_override.clone().unwrap()
+        });
+        assert!(matches!(
+            server.handle(activate_request(&project)),
+            Response::Fallback { ref reason } if reason.contains("NODE_MODULE_VERSION")
+        ));
+    }
+
+    #[test]
+    fn test_socket_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let socket = temp.path().join("d.sock");
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+
+        let server = test_server(&socket, Duration::from_secs(30));
+        let handle = thread::spawn(move || server.run());
+
+        let client = DaemonClient::new(&socket);
+        assert!(client.wait_until_running(Duration::from_secs(5)));
+
+        let response = client.request(&activate_request(&project)).unwrap();
+        assert!(matches!(response, Response::Activate { ref version, .. } if version == "18.20.0"));
+
+        // Editing the version file invalidates the memoized answer
+        fs::write(project.join(".nvmrc"), "20.11.0").unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(5);
+        fs::File::open(project.join(".nvmrc"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        thread::sleep(Duration::from_millis(50));
+        let response = client.request(&activate_request(&project)).unwrap();
+        assert!(matches!(response, Response::Activate { ref version, .. } if version == "20.11.0"));
+
+        let status = client.request(&Request::Status).unwrap();
+        assert!(matches!(status, Response::Status { cached: 1, .. }));
+
+        assert_eq!(
+            client.request(&Request::Shutdown).unwrap(),
+            Response::Stopping
+        );
+        handle.join().unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let temp = TempDir::new().unwrap();
+        let socket = temp.path().join("d.sock");
+
+        let server = test_server(&socket, Duration::from_millis(200));
+        let started = Instant::now();
+        server.run().unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_refuses_second_daemon_and_replaces_stale_socket() {
+        let temp = TempDir::new().unwrap();
+        let socket = temp.path().join("d.sock");
+
+        // A socket file nobody listens on is stale
+        drop(UnixListener::bind(&socket).unwrap());
+        let server = test_server(&socket, Duration::from_secs(30));
+        let handle = thread::spawn(move || server.run());
+
+        let client = DaemonClient::new(&socket);
+        assert!(client.wait_until_running(Duration::from_secs(5)));
+
+        let second = test_server(&socket, Duration::from_secs(30));
+        assert!(second.run().is_err());
+
+        client.request(&Request::Shutdown).unwrap();
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::cache::dir_mtime;
+use log::{debug, trace, warn};
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+
+/// Watches version file directories and version manager directories
+///
+/// On Linux this uses inotify, so changes are noticed as soon as the daemon
+/// polls its file descriptor. Elsewhere (or if inotify is unavailable) it
+/// falls back to comparing modification times.
+pub struct Watcher {
+    /// inotify instance (Linux only)
+    inotify: Option<RawFd>,
+    /// Watched paths with their modification time when first watched
+    watched: HashMap<PathBuf, Option<u128>>,
+}
+
+impl Watcher {
+    /// Creates a watcher, using inotify when available
+    pub fn new() -> Self {
+        Self {
+            inotify: Self::init_inotify(),
+            watched: HashMap::new(),
+        }
+    }
+
+    /// Creates a watcher that only compares modification times
+    #[cfg(test)]
+    pub fn polling() -> Self {
+        Self {
+            inotify: None,
+            watched: HashMap::new(),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn init_inotify() -> Option<RawFd> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd == -1 {
+            warn!(
+                "inotify unavailable, falling back to polling: {}",
+                std::io::Error::last_os_error()
+            );
+            None
+        } else {
+            Some(fd)
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn init_inotify() -> Option<RawFd> {
+        None
+    }
+
+    /// Starts watching a file or directory (no-op if already watched)
+    pub fn watch(&mut self, path: &Path) {
+        if self.watched.contains_key(path) {
+            return;
+        }
+
+        trace!("Watching {}", path.display());
+        self.add_inotify_watch(path);
+        self.watched.insert(path.to_path_buf(), dir_mtime(path));
+    }
+
+    #[cfg(target_os = "linux")]
+    fn add_inotify_watch(&self, path: &Path) {
+        use std::os::unix::ffi::OsStrExt;
+
+        let Some(fd) = self.inotify else {
+            return;
+        };
+        let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+            return;
+        };
+
+        let mask = libc::IN_CREATE
+            | libc::IN_DELETE
+            | libc::IN_MODIFY
+            | libc::IN_CLOSE_WRITE
+            | libc::IN_MOVED_FROM
+            | libc::IN_MOVED_TO
+            | libc::IN_ATTRIB
+            | libc::IN_DELETE_SELF
+            | libc::IN_MOVE_SELF;
+
+        if unsafe { libc::inotify_add_watch(fd, c_path.as_ptr(), mask) } == -1 {
+            debug!(
+                "Failed to watch {}: {}",
+                path.display(),
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn add_inotify_watch(&self, _path: &Path) {}
+
+    /// File descriptor to poll for change events, if any
+    pub fn fd(&self) -> Option<RawFd> {
+        self.inotify
+    }
+
+    /// Number of watched paths
+    pub fn watched_count(&self) -> usize {
+        self.watched.len()
+    }
+
+    /// Returns true if any watched path changed since the last call
+    ///
+    /// Change events are drained, so each change is reported once.
+    pub fn take_changed(&mut self) -> bool {
+        let changed = match self.inotify {
+            Some(fd) => Self::drain_events(fd),
+            None => self.poll_mtimes(),
+        };
+
+        if changed {
+            debug!("Watched paths changed");
+        }
+        changed
+    }
+
+    /// Reads all pending inotify events, returning true if there were any
+    fn drain_events(fd: RawFd) -> bool {
+        let mut buf = [0u8; 4096];
+        let mut any = false;
+
+        loop {
+            let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+            if n <= 0 {
+                return any;
+            }
+            any = true;
+        }
+    }
+
+    /// Compares modification times against the recorded ones
+    fn poll_mtimes(&mut self) -> bool {
+        let mut changed = false;
+        for (path, mtime) in self.watched.iter_mut() {
+            let current = dir_mtime(path);
+            if current != *mtime {
+                *mtime = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        if let Some(fd) = self.inotify.take() {
+            unsafe { libc::close(fd) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn touch_later(path: &Path) {
+        let later = SystemTime::now() + Duration::from_secs(5);
+        fs::File::open(path).unwrap().set_modified(later).unwrap();
+    }
+
+    #[test]
+    fn test_detects_changes() {
+        let temp = TempDir::new().unwrap();
+        let mut watcher = Watcher::new();
+        watcher.watch(temp.path());
+        assert_eq!(watcher.watched_count(), 1);
+        assert!(!watcher.take_changed());
+
+        fs::write(temp.path().join(".nvmrc"), "20.11.0").unwrap();
+        touch_later(temp.path());
+        assert!(watcher.take_changed());
+        assert!(!watcher.take_changed());
+    }
+
+    #[test]
+    fn test_polling_fallback() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join(".nvmrc");
+        fs::write(&file, "18.20.0").unwrap();
+
+        let mut watcher = Watcher::polling();
+        assert!(watcher.fd().is_none());
+        watcher.watch(&file);
+        watcher.watch(&file);
+        assert_eq!(watcher.watched_count(), 1);
+        assert!(!watcher.take_changed());
+
+        touch_later(&file);
+        assert!(watcher.take_changed());
+        assert!(!watcher.take_changed());
+    }
+}
@@ -1,25 +1,53 @@
-use crate::config::Config;
+use crate::config::{Config, RangeStrategy};
 use crate::plugins::PluginRegistry;
+use crate::release_index::{select_release, Release, ReleaseIndex};
+use crate::version_file::{parse_version, VersionRange};
 use anyhow::Result;
 use log::{debug, info, warn};
-use semver::{Version, VersionReq};
+use semver::Version;
 
 /// Smart engines resolver for package.json engines.node requirements
 ///
-/// Implements intelligent version selection logic:
-/// 1. Try user's default version first
-/// 2. If default satisfies requirement, use it
-/// 3. If default < requirement, find latest LTS that satisfies
-/// 4. Fallback to highest matching version
+/// Picks an installed version according to `range_strategy`:
+/// - `default-first`: the user's default version if it satisfies the requirement,
+///   otherwise the latest LTS that satisfies, otherwise the highest match
+/// - `current-if-satisfies`: the same, but with the currently active version
+/// - `highest-lts`: the latest LTS that satisfies, otherwise the highest match
+/// - `highest` / `lowest`: the highest / lowest match
+///
+/// If nothing installed matches, picks a version to install according to
+/// `remote_version_policy`.
 pub struct EnginesResolver<'a> {
     registry: &'a PluginRegistry,
     config: &'a Config,
+    index: Option<&'a ReleaseIndex>,
+    current: Option<Option<String>>,
 }
 
 impl<'a> EnginesResolver<'a> {
     /// Create new engines resolver
     pub fn new(registry: &'a PluginRegistry, config: &'a Config) -> Self {
-        Self { registry, config }
+        Self {
+            registry,
+            config,
+            index: None,
+            current: None,
+        }
+    }
+
+    /// Uses a release index to recognize LTS versions and to resolve
+    /// requirements no installed version satisfies
+    pub fn with_index(mut self, index: &'a ReleaseIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Uses `current` as the active version instead of asking the first plugin
+    ///
+    /// For resolving on behalf of another shell (e.g., in the daemon).
+    pub fn with_current_version(mut self, current: Option<String>) -> Self {
+        self.current = Some(current);
+        self
     }
 
     /// Resolve engines requirement using smart logic
@@ -31,24 +59,31 @@ impl<'a> EnginesResolver<'a> {
     /// * `Ok(String)` - Resolved version to use
     /// * `Err(_)` - Failed to resolve
     pub fn resolve_smart(&self, requirement: &str) -> Result<String> {
-        info!("Smart engines resolution for requirement: {requirement}");
+        let strategy = self.config.range_strategy;
+        info!("Smart engines resolution for requirement: {requirement} ({strategy:?})");
+
+        // 1. Get the preferred version (default or current), if the strategy has one
+        let preferred = match strategy {
+            RangeStrategy::DefaultFirst => self.get_default_version()?,
+            RangeStrategy::CurrentIfSatisfies => self.get_current_version()?,
+            RangeStrategy::Highest | RangeStrategy::Lowest | RangeStrategy::HighestLts => None,
+        };
 
-        // 1. Get user's default version
-        if let Some(default_version) = self.get_default_version()? {
-            debug!("User default version: {default_version}");
+        if let Some(preferred) = preferred {
+            debug!("Preferred version: {preferred}");
 
-            // 2. Check if default version satisfies requirement
-            if self.version_satisfies(&default_version, requirement) {
-                info!("Default version {default_version} satisfies {requirement}, using it");
-                return Ok(default_version);
+            // 2. Check if preferred version satisfies requirement
+            if self.version_satisfies(&preferred, requirement) {
+                info!("Preferred version {preferred} satisfies {requirement}, using it");
+                return Ok(preferred);
             } else {
-                debug!("Default version {default_version} does not satisfy {requirement}");
+                debug!("Preferred version {preferred} does not satisfy {requirement}");
             }
         } else {
-            debug!("No default version available");
+            debug!("No preferred version for strategy {strategy:?}");
         }
 
-        // 3. Find best match (prefer LTS)
+        // 3. Find best installed match
         self.find_best_match(requirement)
     }
 
@@ -64,35 +99,43 @@ impl<'a> EnginesResolver<'a> {
             }
         }
 
-        // Try to detect current version from first available plugin
-        if let Some(plugin) = self.registry.plugins().first() {
-            match plugin.current_version() {
-                Ok(Some(current)) => {
-                    debug!("Current version from plugin: {current}");
-                    return Ok(Some(current));
-                }
-                Ok(None) => debug!("No current version from plugin"),
-                Err(e) => warn!("Failed to get current version: {e}"),
-            }
+        // Fall back to the current version
+        let current = self.get_current_version()?;
+        match &current {
+            Some(current) => debug!("Current version: {current}"),
+            None => debug!("No current version"),
         }
-
-        Ok(None)
+        Ok(current)
     }
 
-    /// Check if a version satisfies a semver requirement
-    fn version_satisfies(&self, version: &str, requirement: &str) -> bool {
-        // Parse version (handle v prefix)
-        let version_str = version.trim_start_matches('v');
-        let parsed_version = match Version::parse(version_str) {
-            Ok(v) => v,
+    /// Get the currently active Node.js version (from the first plugin, unless set)
+    fn get_current_version(&self) -> Result<Option<String>> {
+        if let Some(current) = &self.current {
+            return Ok(current.clone());
+        }
+        let Some(plugin) = self.registry.plugins().first() else {
+            return Ok(None);
+        };
+
+        match plugin.current_version() {
+            Ok(current) => Ok(current),
             Err(e) => {
-                debug!("Failed to parse version '{version}': {e}");
-                return false;
+                warn!("Failed to get current version: {e}");
+                Ok(None)
             }
+        }
+    }
+
+    /// Check if a version satisfies an npm-style range
+    fn version_satisfies(&self, version: &str, requirement: &str) -> bool {
+        // Parse version (handles the v prefix)
+        let Some(parsed_version) = parse_version(version) else {
+            debug!("Failed to parse version '{version}'");
+            return false;
         };
 
         // Parse requirement
-        let version_req = match VersionReq::parse(requirement) {
+        let version_req = match VersionRange::parse(requirement) {
             Ok(req) => req,
             Err(e) => {
                 debug!("Failed to parse requirement '{requirement}': {e}");
@@ -106,7 +149,7 @@ impl<'a> EnginesResolver<'a> {
         satisfies
     }
 
-    /// Find best matching version, preferring LTS
+    /// Find the best installed version for the range strategy
     fn find_best_match(&self, requirement: &str) -> Result<String> {
         debug!("Finding best match for requirement: {requirement}");
 
@@ -125,13 +168,8 @@ impl<'a> EnginesResolver<'a> {
             }
         }
 
-        if all_versions.is_empty() {
-            warn!("No installed versions found, returning original requirement");
-            return Ok(requirement.to_string());
-        }
-
         // Parse requirement
-        let version_req = match VersionReq::parse(requirement) {
+        let version_req = match VersionRange::parse(requirement) {
             Ok(req) => req,
             Err(_) => {
                 debug!("Not a valid semver requirement, returning as-is: {requirement}");
@@ -142,8 +180,7 @@ impl<'a> EnginesResolver<'a> {
         // Filter versions that satisfy requirement
         let mut matching_versions = Vec::new();
         for (version, plugin_name) in all_versions {
-            let version_str = version.trim_start_matches('v');
-            if let Ok(parsed_version) = Version::parse(version_str) {
+            if let Some(parsed_version) = parse_version(&version) {
                 if version_req.matches(&parsed_version) {
                     matching_versions.push((parsed_version, version, plugin_name));
                 }
@@ -152,22 +189,20 @@ impl<'a> EnginesResolver<'a> {
 
         if matching_versions.is_empty() {
             warn!("No installed versions satisfy requirement: {requirement}");
-            return Ok(requirement.to_string());
+            return Ok(self.best_release(&version_req, requirement));
         }
 
-        // Sort by preference: LTS (even major) first, then by version (descending)
-        matching_versions.sort_by(|a, b| {
-            let a_is_lts = self.is_lts_version(&a.0);
-            let b_is_lts = self.is_lts_version(&b.0);
-
-            // Prefer LTS versions
-            match (a_is_lts, b_is_lts) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => {
-                    // Both LTS or both not LTS, sort by version (descending)
-                    b.0.cmp(&a.0)
-                }
+        // Sort by preference for the strategy
+        matching_versions.sort_by(|a, b| match self.config.range_strategy {
+            RangeStrategy::Highest => b.0.cmp(&a.0),
+            RangeStrategy::Lowest => a.0.cmp(&b.0),
+            RangeStrategy::DefaultFirst
+            | RangeStrategy::CurrentIfSatisfies
+            | RangeStrategy::HighestLts => {
+                // Prefer LTS versions, then sort by version (descending)
+                let a_is_lts = self.is_lts_version(&a.0);
+                let b_is_lts = self.is_lts_version(&b.0);
+                b_is_lts.cmp(&a_is_lts).then_with(|| b.0.cmp(&a.0))
             }
         });
 
@@ -180,19 +215,63 @@ impl<'a> EnginesResolver<'a> {
         Ok(best_version.1.clone())
     }
 
-    /// Check if a version is likely an LTS version
+    /// Picks a version to install for a requirement nothing installed satisfies
+    ///
+    /// Chooses from the versions the version manager can install (falling back
+    /// to the release index) according to `remote_version_policy`. Returns the
+    /// requirement unchanged if no available version matches.
+    fn best_release(&self, version_req: &VersionRange, requirement: &str) -> String {
+        let releases = self.remote_versions();
+        let policy = self.config.remote_version_policy;
+
+        let Some(release) = select_release(&releases, version_req, policy) else {
+            warn!("No available versions satisfy requirement: {requirement}");
+            return requirement.to_string();
+        };
+
+        info!(
+            "Selected available version {} ({policy:?}) for requirement: {requirement}",
+            release.version
+        );
+        release.version.to_string()
+    }
+
+    /// Versions available to install from the first available plugin
+    ///
+    /// Falls back to the release index if the plugin can't list them (e.g.,
+    /// no network access).
+    fn remote_versions(&self) -> Vec<Release> {
+        match self.registry.find_available_plugin() {
+            Ok(Some(plugin)) => match plugin.list_remote_versions() {
+                Ok(releases) if !releases.is_empty() => return releases,
+                Ok(_) => debug!("{} listed no remote versions", plugin.name()),
+                Err(e) => warn!("Failed to list remote versions from {}: {e}", plugin.name()),
+            },
+            Ok(None) => debug!("No available plugin to list remote versions"),
+            Err(e) => warn!("Failed to find an available plugin: {e}"),
+        }
+
+        self.index
+            .map(|index| index.releases().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Check if a version is an LTS version
     ///
-    /// LTS versions are typically even major versions (16, 18, 20, 22, etc.)
+    /// Uses the release index when available. Otherwise assumes even major
+    /// versions (16, 18, 20, 22, etc.) are LTS.
     fn is_lts_version(&self, version: &Version) -> bool {
-        // Even major versions are typically LTS
-        version.major % 2 == 0
+        match self.index {
+            Some(index) => index.is_lts(version),
+            None => version.major.is_multiple_of(2),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::config::{Config, RangeStrategy};
     use crate::plugins::mock::MockPlugin;
     use crate::plugins::VersionManagerPlugin;
     use std::sync::Arc;
@@ -307,6 +386,199 @@ mod tests {
         assert_eq!(result, ">=20.0.0"); // Should return original requirement
     }
 
+    #[test]
+    fn test_engines_npm_range_syntax() {
+        let mock_plugin = MockPlugin {
+            available_versions: ["16.20.2", "18.20.0", "20.11.0", "22.0.0"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        let registry = create_test_registry(vec![mock_plugin]);
+        let config = create_test_config(Some("16.20.2"));
+        let resolver = EnginesResolver::new(&registry, &config);
+
+        assert_eq!(resolver.resolve_smart("^18 || ^20").unwrap(), "20.11.0");
+        assert_eq!(resolver.resolve_smart(">=18 <21").unwrap(), "20.11.0");
+        assert_eq!(resolver.resolve_smart("16 - 20").unwrap(), "16.20.2");
+        assert_eq!(resolver.resolve_smart("18.x").unwrap(), "18.20.0");
+    }
+
+    const TEST_INDEX: &str = r#"{
+        "releases": [
+            {"version": "23.3.0"},
+            {"version": "22.11.0", "lts": "Jod"},
+            {"version": "21.7.3"},
+            {"version": "20.18.1", "lts": "Iron"},
+            {"version": "20.5.0"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_engines_resolves_uninstalled_from_index() {
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["18.20.0".to_string()],
+            ..Default::default()
+        };
+
+        let registry = create_test_registry(vec![mock_plugin]);
+        let config = create_test_config(Some("18.20.0"));
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+        let resolver = EnginesResolver::new(&registry, &config).with_index(&index);
+
+        // Newest LTS release wins over a newer non-LTS release
+        assert_eq!(resolver.resolve_smart(">=20.0.0").unwrap(), "22.11.0");
+        assert_eq!(resolver.resolve_smart("^21").unwrap(), "21.7.3");
+        // Nothing in the index matches either
+        assert_eq!(resolver.resolve_smart(">=30").unwrap(), ">=30");
+    }
+
+    #[test]
+    fn test_engines_lts_preference_uses_index() {
+        // 20.5.0 predates Iron's LTS start, so the index doesn't count it as LTS
+        let mock_plugin = MockPlugin {
+            available_versions: vec!["20.5.0".to_string(), "21.7.3".to_string()],
+            ..Default::default()
+        };
+
+        let registry = create_test_registry(vec![mock_plugin]);
+        let config = create_test_config(Some("16.0.0"));
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+
+        let resolver = EnginesResolver::new(&registry, &config);
+        assert_eq!(resolver.resolve_smart(">=20").unwrap(), "20.5.0");
+
+        let resolver = resolver.with_index(&index);
+        assert_eq!(resolver.resolve_smart(">=20").unwrap(), "21.7.3");
+        assert!(resolver.is_lts_version(&Version::new(20, 18, 1)));
+        assert!(!resolver.is_lts_version(&Version::new(20, 5, 0)));
+    }
+
+    #[test]
+    fn test_engines_remote_version_policy() {
+        use crate::config::RemoteVersionPolicy;
+
+        let mock_plugin = MockPlugin::new("mock")
+            .with_versions(&["16.20.2"])
+            .with_remote_versions(&[
+                ("18.19.0", Some("Hydrogen")),
+                ("18.20.0", Some("Hydrogen")),
+                ("20.11.0", Some("Iron")),
+                ("21.6.0", None),
+            ]);
+        let registry = create_test_registry(vec![mock_plugin]);
+
+        let resolve = |policy| {
+            let config = Config {
+                remote_version_policy: policy,
+                ..create_test_config(Some("16.20.2"))
+            };
+            EnginesResolver::new(&registry, &config)
+                .resolve_smart(">=18")
+                .unwrap()
+        };
+
+        assert_eq!(resolve(RemoteVersionPolicy::NewestLts), "20.11.0");
+        assert_eq!(resolve(RemoteVersionPolicy::Newest), "21.6.0");
+        assert_eq!(resolve(RemoteVersionPolicy::Lowest), "18.19.0");
+    }
+
+    #[test]
+    fn test_engines_range_strategies() {
+        let mock_plugin = MockPlugin {
+            available_versions: ["16.20.2", "18.19.0", "18.20.0", "20.11.0", "21.6.0"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            // Reported as the current version
+            default_version: Some("18.20.0".to_string()),
+            ..Default::default()
+        };
+        let registry = create_test_registry(vec![mock_plugin]);
+
+        let resolve = |strategy, requirement| {
+            let config = Config {
+                range_strategy: strategy,
+                ..create_test_config(Some("18.19.0"))
+            };
+            EnginesResolver::new(&registry, &config)
+                .resolve_smart(requirement)
+                .unwrap()
+        };
+
+        let cases = [
+            (RangeStrategy::DefaultFirst, ">=18", "18.19.0"),
+            (RangeStrategy::DefaultFirst, ">=19", "20.11.0"),
+            (RangeStrategy::CurrentIfSatisfies, ">=18", "18.20.0"),
+            (RangeStrategy::CurrentIfSatisfies, ">=19", "20.11.0"),
+            (RangeStrategy::Highest, ">=18", "21.6.0"),
+            (RangeStrategy::Lowest, ">=18", "18.19.0"),
+            (RangeStrategy::Lowest, ">=16", "16.20.2"),
+            (RangeStrategy::HighestLts, ">=18", "20.11.0"),
+            (RangeStrategy::HighestLts, "^21", "21.6.0"),
+        ];
+        for (strategy, requirement, expected) in cases {
+            assert_eq!(
+                resolve(strategy, requirement),
+                expected,
+                "{strategy:?} with {requirement}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_engines_current_version_override() {
+        let mock_plugin = MockPlugin {
+            available_versions: ["18.20.0", "20.11.0"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            // Reported as the current version
+            default_version: Some("18.20.0".to_string()),
+            ..Default::default()
+        };
+        let registry = create_test_registry(vec![mock_plugin]);
+        let config = Config {
+            range_strategy: RangeStrategy::CurrentIfSatisfies,
+            ..create_test_config(None)
+        };
+
+        // Another shell's active version wins over the plugin's
+        let resolver = EnginesResolver::new(&registry, &config)
+            .with_current_version(Some("20.11.0".to_string()));
+        assert_eq!(resolver.resolve_smart(">=18").unwrap(), "20.11.0");
+
+        // As does having none active
+        let resolver = EnginesResolver::new(&registry, &config).with_current_version(None);
+        assert_eq!(resolver.resolve_smart(">=18").unwrap(), "20.11.0");
+    }
+
+    #[test]
+    fn test_engines_range_strategy_falls_back_to_remote() {
+        // Every strategy installs an available version when nothing installed matches
+        let mock_plugin = MockPlugin::new("mock")
+            .with_default("16.20.2")
+            .with_remote_versions(&[("18.20.0", Some("Hydrogen")), ("20.11.0", Some("Iron"))]);
+        let registry = create_test_registry(vec![mock_plugin]);
+
+        for strategy in [
+            RangeStrategy::DefaultFirst,
+            RangeStrategy::CurrentIfSatisfies,
+            RangeStrategy::Highest,
+            RangeStrategy::Lowest,
+            RangeStrategy::HighestLts,
+        ] {
+            let config = Config {
+                range_strategy: strategy,
+                ..create_test_config(None)
+            };
+            let resolver = EnginesResolver::new(&registry, &config);
+            assert_eq!(resolver.resolve_smart(">=18").unwrap(), "20.11.0");
+        }
+    }
+
     #[test]
     fn test_is_lts_version() {
         let registry = create_test_registry(vec![]);
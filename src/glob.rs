@@ -0,0 +1,49 @@
+//! Glob patterns for paths
+//!
+//! Used for `.gitignore`-style excludes in `anvs scan` and for path rules in
+//! the user config.
+
+/// Matches `text` against a glob where `*` and `?` stay within a path segment
+/// and `**` spans segments
+pub fn matches(pattern: &str, text: &str) -> bool {
+    glob_match(pattern.as_bytes(), text.as_bytes())
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*'] => true,
+        [b'*', b'*', rest @ ..] => {
+            let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len())
+                .filter(|&i| i == 0 || text[i - 1] == b'/')
+                .any(|i| glob_match(rest, &text[i..]))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            matches!(text.first(), Some(c) if *c != b'/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.log", "debug.log"));
+        assert!(!matches("*.log", "logs/debug.log"));
+        assert!(matches("build", "build"));
+        assert!(matches("packages/*/dist", "packages/web/dist"));
+        assert!(!matches("packages/*/dist", "packages/web/src/dist"));
+        assert!(matches("**/fixtures", "fixtures"));
+        assert!(matches("**/fixtures", "a/b/fixtures"));
+        assert!(matches("vendor/**", "vendor/a/b"));
+        assert!(matches("v?", "v1"));
+        assert!(!matches("v?", "v12"));
+    }
+}
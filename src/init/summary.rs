@@ -165,6 +165,7 @@ mod tests {
             version_files: vec![".nvmrc".to_string()],
             use_default: true,
             default_version: None,
+            ..Config::default()
         };
         let shell = Shell::Zsh;
 
@@ -98,6 +98,7 @@ mod tests {
             version_files: vec![".nvmrc".to_string()],
             use_default: true,
             default_version: None,
+            ..Config::default()
         };
         assert!(validate_config(&config).is_ok());
     }
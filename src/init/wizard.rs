@@ -42,10 +42,11 @@ impl WizardState {
     pub fn to_config(&self) -> Result<Config> {
         Ok(Config {
             plugins: self.plugins.clone(),
-            auto_install: self.auto_install.clone(),
+            auto_install: self.auto_install,
             version_files: self.version_files.clone(),
             use_default: true,     // Default to enabled
             default_version: None, // No default version from wizard
+            ..Config::default()
         })
     }
 
@@ -352,7 +353,7 @@ pub fn run_interactive_wizard(force: bool) -> Result<()> {
         shell: state.get_shell()?,
         profile_path,
         plugins: state.plugins.clone(),
-        auto_install: state.auto_install.clone(),
+        auto_install: state.auto_install,
         version_files: state.version_files.clone(),
         config_path: config_path.clone(),
     };
@@ -442,7 +443,7 @@ pub fn run_quick_setup(force: bool) -> Result<()> {
         shell,
         profile_path,
         plugins: state.plugins.clone(),
-        auto_install: state.auto_install.clone(),
+        auto_install: state.auto_install,
         version_files: state.version_files.clone(),
         config_path,
     };
@@ -594,7 +595,7 @@ fn results_to_config(results: &DetectionResults) -> Result<Config> {
         } else {
             results.version_managers.clone()
         },
-        auto_install: results.auto_install.clone(),
+        auto_install: results.auto_install,
         version_files: vec![
             ".nvmrc".to_string(),
             ".node-version".to_string(),
@@ -602,6 +603,7 @@ fn results_to_config(results: &DetectionResults) -> Result<Config> {
         ],
         use_default: true,
         default_version: None, // No default version from detection
+        ..Config::default()
     })
 }
 
@@ -738,6 +740,7 @@ pub fn run_advanced_wizard() -> Result<(Config, Shell)> {
         ],
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     // Show configuration preview and confirm
@@ -849,6 +852,7 @@ mod tests {
             version_files: vec![".nvmrc".to_string()],
             use_default: true,
             default_version: None,
+            ..Config::default()
         };
         let yaml = generate_config(&config);
 
@@ -5,23 +5,32 @@
 //! ANVS automatically switches Node.js versions when you change directories,
 //! reading from `.nvmrc`, `.node-version`, or `package.json` files.
 
+pub mod abi;
 pub mod activation;
+pub mod cache;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod daemon;
 pub mod engines_resolver;
 pub mod error;
+mod glob;
+pub mod hooks;
 pub mod init;
 pub mod installation_detector;
 pub mod output;
+pub mod package_manager;
 pub mod plugins;
+pub mod release_index;
+pub mod scan;
 pub mod setup;
 pub mod shell;
+pub mod shims;
 pub mod version_file;
 
 // Re-export key types
 pub use config::Config;
 pub use error::AnvsError;
 pub use plugins::{PluginRegistry, VersionManagerPlugin};
-pub use shell::CommandWriter;
+pub use shell::{CommandSink, CommandWriter};
 pub use version_file::VersionFile;
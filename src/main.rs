@@ -5,5 +5,13 @@ fn main() -> Result<()> {
     // In normal operation (release builds), users shouldn't see logs
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
+    // Started through a shim (~/.anvs/shims/node → anvs): run the real binary
+    if let Some(tool) = std::env::args_os()
+        .next()
+        .and_then(|argv0| anvs::shims::shim_name(&argv0))
+    {
+        std::process::exit(anvs::shims::run(tool));
+    }
+
     anvs::cli::run()
 }
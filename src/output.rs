@@ -54,6 +54,32 @@ pub fn error(msg: &str) {
     eprintln!("{}", msg.red().bold());
 }
 
+/// Print rows as left-aligned columns under a header
+pub fn table<S: AsRef<str>>(header: &[&str], rows: &[Vec<S>]) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.as_ref().chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(header.to_vec()).bold());
+    for row in rows {
+        println!("{}", format_row(row.iter().map(AsRef::as_ref).collect()));
+    }
+}
+
 /// Print version switch success
 pub fn switched(version: &str, _plugin: &str) {
     println!("{}", format!("{BRAND}:").truecolor(50, 205, 50));
@@ -64,6 +90,20 @@ pub fn switched(version: &str, _plugin: &str) {
     );
 }
 
+/// Print environment restore message
+pub fn restored() {
+    println!("{}", format!("{BRAND}:").truecolor(50, 205, 50));
+    println!(
+        "{} {}",
+        "✓".green().bold(),
+        "Restoring previous Node.js environment".truecolor(
+            INFO_COLOR.0,
+            INFO_COLOR.1,
+            INFO_COLOR.2
+        )
+    );
+}
+
 /// Print installing message
 pub fn installing(version: &str, plugin: &str) {
     println!("{}", format!("{BRAND}:").truecolor(50, 205, 50));
@@ -0,0 +1,298 @@
+//! Package manager requirements from package.json
+//!
+//! Projects pin their package manager with `packageManager` ("pnpm@8.15.4",
+//! handled by corepack) or constrain npm with `engines.npm`. After switching
+//! Node.js, anvs can prepare the pinned package manager and check the npm
+//! bundled with the new version.
+
+use crate::version_file::{PackageJson, VersionRange};
+use log::{debug, trace};
+use semver::Version;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Package managers corepack can install
+const COREPACK_MANAGERS: &[&str] = &["pnpm", "yarn"];
+
+/// A `packageManager` field, e.g. "pnpm@8.15.4+sha512.abc..."
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageManagerSpec {
+    /// Package manager name ("pnpm", "yarn", "npm")
+    pub name: String,
+    /// Exact version, without the integrity hash
+    pub version: String,
+    /// The field as written, which is what corepack expects
+    pub raw: String,
+}
+
+impl PackageManagerSpec {
+    /// Parses a `packageManager` value
+    ///
+    /// # Returns
+    /// `None` if the value isn't of the form `name@version`
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (name, rest) = value.split_once('@')?;
+        let version = rest.split_once('+').map_or(rest, |(version, _)| version);
+        if name.is_empty() || version.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            raw: value.to_string(),
+        })
+    }
+
+    /// Whether corepack can prepare this package manager
+    pub fn uses_corepack(&self) -> bool {
+        COREPACK_MANAGERS.contains(&self.name.as_str())
+    }
+}
+
+/// Package manager requirements that apply to a directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Requirements {
+    /// Nearest `packageManager` field
+    pub package_manager: Option<PackageManagerSpec>,
+    /// Nearest `engines.npm` range
+    pub npm_range: Option<String>,
+    /// package.json files the requirements came from
+    pub sources: Vec<PathBuf>,
+}
+
+impl Requirements {
+    /// Finds the nearest `packageManager` and `engines.npm` above `start_dir`
+    ///
+    /// Each is taken from the closest package.json that declares it, so a
+    /// workspace root's `packageManager` applies to its packages. The search
+    /// stops at HOME.
+    pub fn find(start_dir: &Path) -> Self {
+        let home = dirs::home_dir().unwrap_or_default();
+        let mut requirements = Self::default();
+
+        for dir in start_dir.ancestors() {
+            let path = dir.join("package.json");
+            trace!("Checking for package manager requirements: {path:?}");
+            if let Ok(pkg) = PackageJson::parse(&path) {
+                let mut used = false;
+                if requirements.package_manager.is_none() {
+                    requirements.package_manager = pkg
+                        .package_manager
+                        .as_deref()
+                        .and_then(PackageManagerSpec::parse);
+                    used |= requirements.package_manager.is_some();
+                }
+                if requirements.npm_range.is_none() {
+                    requirements.npm_range = pkg.npm_version().map(String::from);
+                    used |= requirements.npm_range.is_some();
+                }
+                if used {
+                    debug!("Package manager requirements from {}", path.display());
+                    requirements.sources.push(path);
+                }
+            }
+            if requirements.package_manager.is_some() && requirements.npm_range.is_some() {
+                break;
+            }
+            if dir == home {
+                break;
+            }
+        }
+
+        requirements
+    }
+
+    /// Whether there's nothing to check or prepare
+    pub fn is_empty(&self) -> bool {
+        self.package_manager.is_none() && self.npm_range.is_none()
+    }
+
+    /// Problems with the npm bundled with a Node.js version
+    ///
+    /// Compares `npm_version` against `engines.npm`, and against
+    /// `packageManager` when that pins npm.
+    pub fn npm_problems(&self, npm_version: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(range) = &self.npm_range {
+            match VersionRange::parse(range) {
+                Ok(parsed) if !parsed.matches_str(npm_version) => problems.push(format!(
+                    "npm {npm_version} doesn't satisfy engines.npm {range}"
+                )),
+                Ok(_) => {}
+                Err(e) => debug!("Ignoring invalid engines.npm '{range}': {e}"),
+            }
+        }
+
+        if let Some(spec) = self.package_manager.as_ref().filter(|s| s.name == "npm") {
+            if spec.version != npm_version {
+                problems.push(format!(
+                    "npm {npm_version} doesn't match packageManager {}",
+                    spec.raw
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Reads the version of the npm bundled with a Node.js installation
+///
+/// `node_dir` is the installation prefix (containing `bin/node` on Unix).
+/// Reading npm's package.json avoids spawning npm on every activation.
+pub fn bundled_npm_version(node_dir: &Path) -> Option<String> {
+    #[derive(Deserialize)]
+    struct NpmPackage {
+        version: String,
+    }
+
+    // Unix installs keep global modules under lib/, Windows installs don't
+    let candidates = [
+        node_dir.join("lib/node_modules/npm/package.json"),
+        node_dir.join("node_modules/npm/package.json"),
+    ];
+    candidates.iter().find_map(|path| {
+        let content = fs::read_to_string(path).ok()?;
+        let pkg: NpmPackage = serde_json::from_str(&content).ok()?;
+        Some(pkg.version)
+    })
+}
+
+/// Whether a Node.js version ships with corepack (16.9 through 24)
+pub fn bundles_corepack(version: &str) -> bool {
+    let Ok(version) = Version::parse(version.trim().trim_start_matches('v')) else {
+        // Aliases and partial versions are assumed to be recent
+        return true;
+    };
+    version >= Version::new(16, 9, 0) && version.major < 25
+}
+
+/// Shell command that prepares `spec` with corepack
+///
+/// `corepack enable` is skipped when the package manager's shim already
+/// exists in `node_dir`.
+pub fn corepack_command(spec: &PackageManagerSpec, node_dir: Option<&Path>) -> String {
+    let prepare = format!(
+        "corepack prepare {}",
+        shell_escape::escape(spec.raw.as_str().into())
+    );
+    let enabled = node_dir.is_some_and(|dir| dir.join("bin").join(&spec.name).exists());
+    if enabled {
+        prepare
+    } else {
+        format!("corepack enable && {prepare}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = PackageManagerSpec::parse("pnpm@8.15.4").unwrap();
+        assert_eq!(spec.name, "pnpm");
+        assert_eq!(spec.version, "8.15.4");
+        assert!(spec.uses_corepack());
+
+        let spec = PackageManagerSpec::parse("yarn@4.1.0+sha512.5b7bc055cad63273").unwrap();
+        assert_eq!(spec.version, "4.1.0");
+        assert_eq!(spec.raw, "yarn@4.1.0+sha512.5b7bc055cad63273");
+
+        assert!(!PackageManagerSpec::parse("npm@10.2.4")
+            .unwrap()
+            .uses_corepack());
+        assert_eq!(PackageManagerSpec::parse("pnpm"), None);
+        assert_eq!(PackageManagerSpec::parse("@8.15.4"), None);
+    }
+
+    #[test]
+    fn test_find_requirements() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+        let package = root.join("packages/api");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"packageManager": "pnpm@8.15.4", "engines": {"npm": ">=8"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{"engines": {"node": ">=18", "npm": ">=9"}}"#,
+        )
+        .unwrap();
+
+        let requirements = Requirements::find(&package);
+        assert_eq!(requirements.package_manager.unwrap().raw, "pnpm@8.15.4");
+        assert_eq!(requirements.npm_range.as_deref(), Some(">=9"));
+        assert_eq!(requirements.sources.len(), 2);
+
+        let empty = tempdir().unwrap();
+        assert!(Requirements::find(empty.path()).is_empty());
+    }
+
+    #[test]
+    fn test_npm_problems() {
+        let requirements = Requirements {
+            package_manager: PackageManagerSpec::parse("npm@10.2.4"),
+            npm_range: Some(">=10".to_string()),
+            sources: Vec::new(),
+        };
+        assert!(requirements.npm_problems("10.2.4").is_empty());
+
+        let problems = requirements.npm_problems("9.8.1");
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("engines.npm >=10"));
+        assert!(problems[1].contains("packageManager npm@10.2.4"));
+    }
+
+    #[test]
+    fn test_bundled_npm_version() {
+        let temp = tempdir().unwrap();
+        assert_eq!(bundled_npm_version(temp.path()), None);
+
+        let npm = temp.path().join("lib/node_modules/npm");
+        fs::create_dir_all(&npm).unwrap();
+        fs::write(
+            npm.join("package.json"),
+            r#"{"name": "npm", "version": "10.2.4"}"#,
+        )
+        .unwrap();
+        assert_eq!(bundled_npm_version(temp.path()).as_deref(), Some("10.2.4"));
+    }
+
+    #[test]
+    fn test_bundles_corepack() {
+        assert!(!bundles_corepack("14.21.3"));
+        assert!(!bundles_corepack("16.8.0"));
+        assert!(bundles_corepack("16.9.0"));
+        assert!(bundles_corepack("v20.11.0"));
+        assert!(!bundles_corepack("25.0.0"));
+        assert!(bundles_corepack("lts/iron"));
+    }
+
+    #[test]
+    fn test_corepack_command() {
+        let spec = PackageManagerSpec::parse("pnpm@8.15.4").unwrap();
+        assert_eq!(
+            corepack_command(&spec, None),
+            "corepack enable && corepack prepare 'pnpm@8.15.4'"
+        );
+
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("bin")).unwrap();
+        fs::write(temp.path().join("bin/pnpm"), "").unwrap();
+        assert_eq!(
+            corepack_command(&spec, Some(temp.path())),
+            "corepack prepare 'pnpm@8.15.4'"
+        );
+
+        let spec = PackageManagerSpec::parse("yarn@4.1.0+sha512.a;rm -rf").unwrap();
+        assert!(corepack_command(&spec, None).ends_with("'yarn@4.1.0+sha512.a;rm -rf'"));
+    }
+}
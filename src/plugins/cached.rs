@@ -0,0 +1,309 @@
+use super::VersionManagerPlugin;
+use crate::cache::ResolutionCache;
+use crate::release_index::Release;
+use anyhow::Result;
+use log::{debug, warn};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+
+/// Plugin wrapper that serves installed versions from the on-disk cache
+///
+/// Wraps another plugin and answers `is_available`, `list_versions` and
+/// `has_version` from `ResolutionCache`, so cache hits never spawn the
+/// version manager. Everything else is delegated to the wrapped plugin.
+///
+/// Concrete versions are looked up in the cached version list. Aliases and
+/// partial versions (e.g., `lts/iron`, `20`) need the version manager to
+/// resolve them, so its answer is cached per requirement instead.
+#[derive(Debug)]
+pub struct CachedPlugin {
+    inner: Arc<dyn VersionManagerPlugin>,
+    cache: Arc<ResolutionCache>,
+}
+
+impl CachedPlugin {
+    /// Wrap a plugin with the given cache
+    pub fn new(inner: Arc<dyn VersionManagerPlugin>, cache: Arc<ResolutionCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Installed versions, from the cache when valid
+    ///
+    /// Returns None if the plugin has no versions directory (not cacheable).
+    fn cached_versions(&self) -> Result<Option<Vec<String>>> {
+        let Some(dir) = self.inner.versions_dir() else {
+            return Ok(None);
+        };
+
+        if let Some(versions) = self.cache.get_versions(self.inner.name(), &dir) {
+            return Ok(Some(versions));
+        }
+
+        debug!("Listing versions from {} (cache miss)", self.inner.name());
+        let versions = self.inner.list_versions()?;
+        if let Err(e) = self.cache.put_versions(self.inner.name(), &dir, &versions) {
+            warn!("Failed to cache versions for {}: {e}", self.inner.name());
+        }
+
+        Ok(Some(versions))
+    }
+
+    /// Answer `has_version` for an alias or partial version, from the cache when valid
+    fn cached_has_requirement(&self, requirement: &str) -> Result<bool> {
+        let Some(dir) = self.inner.versions_dir() else {
+            return self.inner.has_version(requirement);
+        };
+
+        if let Some(installed) = self
+            .cache
+            .get_installed(self.inner.name(), &dir, requirement)
+        {
+            return Ok(installed);
+        }
+
+        debug!(
+            "Checking {requirement} with {} (cache miss)",
+            self.inner.name()
+        );
+        let installed = self.inner.has_version(requirement)?;
+        if let Err(e) = self
+            .cache
+            .put_installed(self.inner.name(), &dir, requirement, installed)
+        {
+            warn!(
+                "Failed to cache {requirement} for {}: {e}",
+                self.inner.name()
+            );
+        }
+
+        Ok(installed)
+    }
+
+    /// Whether a version string names a concrete version (e.g., "18.20.0", "v18.20.0")
+    fn is_concrete(version: &str) -> bool {
+        semver::Version::parse(version.trim_start_matches('v')).is_ok()
+    }
+}
+
+impl VersionManagerPlugin for CachedPlugin {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn version_files(&self) -> Vec<&str> {
+        self.inner.version_files()
+    }
+
+    fn is_available(&self) -> Result<bool> {
+        let Some(binary) = self.inner.binary() else {
+            return self.inner.is_available();
+        };
+
+        if let Some(available) = self.cache.get_available(self.inner.name(), &binary) {
+            return Ok(available);
+        }
+
+        let available = self.inner.is_available()?;
+        if let Err(e) = self
+            .cache
+            .put_available(self.inner.name(), &binary, available)
+        {
+            warn!("Failed to cache availability of {}: {e}", self.inner.name());
+        }
+
+        Ok(available)
+    }
+
+    fn has_version(&self, version: &str) -> Result<bool> {
+        if !Self::is_concrete(version) {
+            return self.cached_has_requirement(version);
+        }
+
+        match self.cached_versions()? {
+            Some(versions) => {
+                let wanted = version.trim_start_matches('v');
+                Ok(versions.iter().any(|v| v.trim_start_matches('v') == wanted))
+            }
+            None => self.inner.has_version(version),
+        }
+    }
+
+    fn current_version(&self) -> Result<Option<String>> {
+        self.inner.current_version()
+    }
+
+    fn activate_command(&self, version: &str) -> Result<String> {
+        self.inner.activate_command(version)
+    }
+
+    fn install_command(&self, version: &str) -> Result<String> {
+        self.inner.install_command(version)
+    }
+
+    fn install_process(&self, version: &str) -> Result<Command> {
+        self.inner.install_process(version)
+    }
+
+    fn resolve_version(&self, version: &str) -> Result<String> {
+        self.inner.resolve_version(version)
+    }
+
+    fn list_versions(&self) -> Result<Vec<String>> {
+        match self.cached_versions()? {
+            Some(versions) => Ok(versions),
+            None => self.inner.list_versions(),
+        }
+    }
+
+    fn list_remote_versions(&self) -> Result<Vec<Release>> {
+        self.inner.list_remote_versions()
+    }
+
+    fn default_version(&self) -> Result<Option<String>> {
+        self.inner.default_version()
+    }
+
+    fn versions_dir(&self) -> Option<PathBuf> {
+        self.inner.versions_dir()
+    }
+
+    fn binary(&self) -> Option<PathBuf> {
+        self.inner.binary()
+    }
+
+    fn node_dir(&self, version: &str) -> Option<PathBuf> {
+        self.inner.node_dir(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Plugin that counts how often it is asked to spawn the version manager
+    #[derive(Debug, Default)]
+    struct CountingPlugin {
+        dir: PathBuf,
+        binary: Option<PathBuf>,
+        calls: AtomicUsize,
+        checks: AtomicUsize,
+    }
+
+    impl VersionManagerPlugin for CountingPlugin {
+        fn name(&self) -> &str {
+            "counting"
+        }
+        fn version_files(&self) -> Vec<&str> {
+            vec![".nvmrc"]
+        }
+        fn is_available(&self) -> Result<bool> {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        }
+        fn has_version(&self, version: &str) -> Result<bool> {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            Ok(version == "lts/iron")
+        }
+        fn current_version(&self) -> Result<Option<String>> {
+            Ok(None)
+        }
+        fn activate_command(&self, version: &str) -> Result<String> {
+            Ok(format!("counting use {version}"))
+        }
+        fn install_command(&self, version: &str) -> Result<String> {
+            Ok(format!("counting install {version}"))
+        }
+        fn list_versions(&self) -> Result<Vec<String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec!["18.20.0".to_string(), "20.11.0".to_string()])
+        }
+        fn versions_dir(&self) -> Option<PathBuf> {
+            Some(self.dir.clone())
+        }
+        fn binary(&self) -> Option<PathBuf> {
+            self.binary.clone()
+        }
+    }
+
+    /// Cache in `temp`, created up front so writing it doesn't change the versions dir mtime
+    fn cache_in(temp: &TempDir) -> Arc<ResolutionCache> {
+        std::fs::create_dir(temp.path().join("cache")).unwrap();
+        Arc::new(ResolutionCache::new(temp.path().join("cache")))
+    }
+
+    #[test]
+    fn test_second_lookup_is_served_from_cache() {
+        let temp = TempDir::new().unwrap();
+        let inner = Arc::new(CountingPlugin {
+            dir: temp.path().to_path_buf(),
+            ..Default::default()
+        });
+        let cache = cache_in(&temp);
+
+        let plugin = CachedPlugin::new(inner.clone(), cache.clone());
+        assert!(plugin.has_version("18.20.0").unwrap());
+        assert!(plugin.has_version("v20.11.0").unwrap());
+        assert!(!plugin.has_version("16.0.0").unwrap());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        // A fresh wrapper (new process) still hits the on-disk cache
+        let plugin = CachedPlugin::new(inner.clone(), cache);
+        assert_eq!(plugin.list_versions().unwrap().len(), 2);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_aliases_are_cached_per_requirement() {
+        let temp = TempDir::new().unwrap();
+        let inner = Arc::new(CountingPlugin {
+            dir: temp.path().to_path_buf(),
+            ..Default::default()
+        });
+        let cache = cache_in(&temp);
+
+        let plugin = CachedPlugin::new(inner.clone(), cache.clone());
+        assert!(plugin.has_version("lts/iron").unwrap());
+        assert!(!plugin.has_version("20").unwrap());
+        assert_eq!(inner.checks.load(Ordering::SeqCst), 2);
+
+        let plugin = CachedPlugin::new(inner.clone(), cache);
+        assert!(plugin.has_version("lts/iron").unwrap());
+        assert!(!plugin.has_version("20").unwrap());
+        assert_eq!(inner.checks.load(Ordering::SeqCst), 2);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_availability_is_cached_by_binary() {
+        let temp = TempDir::new().unwrap();
+        let binary = temp.path().join("counting");
+        std::fs::write(&binary, "").unwrap();
+        let cache = cache_in(&temp);
+
+        let inner = Arc::new(CountingPlugin {
+            dir: temp.path().to_path_buf(),
+            binary: Some(binary),
+            ..Default::default()
+        });
+        assert!(CachedPlugin::new(inner.clone(), cache.clone())
+            .is_available()
+            .unwrap());
+        assert!(CachedPlugin::new(inner.clone(), cache.clone())
+            .is_available()
+            .unwrap());
+        assert_eq!(inner.checks.load(Ordering::SeqCst), 1);
+
+        // Without a known executable, every check is delegated
+        let inner = Arc::new(CountingPlugin {
+            dir: temp.path().to_path_buf(),
+            ..Default::default()
+        });
+        let plugin = CachedPlugin::new(inner.clone(), cache);
+        assert!(plugin.is_available().unwrap());
+        assert!(plugin.is_available().unwrap());
+        assert_eq!(inner.checks.load(Ordering::SeqCst), 2);
+    }
+}
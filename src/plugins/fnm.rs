@@ -1,7 +1,12 @@
+use super::process::{self, CommandTimeout, DEFAULT_COMMAND_TIMEOUT};
 use crate::plugins::VersionManagerPlugin;
+use crate::release_index::Release;
+use crate::version_file::parse_version;
 use anyhow::{Context, Result};
+use std::path::PathBuf;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Plugin for Fast Node Manager (fnm)
 ///
@@ -11,6 +16,10 @@ use std::sync::{Arc, Mutex};
 pub struct FnmPlugin {
     /// Cached availability status (None = not yet checked)
     available: Arc<Mutex<Option<bool>>>,
+    /// fnm executable (looked up on PATH by default)
+    binary: PathBuf,
+    /// Time allowed for each fnm command (None = no limit)
+    timeout: Option<Duration>,
 }
 
 impl FnmPlugin {
@@ -18,15 +27,31 @@ impl FnmPlugin {
     pub fn new() -> Self {
         Self {
             available: Arc::new(Mutex::new(None)),
+            binary: PathBuf::from("fnm"),
+            timeout: Some(DEFAULT_COMMAND_TIMEOUT),
         }
     }
 
+    /// Sets the time allowed for each fnm command (None = no limit)
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Uses a specific fnm executable (for testing)
+    #[cfg(test)]
+    fn with_binary(mut self, binary: impl Into<PathBuf>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
     /// Run an fnm command and capture output
+    ///
+    /// The command is killed if it runs longer than the configured timeout.
     fn run_fnm_command(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("fnm")
-            .args(args)
-            .output()
-            .context("Failed to execute fnm command")?;
+        let output =
+            process::output_with_timeout(Command::new(&self.binary).args(args), self.timeout)
+                .context("Failed to execute fnm command")?;
 
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -79,6 +104,70 @@ impl FnmPlugin {
         false
     }
 
+    /// Parse fnm list output into version strings (without 'v' prefix)
+    fn parse_fnm_versions(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let version_part = line
+                    .trim()
+                    .trim_start_matches('*')
+                    .split_whitespace()
+                    .next()?;
+                version_part
+                    .strip_prefix('v')
+                    .map(|version| version.to_string())
+            })
+            .collect()
+    }
+
+    /// Parse `fnm list-remote` output into releases
+    ///
+    /// Example output:
+    /// ```text
+    /// v20.11.0 (Iron)
+    /// v21.0.0
+    /// ```
+    fn parse_fnm_list_remote(output: &str) -> Vec<Release> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let version = parse_version(parts.next()?)?;
+                let lts = parts
+                    .next()
+                    .and_then(|part| part.strip_prefix('('))
+                    .and_then(|part| part.strip_suffix(')'))
+                    .map(|codename| codename.to_string());
+                Some(Release {
+                    version,
+                    date: None,
+                    lts,
+                    security: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns fnm's base directory
+    ///
+    /// Checks in order:
+    /// 1. $FNM_DIR (if set)
+    /// 2. ~/.fnm (legacy location, if it exists)
+    /// 3. Platform data directory (e.g., ~/.local/share/fnm)
+    fn fnm_dir() -> Option<PathBuf> {
+        if let Ok(dir) = std::env::var("FNM_DIR") {
+            return Some(PathBuf::from(dir));
+        }
+
+        let legacy = dirs::home_dir()?.join(".fnm");
+        if legacy.is_dir() {
+            return Some(legacy);
+        }
+
+        dirs::data_dir().map(|dir| dir.join("fnm"))
+    }
+
     /// Escape a version string for safe shell usage
     fn escape_version(version: &str) -> String {
         shell_escape::escape(version.into()).into_owned()
@@ -114,8 +203,9 @@ impl VersionManagerPlugin for FnmPlugin {
         }
 
         // Try to run fnm --version
-        let available = match Command::new("fnm").arg("--version").output() {
-            Ok(output) => output.status.success(),
+        let available = match self.run_fnm_command(&["--version"]) {
+            Ok(_) => true,
+            Err(e) if CommandTimeout::find(&e).is_some() => return Err(e),
             Err(_) => false,
         };
 
@@ -141,6 +231,7 @@ impl VersionManagerPlugin for FnmPlugin {
                 let has_it = self.parse_fnm_list(&output, version);
                 Ok(has_it)
             }
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => Ok(false),
         }
     }
@@ -159,6 +250,7 @@ impl VersionManagerPlugin for FnmPlugin {
                     Ok(Some(trimmed.to_string()))
                 }
             }
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => Ok(None),
         }
     }
@@ -173,10 +265,36 @@ impl VersionManagerPlugin for FnmPlugin {
         Ok(format!("fnm install {escaped}"))
     }
 
-    fn resolve_version(&self, version: &str) -> Result<String> {
-        // fnm doesn't have built-in alias resolution like nvm
-        // Just return the version as-is
-        Ok(version.to_string())
+    fn list_versions(&self) -> Result<Vec<String>> {
+        if !self.is_available()? {
+            return Ok(Vec::new());
+        }
+
+        let output = self.run_fnm_command(&["list"])?;
+        Ok(Self::parse_fnm_versions(&output))
+    }
+
+    fn list_remote_versions(&self) -> Result<Vec<Release>> {
+        let output = self.run_fnm_command(&["list-remote"])?;
+        Ok(Self::parse_fnm_list_remote(&output))
+    }
+
+    fn versions_dir(&self) -> Option<PathBuf> {
+        let dir = Self::fnm_dir()?.join("node-versions");
+        dir.is_dir().then_some(dir)
+    }
+
+    fn binary(&self) -> Option<PathBuf> {
+        which::which(&self.binary).ok()
+    }
+
+    fn node_dir(&self, version: &str) -> Option<PathBuf> {
+        // fnm nests each version's prefix in an `installation` directory
+        let dir = self
+            .versions_dir()?
+            .join(format!("v{}", version.trim().trim_start_matches('v')))
+            .join("installation");
+        dir.is_dir().then_some(dir)
     }
 
     fn default_version(&self) -> Result<Option<String>> {
@@ -208,6 +326,7 @@ impl VersionManagerPlugin for FnmPlugin {
                 // No default found
                 Ok(None)
             }
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => {
                 // If command fails, assume no default is configured
                 Ok(None)
@@ -258,6 +377,52 @@ mod tests {
         assert!(!plugin.parse_fnm_list(output, "16.0.0"));
     }
 
+    #[test]
+    fn test_hung_fnm_times_out() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Fake fnm: answers --version, hangs on everything else
+        let temp = tempfile::TempDir::new().unwrap();
+        let fake = temp.path().join("fnm");
+        std::fs::write(
+            &fake,
+            "#!/bin/sh\n[ \"$1\" = --version ] && echo 1.0.0 && exit 0\nsleep 10\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let plugin = FnmPlugin::new()
+            .with_binary(&fake)
+            .with_timeout(Some(Duration::from_millis(200)));
+        assert!(plugin.is_available().unwrap());
+
+        let started = std::time::Instant::now();
+        let err = plugin.has_version("18.20.0").unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(CommandTimeout::find(&err).is_some());
+    }
+
+    #[test]
+    fn test_parse_fnm_versions() {
+        let output = "* v18.20.0 default\n  v20.0.0\n  system";
+        assert_eq!(
+            FnmPlugin::parse_fnm_versions(output),
+            vec!["18.20.0", "20.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_parse_fnm_list_remote() {
+        let output = "v20.11.0 (Iron)\nv21.0.0\nnot-a-version\n";
+        let releases = FnmPlugin::parse_fnm_list_remote(output);
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version.to_string(), "20.11.0");
+        assert_eq!(releases[0].lts.as_deref(), Some("Iron"));
+        assert_eq!(releases[1].version.to_string(), "21.0.0");
+        assert_eq!(releases[1].lts, None);
+    }
+
     #[test]
     fn test_shell_escaping_activate() {
         let plugin = FnmPlugin::new();
@@ -1,6 +1,9 @@
 use super::VersionManagerPlugin;
+use crate::release_index::Release;
+use crate::version_file::parse_version;
 use anyhow::Result;
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 /// Mock plugin for testing
 ///
@@ -13,6 +16,10 @@ pub struct MockPlugin {
     pub installed_versions: HashSet<String>,
     pub available_versions: Vec<String>,
     pub default_version: Option<String>,
+    pub remote_versions: Vec<Release>,
+    pub node_dir: Option<PathBuf>,
+    /// Whether generating activate and install commands fails
+    pub broken: bool,
 }
 
 impl MockPlugin {
@@ -24,6 +31,9 @@ impl MockPlugin {
             installed_versions: HashSet::new(),
             available_versions: Vec::new(),
             default_version: None,
+            remote_versions: Vec::new(),
+            node_dir: None,
+            broken: false,
         }
     }
 
@@ -47,6 +57,34 @@ impl MockPlugin {
         self
     }
 
+    /// Set the versions available to install, with their LTS codenames
+    pub fn with_remote_versions(mut self, versions: &[(&str, Option<&str>)]) -> Self {
+        self.remote_versions = versions
+            .iter()
+            .filter_map(|(version, lts)| {
+                Some(Release {
+                    version: parse_version(version)?,
+                    date: None,
+                    lts: lts.map(String::from),
+                    security: false,
+                })
+            })
+            .collect();
+        self
+    }
+
+    /// Set the installation prefix returned for every version
+    pub fn with_node_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.node_dir = Some(dir.into());
+        self
+    }
+
+    /// Make generating activate and install commands fail
+    pub fn broken(mut self) -> Self {
+        self.broken = true;
+        self
+    }
+
     /// Set the default version
     pub fn with_default(mut self, version: impl Into<String>) -> Self {
         self.default_version = Some(version.into());
@@ -72,10 +110,16 @@ impl VersionManagerPlugin for MockPlugin {
     }
 
     fn activate_command(&self, version: &str) -> Result<String> {
+        if self.broken {
+            anyhow::bail!("{} is broken", self.name);
+        }
         Ok(format!("{} use {}", self.name, version))
     }
 
     fn install_command(&self, version: &str) -> Result<String> {
+        if self.broken {
+            anyhow::bail!("{} is broken", self.name);
+        }
         Ok(format!("{} install {}", self.name, version))
     }
 
@@ -83,6 +127,10 @@ impl VersionManagerPlugin for MockPlugin {
         Ok(self.available_versions.clone())
     }
 
+    fn list_remote_versions(&self) -> Result<Vec<Release>> {
+        Ok(self.remote_versions.clone())
+    }
+
     fn default_version(&self) -> Result<Option<String>> {
         Ok(self.default_version.clone())
     }
@@ -90,4 +138,8 @@ impl VersionManagerPlugin for MockPlugin {
     fn current_version(&self) -> Result<Option<String>> {
         Ok(self.default_version.clone())
     }
+
+    fn node_dir(&self, _version: &str) -> Option<PathBuf> {
+        self.node_dir.clone()
+    }
 }
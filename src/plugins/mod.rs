@@ -1,11 +1,16 @@
+mod cached;
 mod fnm;
 pub mod mock;
 mod nvm;
+mod process;
 mod registry;
 mod trait_def;
 
+pub use cached::CachedPlugin;
 pub use fnm::FnmPlugin;
 pub use nvm::NvmPlugin;
+pub(crate) use process::output_with_timeout;
+pub use process::{CommandTimeout, DEFAULT_COMMAND_TIMEOUT};
 pub use registry::PluginRegistry;
 pub use trait_def::VersionManagerPlugin;
 
@@ -1,8 +1,12 @@
+use super::process::{self, CommandTimeout, DEFAULT_COMMAND_TIMEOUT};
 use crate::plugins::VersionManagerPlugin;
+use crate::release_index::{Release, ReleaseIndex};
+use crate::version_file::parse_version;
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Plugin for nvm (Node Version Manager)
 ///
@@ -12,6 +16,8 @@ use std::sync::{Arc, Mutex};
 pub struct NvmPlugin {
     /// Cached availability check result
     availability_cache: Arc<Mutex<Option<bool>>>,
+    /// Time allowed for each nvm command (None = no limit)
+    timeout: Option<Duration>,
 }
 
 impl NvmPlugin {
@@ -19,9 +25,16 @@ impl NvmPlugin {
     pub fn new() -> Self {
         Self {
             availability_cache: Arc::new(Mutex::new(None)),
+            timeout: Some(DEFAULT_COMMAND_TIMEOUT),
         }
     }
 
+    /// Sets the time allowed for each nvm command (None = no limit)
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Returns the path to nvm.sh
     ///
     /// Checks in order:
@@ -49,7 +62,8 @@ impl NvmPlugin {
 
     /// Runs an nvm command and returns the output
     ///
-    /// This sources nvm.sh and then executes the given nvm command.
+    /// This sources nvm.sh and then executes the given nvm command. The shell
+    /// is killed if it runs longer than the configured timeout.
     fn run_nvm_command(&self, args: &[&str]) -> Result<String> {
         let nvm_sh = self.nvm_sh_path()?;
 
@@ -62,11 +76,11 @@ impl NvmPlugin {
         ];
         cmd_parts.extend(args);
 
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(cmd_parts.join(" "))
-            .output()
-            .context("Failed to execute nvm command")?;
+        let output = process::output_with_timeout(
+            Command::new("bash").arg("-c").arg(cmd_parts.join(" ")),
+            self.timeout,
+        )
+        .context("Failed to execute nvm command")?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
@@ -76,6 +90,61 @@ impl NvmPlugin {
         }
     }
 
+    /// Parse `nvm ls --no-colors --no-alias` output into version strings
+    ///
+    /// nvm ls output format:
+    /// ```text
+    ///        v18.20.0 *
+    /// ->     v20.11.0 *
+    ///          system
+    /// ```
+    fn parse_nvm_ls(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| {
+                line.split_whitespace()
+                    .find(|part| {
+                        part.starts_with('v') && part[1..].starts_with(|c: char| c.is_ascii_digit())
+                    })
+                    .map(|part| part.trim_start_matches('v').to_string())
+            })
+            .collect()
+    }
+
+    /// Parse `nvm ls-remote --no-colors` output into releases
+    ///
+    /// Example output:
+    /// ```text
+    ///        v20.10.0   (LTS: Iron)
+    /// ->     v20.11.0 * (Latest LTS: Iron)
+    ///        v21.0.0
+    /// ```
+    fn parse_nvm_ls_remote(output: &str) -> Vec<Release> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let version = Self::parse_nvm_ls(line).pop()?;
+                let lts = line
+                    .split_once("LTS: ")
+                    .and_then(|(_, rest)| rest.split(')').next())
+                    .map(|codename| codename.trim().to_string());
+                Some(Release {
+                    version: parse_version(&version)?,
+                    date: None,
+                    lts,
+                    security: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Resolves an alias nvm couldn't resolve (e.g., not installed yet) with the release index
+    fn resolve_from_index(version: &str) -> String {
+        ReleaseIndex::load_default()
+            .resolve_alias(version)
+            .unwrap_or_else(|| version.to_string())
+    }
+
     /// Escapes a version string for safe use in shell commands
     fn escape_version(&self, version: &str) -> String {
         shell_escape::escape(version.into()).into_owned()
@@ -129,6 +198,7 @@ impl VersionManagerPlugin for NvmPlugin {
         // This returns a path if installed, or an error if not
         match self.run_nvm_command(&["which", version]) {
             Ok(output) => Ok(!output.is_empty() && !output.contains("N/A")),
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => Ok(false),
         }
     }
@@ -144,6 +214,7 @@ impl VersionManagerPlugin for NvmPlugin {
                     Ok(Some(trimmed.to_string()))
                 }
             }
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => Ok(None),
         }
     }
@@ -158,6 +229,19 @@ impl VersionManagerPlugin for NvmPlugin {
         Ok(format!("nvm install {escaped}"))
     }
 
+    /// nvm is a shell function, so the install runs in bash after sourcing nvm.sh
+    fn install_process(&self, version: &str) -> Result<Command> {
+        let nvm_sh = self.nvm_sh_path()?;
+        let script = format!(
+            "source {} && {}",
+            shell_escape::escape(nvm_sh.to_string_lossy()),
+            self.install_command(version)?
+        );
+        let mut command = Command::new("bash");
+        command.arg("-c").arg(script);
+        Ok(command)
+    }
+
     fn resolve_version(&self, version: &str) -> Result<String> {
         // Try to resolve aliases like "lts/hydrogen" or "node" using `nvm version`
         match self.run_nvm_command(&["version", version]) {
@@ -166,19 +250,44 @@ impl VersionManagerPlugin for NvmPlugin {
                 if resolved.starts_with('v') {
                     Ok(resolved.trim_start_matches('v').to_string())
                 } else if resolved == "N/A" {
-                    // Return original version if cannot be resolved
-                    Ok(version.to_string())
+                    // Not installed: fall back to the release index
+                    Ok(Self::resolve_from_index(version))
                 } else {
                     Ok(resolved)
                 }
             }
             Err(_) => {
-                // If resolution fails, return version as-is
-                Ok(version.to_string())
+                // If resolution fails, fall back to the release index
+                Ok(Self::resolve_from_index(version))
             }
         }
     }
 
+    fn list_versions(&self) -> Result<Vec<String>> {
+        if !self.is_available()? {
+            return Ok(Vec::new());
+        }
+
+        let output = self.run_nvm_command(&["ls", "--no-colors", "--no-alias"])?;
+        Ok(Self::parse_nvm_ls(&output))
+    }
+
+    fn list_remote_versions(&self) -> Result<Vec<Release>> {
+        let output = self.run_nvm_command(&["ls-remote", "--no-colors"])?;
+        Ok(Self::parse_nvm_ls_remote(&output))
+    }
+
+    fn versions_dir(&self) -> Option<PathBuf> {
+        // nvm.sh lives in $NVM_DIR; installed versions live in $NVM_DIR/versions/node
+        let dir = self
+            .nvm_sh_path()
+            .ok()?
+            .parent()?
+            .join("versions")
+            .join("node");
+        dir.is_dir().then_some(dir)
+    }
+
     fn default_version(&self) -> Result<Option<String>> {
         // Get nvm's default version using `nvm version default`
         match self.run_nvm_command(&["version", "default"]) {
@@ -192,6 +301,7 @@ impl VersionManagerPlugin for NvmPlugin {
                     Ok(Some(version))
                 }
             }
+            Err(e) if CommandTimeout::find(&e).is_some() => Err(e),
             Err(_) => {
                 // If command fails, assume no default is configured
                 Ok(None)
@@ -284,6 +394,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_nvm_ls() {
+        let output = "       v18.20.0 *\n->     v20.11.0 *\n         system";
+        assert_eq!(NvmPlugin::parse_nvm_ls(output), vec!["18.20.0", "20.11.0"]);
+        assert!(NvmPlugin::parse_nvm_ls("N/A").is_empty());
+    }
+
+    #[test]
+    fn test_parse_nvm_ls_remote() {
+        let output = "       v20.10.0   (LTS: Iron)\n\
+                      ->     v20.11.0 * (Latest LTS: Iron)\n\
+                             v21.0.0\n\
+                             v21.1.0-rc.1";
+        let releases = NvmPlugin::parse_nvm_ls_remote(output);
+
+        let summary: Vec<_> = releases
+            .iter()
+            .map(|r| (r.version.to_string(), r.lts.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("20.10.0".to_string(), Some("Iron")),
+                ("20.11.0".to_string(), Some("Iron")),
+                ("21.0.0".to_string(), None),
+                ("21.1.0-rc.1".to_string(), None),
+            ]
+        );
+    }
+
     #[test]
     fn test_availability_caching() {
         let plugin = NvmPlugin::new();
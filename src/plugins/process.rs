@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Default time allowed for a single version manager command
+pub const DEFAULT_COMMAND_TIMEOUT: Duration =
+    Duration::from_millis(crate::config::DEFAULT_COMMAND_TIMEOUT_MS);
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long the pipes may stay open after killing leftover processes
+const KILL_GRACE: Duration = Duration::from_millis(100);
+
+/// A version manager command was killed after exceeding its timeout
+#[derive(Debug, Error)]
+#[error("`{command}` timed out after {}ms", timeout.as_millis())]
+pub struct CommandTimeout {
+    /// Command that timed out (program and arguments)
+    pub command: String,
+    /// Timeout that was exceeded
+    pub timeout: Duration,
+}
+
+impl CommandTimeout {
+    /// Returns the timeout details if `error` was caused by a command timeout
+    pub fn find(error: &anyhow::Error) -> Option<&CommandTimeout> {
+        error.downcast_ref::<CommandTimeout>()
+    }
+}
+
+/// Runs a command and captures its output, killing it after `timeout`
+///
+/// With `timeout` set to `None` this behaves like `Command::output()`.
+///
+/// The command runs in its own process group, so a timeout also kills anything
+/// it started (e.g., the `node` processes behind `nvm ls`). So does a leftover
+/// process still holding the output pipes when the timeout passes after the
+/// command exited.
+pub fn output_with_timeout(command: &mut Command, timeout: Option<Duration>) -> Result<Output> {
+    let Some(timeout) = timeout else {
+        return command.output().context("failed to run command");
+    };
+
+    let description = describe(command);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+        .with_context(|| format!("failed to run `{description}`"))?;
+
+    // Drain the pipes on threads so a chatty child can't block on a full pipe
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if started.elapsed() >= timeout {
+            warn!("Killing `{description}` after {}ms", timeout.as_millis());
+            kill_group(&mut child);
+            return Err(CommandTimeout {
+                command: description,
+                timeout,
+            }
+            .into());
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    // A background process the command started may still hold the pipes open
+    let drained = || stdout.is_finished() && stderr.is_finished();
+    while !drained() && started.elapsed() < timeout {
+        thread::sleep(POLL_INTERVAL);
+    }
+    if !drained() {
+        warn!("Killing processes left holding the output of `{description}`");
+        signal_group(child.id());
+        let killed = Instant::now();
+        while !drained() && killed.elapsed() < KILL_GRACE {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    debug!("`{description}` finished in {:?}", started.elapsed());
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Output read by a `drain` thread, or nothing if the pipe is still open
+fn collect(reader: thread::JoinHandle<Vec<u8>>) -> Vec<u8> {
+    if reader.is_finished() {
+        reader.join().unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Reads a pipe to the end on a background thread
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Kills the process group led by `pid`
+fn signal_group(pid: u32) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        // SAFETY: signals the process group the child leads; no memory is shared
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+}
+
+/// Kills a child's process group and reaps the child
+fn kill_group(child: &mut Child) {
+    signal_group(child.id());
+    if let Err(e) = child.kill() {
+        debug!("Failed to kill child process: {e}");
+    }
+    let _ = child.wait();
+}
+
+/// Human-readable command line for logs and errors
+fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completes_within_timeout() {
+        let output = output_with_timeout(
+            Command::new("echo").arg("hello"),
+            Some(Duration::from_secs(5)),
+        )
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+
+    #[test]
+    fn test_kills_slow_command() {
+        let started = Instant::now();
+        let err = output_with_timeout(
+            Command::new("sleep").arg("10"),
+            Some(Duration::from_millis(100)),
+        )
+        .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let timeout = CommandTimeout::find(&err).expect("expected a timeout error");
+        assert_eq!(timeout.command, "sleep 10");
+        assert!(err.to_string().contains("timed out after 100ms"));
+    }
+
+    #[test]
+    fn test_timeout_kills_grandchildren() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let pid_file = temp.path().join("pid");
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let err = output_with_timeout(
+            Command::new("sh").arg("-c").arg(script),
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap_err();
+        assert!(CommandTimeout::find(&err).is_some());
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = format!("/proc/{}/stat", pid.trim());
+        let started = Instant::now();
+        // Dead once gone or a zombie waiting to be reaped by init
+        while std::fs::read_to_string(&stat).is_ok_and(|stat| !stat.contains(") Z ")) {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "sleep outlived the timeout"
+            );
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    #[test]
+    fn test_leftover_process_holding_pipes() {
+        // The shell exits at once, but `sleep` keeps its stdout open
+        let started = Instant::now();
+        let output = output_with_timeout(
+            Command::new("sh").arg("-c").arg("echo started; sleep 30 &"),
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "started");
+    }
+
+    #[test]
+    fn test_no_timeout() {
+        let output = output_with_timeout(Command::new("true").stdout(Stdio::null()), None).unwrap();
+        assert!(output.status.success());
+        assert!(CommandTimeout::find(&anyhow::anyhow!("other")).is_none());
+    }
+}
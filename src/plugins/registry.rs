@@ -1,7 +1,11 @@
-use super::{FnmPlugin, NvmPlugin, VersionManagerPlugin};
+use super::process::{CommandTimeout, DEFAULT_COMMAND_TIMEOUT};
+use super::{CachedPlugin, FnmPlugin, NvmPlugin, VersionManagerPlugin};
+use crate::cache::ResolutionCache;
+use crate::config::Config;
 use anyhow::Result;
 use log::{debug, info};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Registry for managing version manager plugins
 ///
@@ -22,7 +26,17 @@ impl PluginRegistry {
     /// # Arguments
     /// * `plugin_names` - List of plugin names in priority order (from config)
     pub fn new(plugin_names: &[String]) -> Self {
-        info!("Initializing plugin registry with: {plugin_names:?}");
+        Self::with_timeout(plugin_names, Some(DEFAULT_COMMAND_TIMEOUT))
+    }
+
+    /// Create a registry for the configured plugins and command timeout
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_timeout(&config.plugins, config.command_timeout())
+    }
+
+    /// Create a registry whose plugins kill commands running longer than `timeout`
+    pub fn with_timeout(plugin_names: &[String], timeout: Option<Duration>) -> Self {
+        info!("Initializing plugin registry with: {plugin_names:?} (timeout: {timeout:?})");
 
         let mut plugins: Vec<Arc<dyn VersionManagerPlugin>> = Vec::new();
 
@@ -30,11 +44,11 @@ impl PluginRegistry {
             match name.as_str() {
                 "nvm" => {
                     debug!("Loading nvm plugin");
-                    plugins.push(Arc::new(NvmPlugin::new()));
+                    plugins.push(Arc::new(NvmPlugin::new().with_timeout(timeout)));
                 }
                 "fnm" => {
                     debug!("Loading fnm plugin");
-                    plugins.push(Arc::new(FnmPlugin::new()));
+                    plugins.push(Arc::new(FnmPlugin::new().with_timeout(timeout)));
                 }
                 _ => {
                     log::warn!("Unknown plugin '{name}' in config (ignoring)");
@@ -49,6 +63,41 @@ impl PluginRegistry {
         Self { plugins }
     }
 
+    /// Wrap every plugin with the on-disk resolution cache
+    ///
+    /// Cached plugins answer installed-version lookups from `~/.anvs/cache`
+    /// without spawning the version manager.
+    pub fn with_cache(self, cache: Arc<ResolutionCache>) -> Self {
+        let plugins = self
+            .plugins
+            .into_iter()
+            .map(|plugin| {
+                Arc::new(CachedPlugin::new(plugin, Arc::clone(&cache)))
+                    as Arc<dyn VersionManagerPlugin>
+            })
+            .collect();
+
+        Self { plugins }
+    }
+
+    /// Fingerprint of all plugins' versions directories
+    ///
+    /// Changes whenever a version is installed or removed by any plugin, so it
+    /// can be used to invalidate cached resolutions.
+    pub fn fingerprint(&self) -> String {
+        self.plugins
+            .iter()
+            .map(|plugin| {
+                let mtime = plugin
+                    .versions_dir()
+                    .and_then(|dir| crate::cache::dir_mtime(&dir))
+                    .unwrap_or(0);
+                format!("{}={mtime}", plugin.name())
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
     /// Get all registered plugins
     pub fn plugins(&self) -> &[Arc<dyn VersionManagerPlugin>] {
         &self.plugins
@@ -96,7 +145,7 @@ impl PluginRegistry {
     /// # Returns
     /// - `Ok(Some(plugin))` - First plugin with this version
     /// - `Ok(None)` - No plugin has this version installed
-    /// - `Err(_)` - Error checking plugins
+    /// - `Err(_)` - A plugin command timed out (other plugin errors are skipped)
     pub fn find_plugin_with_version(
         &self,
         version: &str,
@@ -105,8 +154,10 @@ impl PluginRegistry {
 
         for plugin in &self.plugins {
             // Skip if plugin not available
-            if !plugin.is_available().unwrap_or(false) {
-                continue;
+            match plugin.is_available() {
+                Ok(true) => {}
+                Err(e) if CommandTimeout::find(&e).is_some() => return Err(e),
+                _ => continue,
             }
 
             match plugin.has_version(version) {
@@ -117,6 +168,8 @@ impl PluginRegistry {
                 Ok(false) => {
                     debug!("Plugin {} does not have version {}", plugin.name(), version);
                 }
+                // A hung version manager would hang the next plugin too; stop here
+                Err(e) if CommandTimeout::find(&e).is_some() => return Err(e),
                 Err(e) => {
                     log::warn!(
                         "Error checking version {} on {}: {}",
@@ -143,6 +196,29 @@ impl PluginRegistry {
             .collect()
     }
 
+    /// Registry with the named plugin moved to the front
+    ///
+    /// Used for path rules that pick a version manager. Unknown names leave
+    /// the order unchanged.
+    pub fn preferring(&self, name: &str) -> Self {
+        let mut plugins = self.plugins.clone();
+        plugins.sort_by_key(|plugin| plugin.name() != name);
+        Self { plugins }
+    }
+
+    /// Registry without the named plugin
+    ///
+    /// Used to fall back to the next plugin after one fails.
+    pub fn without(&self, name: &str) -> Self {
+        let plugins = self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.name() != name)
+            .map(Arc::clone)
+            .collect();
+        Self { plugins }
+    }
+
     /// Get a plugin by name
     pub fn get_plugin(&self, name: &str) -> Option<Arc<dyn VersionManagerPlugin>> {
         self.plugins
@@ -177,6 +253,22 @@ mod tests {
         assert_eq!(registry.plugins().len(), 2);
     }
 
+    #[test]
+    fn test_registry_preferring() {
+        let registry = PluginRegistry::new(&["nvm".to_string(), "fnm".to_string()]);
+
+        let names = |registry: &PluginRegistry| {
+            registry
+                .plugins()
+                .iter()
+                .map(|p| p.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&registry.preferring("fnm")), ["fnm", "nvm"]);
+        assert_eq!(names(&registry.preferring("volta")), ["nvm", "fnm"]);
+        assert_eq!(names(&registry.without("nvm")), ["fnm"]);
+    }
+
     #[test]
     fn test_registry_ignores_unknown_plugins() {
         let registry =
@@ -1,5 +1,8 @@
+use crate::release_index::{Release, ReleaseIndex};
 use anyhow::Result;
 use std::fmt::Debug;
+use std::path::PathBuf;
+use std::process::Command;
 
 /// Trait that all version manager plugins must implement
 ///
@@ -82,11 +85,28 @@ pub trait VersionManagerPlugin: Debug + Send + Sync {
     /// Must properly escape version strings to prevent command injection
     fn install_command(&self, version: &str) -> Result<String>;
 
+    /// Builds a child process that installs a specific version
+    ///
+    /// Used with `install_mode: process`, where anvs runs the install itself
+    /// and only switches the shell once it succeeded.
+    ///
+    /// Default implementation runs `install_command` with `sh -c`, which
+    /// works for version managers that are executables (not shell functions).
+    ///
+    /// # Arguments
+    /// * `version` - The version to install
+    fn install_process(&self, version: &str) -> Result<Command> {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(self.install_command(version)?);
+        Ok(command)
+    }
+
     /// Resolves a version string to a concrete version
     ///
     /// For example, resolves "lts/hydrogen" to "18.20.0" or "latest" to "21.0.0".
     ///
-    /// Default implementation returns the version unchanged.
+    /// Default implementation resolves aliases with the offline release index
+    /// and returns other versions unchanged.
     ///
     /// # Arguments
     /// * `version` - The version string to resolve
@@ -94,8 +114,9 @@ pub trait VersionManagerPlugin: Debug + Send + Sync {
     /// # Returns
     /// The resolved version string
     fn resolve_version(&self, version: &str) -> Result<String> {
-        // Default implementation: return version as-is
-        Ok(version.to_string())
+        Ok(ReleaseIndex::load_default()
+            .resolve_alias(version)
+            .unwrap_or_else(|| version.to_string()))
     }
 
     /// List all installed versions managed by this version manager
@@ -111,6 +132,19 @@ pub trait VersionManagerPlugin: Debug + Send + Sync {
         Ok(Vec::new())
     }
 
+    /// List versions available to install
+    ///
+    /// Used to pick a concrete version to install when no installed version
+    /// satisfies a package.json range. May need network access.
+    ///
+    /// Default implementation lists the releases in the offline release index.
+    ///
+    /// # Returns
+    /// Available releases with their LTS codenames, in any order
+    fn list_remote_versions(&self) -> Result<Vec<Release>> {
+        Ok(ReleaseIndex::load_default().releases().to_vec())
+    }
+
     /// Gets the default Node.js version configured for this version manager
     ///
     /// This is used when anvs needs to switch back to a "default" version,
@@ -130,4 +164,51 @@ pub trait VersionManagerPlugin: Debug + Send + Sync {
         // Default implementation: no default configured
         Ok(None)
     }
+
+    /// Returns the directory where this version manager installs Node.js versions
+    ///
+    /// Used by the resolution cache: the directory's modification time changes
+    /// whenever a version is installed or removed, which invalidates cached
+    /// version lists.
+    ///
+    /// Default implementation returns None (version lists are never cached).
+    ///
+    /// # Returns
+    /// - `Some(path)` if the versions directory exists
+    /// - `None` if unknown or not present
+    fn versions_dir(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the executable this version manager runs, if it is one
+    ///
+    /// Used by the resolution cache to remember `is_available` until the
+    /// executable is upgraded or removed.
+    ///
+    /// Default implementation returns None (availability is never cached).
+    ///
+    /// # Returns
+    /// - `Some(path)` if the executable was found
+    /// - `None` if not found or not applicable (e.g., nvm is a shell function)
+    fn binary(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the installation prefix of an installed Node.js version
+    ///
+    /// The prefix contains `bin/node` and the bundled npm, which package
+    /// manager checks read without spawning npm.
+    ///
+    /// Default implementation assumes nvm's layout, `v{version}` inside
+    /// `versions_dir()`.
+    ///
+    /// # Returns
+    /// - `Some(path)` if the version's directory exists
+    /// - `None` if unknown or not installed
+    fn node_dir(&self, version: &str) -> Option<PathBuf> {
+        let dir = self
+            .versions_dir()?
+            .join(format!("v{}", version.trim().trim_start_matches('v')));
+        dir.is_dir().then_some(dir)
+    }
 }
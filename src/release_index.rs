@@ -0,0 +1,792 @@
+//! Offline Node.js release index
+//!
+//! A list of Node.js releases (version, release date, LTS codename) and release
+//! lines (LTS codename, end-of-life date) bundled into the binary, so aliases
+//! like `lts/hydrogen` and `node` can be resolved, and LTS versions recognized,
+//! without a network call.
+//!
+//! The bundled copy is generated by `scripts/update-node-index.sh` and has
+//! every release of each line, so ranges like `~16.14` resolve offline.
+//!
+//! `anvs index update` refreshes the index from nodejs.org, a mirror, or a
+//! local file and stores it in `~/.anvs/node-index.json`, which takes
+//! precedence over the bundled copy.
+
+use crate::config::RemoteVersionPolicy;
+use crate::plugins::output_with_timeout;
+use crate::version_file::{parse_version, VersionRange};
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use log::{debug, warn};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Index bundled at build time
+const BUNDLED_INDEX: &str = include_str!("../data/node-index.json");
+
+/// Default source for `anvs index update`
+pub const DEFAULT_INDEX_URL: &str = "https://nodejs.org/dist/index.json";
+
+/// Time allowed for downloading the index
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Date format used in index files
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Days before end-of-life at which a release line counts as near EOL
+pub const NEAR_EOL_DAYS: i64 = 90;
+
+/// A single Node.js release
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Release version
+    pub version: Version,
+    /// Release date
+    pub date: Option<NaiveDate>,
+    /// LTS codename, if this release is part of an LTS line
+    pub lts: Option<String>,
+    /// Whether this release fixes security vulnerabilities
+    pub security: bool,
+}
+
+/// A Node.js release line (all releases sharing a major version)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseLine {
+    /// Major version
+    pub major: u64,
+    /// LTS codename, if the line is (or will become) LTS
+    pub codename: Option<String>,
+    /// End-of-life date
+    pub eol: Option<NaiveDate>,
+}
+
+/// Support problem with a Node.js version, most severe first
+#[derive(Debug, Clone, PartialEq)]
+pub enum Advisory {
+    /// The release line is past its end-of-life date
+    EndOfLife { major: u64, eol: NaiveDate },
+    /// The release line reaches end-of-life within `NEAR_EOL_DAYS`
+    NearEndOfLife {
+        major: u64,
+        eol: NaiveDate,
+        days: i64,
+    },
+    /// A newer release of the same major line is available
+    Outdated { latest: Version, security: bool },
+}
+
+impl Advisory {
+    /// Whether the version no longer receives any updates
+    pub fn is_end_of_life(&self) -> bool {
+        matches!(self, Self::EndOfLife { .. })
+    }
+
+    /// Short status label ("eol", "near-eol", "outdated")
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::EndOfLife { .. } => "eol",
+            Self::NearEndOfLife { .. } => "near-eol",
+            Self::Outdated { .. } => "outdated",
+        }
+    }
+
+    /// Human-readable warning for `version`
+    pub fn message(&self, version: &str) -> String {
+        match self {
+            Self::EndOfLife { major, eol } => format!(
+                "Node.js {version} is end-of-life: the {major}.x line stopped receiving \
+                 security updates on {}",
+                format_date(*eol)
+            ),
+            Self::NearEndOfLife { major, eol, days } => format!(
+                "Node.js {major}.x reaches end-of-life on {} (in {days} days)",
+                format_date(*eol)
+            ),
+            Self::Outdated { latest, security } => format!(
+                "Node.js {version} is outdated: {latest} is available{}",
+                if *security {
+                    " and includes security fixes"
+                } else {
+                    ""
+                }
+            ),
+        }
+    }
+}
+
+/// Index file format written by `anvs index update`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    updated: Option<String>,
+    #[serde(default)]
+    releases: Vec<ReleaseEntry>,
+    #[serde(default)]
+    lines: Vec<LineEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReleaseEntry {
+    version: String,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    lts: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    security: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LineEntry {
+    major: u64,
+    #[serde(default)]
+    codename: Option<String>,
+    #[serde(default)]
+    eol: Option<String>,
+}
+
+/// Entry in nodejs.org's `dist/index.json`
+#[derive(Debug, Deserialize)]
+struct DistEntry {
+    version: String,
+    #[serde(default)]
+    date: Option<String>,
+    /// `false` for non-LTS releases, the codename otherwise
+    #[serde(default)]
+    lts: serde_json::Value,
+    #[serde(default)]
+    security: bool,
+}
+
+/// Node.js release index
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseIndex {
+    /// Releases, newest first
+    releases: Vec<Release>,
+    /// Release lines by major version
+    lines: BTreeMap<u64, ReleaseLine>,
+    /// Date the index was generated
+    updated: Option<String>,
+}
+
+impl ReleaseIndex {
+    /// Index bundled into the binary
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_INDEX).expect("bundled release index is valid")
+    }
+
+    /// Parse an index in anvs' format or nodejs.org's `dist/index.json` format
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).context("release index is not valid JSON")?;
+
+        let file = if value.is_array() {
+            let entries: Vec<DistEntry> =
+                serde_json::from_value(value).context("unrecognized release index format")?;
+            IndexFile {
+                updated: entries.iter().filter_map(|e| e.date.clone()).max(),
+                releases: entries
+                    .into_iter()
+                    .map(|entry| ReleaseEntry {
+                        version: entry.version,
+                        date: entry.date,
+                        lts: entry.lts.as_str().map(String::from),
+                        security: entry.security,
+                    })
+                    .collect(),
+                lines: Vec::new(),
+            }
+        } else {
+            serde_json::from_value(value).context("unrecognized release index format")?
+        };
+
+        Ok(Self::from_file(file))
+    }
+
+    fn from_file(file: IndexFile) -> Self {
+        let mut releases: Vec<Release> = file
+            .releases
+            .into_iter()
+            .filter_map(|entry| {
+                let Some(version) = parse_version(&entry.version) else {
+                    debug!("Skipping unparseable release '{}'", entry.version);
+                    return None;
+                };
+                Some(Release {
+                    version,
+                    date: entry.date.as_deref().and_then(parse_date),
+                    lts: entry.lts.map(|codename| capitalize(&codename)),
+                    security: entry.security,
+                })
+            })
+            .collect();
+        releases.sort_by(|a, b| b.version.cmp(&a.version));
+        releases.dedup_by(|a, b| a.version == b.version);
+
+        let mut lines: BTreeMap<u64, ReleaseLine> = file
+            .lines
+            .into_iter()
+            .map(|entry| {
+                let line = ReleaseLine {
+                    major: entry.major,
+                    codename: entry.codename.map(|codename| capitalize(&codename)),
+                    eol: entry.eol.as_deref().and_then(parse_date),
+                };
+                (entry.major, line)
+            })
+            .collect();
+
+        // Every release belongs to a line, even if the file didn't list it
+        for release in &releases {
+            let line = lines
+                .entry(release.version.major)
+                .or_insert_with(|| ReleaseLine {
+                    major: release.version.major,
+                    codename: None,
+                    eol: None,
+                });
+            if line.codename.is_none() {
+                line.codename = release.lts.clone();
+            }
+        }
+
+        Self {
+            releases,
+            lines,
+            updated: file.updated,
+        }
+    }
+
+    /// Default location of the updated index: ~/.anvs/node-index.json
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".anvs").join("node-index.json"))
+    }
+
+    /// Load an index file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Load the updated index if present, otherwise the bundled one
+    pub fn load_default() -> Self {
+        let Ok(path) = Self::default_path() else {
+            return Self::bundled();
+        };
+        if !path.exists() {
+            return Self::bundled();
+        }
+
+        match Self::load(&path) {
+            Ok(index) => index,
+            Err(e) => {
+                warn!("Ignoring release index {}: {e:#}", path.display());
+                Self::bundled()
+            }
+        }
+    }
+
+    /// Download or read an index from a URL, mirror base URL, or local file
+    ///
+    /// Mirror base URLs (e.g., `https://npmmirror.com/mirrors/node`) get
+    /// `/index.json` appended. Downloads use `curl`.
+    pub fn fetch(source: &str) -> Result<Self> {
+        let json = if source.starts_with("http://") || source.starts_with("https://") {
+            let url = if source.ends_with(".json") {
+                source.to_string()
+            } else {
+                format!("{}/index.json", source.trim_end_matches('/'))
+            };
+            debug!("Downloading release index from {url}");
+
+            let output = output_with_timeout(
+                Command::new("curl").args(["-fsSL", &url]),
+                Some(FETCH_TIMEOUT),
+            )
+            .context("failed to run curl (is it installed?)")?;
+            if !output.status.success() {
+                bail!(
+                    "failed to download {url}: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            String::from_utf8(output.stdout).context("release index is not valid UTF-8")?
+        } else {
+            fs::read_to_string(source).with_context(|| format!("failed to read {source}"))?
+        };
+
+        Self::from_json(&json)
+    }
+
+    /// Fill in release line details (codenames, EOL dates) missing from this index
+    ///
+    /// nodejs.org's `dist/index.json` has no EOL dates, so they are carried
+    /// over from the previous index.
+    pub fn merge_lines(&mut self, other: &ReleaseIndex) {
+        for (major, theirs) in &other.lines {
+            let ours = self.lines.entry(*major).or_insert_with(|| theirs.clone());
+            if ours.codename.is_none() {
+                ours.codename = theirs.codename.clone();
+            }
+            if ours.eol.is_none() {
+                ours.eol = theirs.eol;
+            }
+        }
+    }
+
+    /// Save the index in anvs' format
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        let file = IndexFile {
+            updated: self.updated.clone(),
+            releases: self
+                .releases
+                .iter()
+                .map(|release| ReleaseEntry {
+                    version: release.version.to_string(),
+                    date: release.date.map(format_date),
+                    lts: release.lts.clone(),
+                    security: release.security,
+                })
+                .collect(),
+            lines: self
+                .lines
+                .values()
+                .rev()
+                .map(|line| LineEntry {
+                    major: line.major,
+                    codename: line.codename.clone(),
+                    eol: line.eol.map(format_date),
+                })
+                .collect(),
+        };
+
+        let json = serde_json::to_string_pretty(&file)?;
+        fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// All releases, newest first
+    pub fn releases(&self) -> &[Release] {
+        &self.releases
+    }
+
+    /// Date the index was generated (YYYY-MM-DD)
+    pub fn updated(&self) -> Option<&str> {
+        self.updated.as_deref()
+    }
+
+    /// Release line for a major version
+    pub fn line(&self, major: u64) -> Option<&ReleaseLine> {
+        self.lines.get(&major)
+    }
+
+    /// Release entry for an exact version
+    pub fn release(&self, version: &Version) -> Option<&Release> {
+        self.releases.iter().find(|r| &r.version == version)
+    }
+
+    /// Newest release
+    pub fn latest(&self) -> Option<&Release> {
+        self.releases.first()
+    }
+
+    /// Newest LTS release
+    pub fn latest_lts(&self) -> Option<&Release> {
+        self.releases.iter().find(|r| r.lts.is_some())
+    }
+
+    /// Whether a version belongs to an LTS release
+    ///
+    /// Versions newer than the index count as LTS if their line is LTS.
+    pub fn is_lts(&self, version: &Version) -> bool {
+        match self.release(version) {
+            Some(release) => release.lts.is_some(),
+            None => {
+                let newer_than_index = self
+                    .releases
+                    .iter()
+                    .filter(|r| r.version.major == version.major)
+                    .all(|r| &r.version < version);
+                newer_than_index
+                    && self
+                        .line(version.major)
+                        .is_some_and(|line| line.codename.is_some())
+            }
+        }
+    }
+
+    /// Most severe support problem with a version, as of `today`
+    ///
+    /// Returns `None` if the version is supported and up to date, or its
+    /// release line is unknown.
+    pub fn advisory(&self, version: &Version, today: NaiveDate) -> Option<Advisory> {
+        let major = version.major;
+
+        if let Some(eol) = self.line(major).and_then(|line| line.eol) {
+            let days = (eol - today).num_days();
+            if days < 0 {
+                return Some(Advisory::EndOfLife { major, eol });
+            }
+            if days <= NEAR_EOL_DAYS {
+                return Some(Advisory::NearEndOfLife { major, eol, days });
+            }
+        }
+
+        let newer: Vec<&Release> = self
+            .releases
+            .iter()
+            .filter(|r| r.version.major == major && r.version > *version)
+            .collect();
+        let latest = newer.first()?;
+        Some(Advisory::Outdated {
+            latest: latest.version.clone(),
+            security: newer.iter().any(|r| r.security),
+        })
+    }
+
+    /// Newest release satisfying a range, preferring LTS releases
+    pub fn best_match(&self, range: &VersionRange) -> Option<&Release> {
+        select_release(&self.releases, range, RemoteVersionPolicy::NewestLts)
+    }
+
+    /// Versions an alias (`node`, `lts/*`, `lts/-1`, `lts/hydrogen`) refers to
+    ///
+    /// LTS aliases cover the LTS releases of their line, including patch
+    /// releases newer than the index.
+    ///
+    /// # Returns
+    /// `None` if `alias` is not an alias or names an unknown LTS line
+    pub fn alias_range(&self, alias: &str) -> Option<VersionRange> {
+        let alias = alias.trim().to_ascii_lowercase();
+
+        let line = match alias.as_str() {
+            "node" | "latest" | "current" | "stable" => {
+                return VersionRange::parse("*").ok();
+            }
+            "lts" | "lts/*" => self.lts_lines().next()?,
+            _ => {
+                let name = alias.strip_prefix("lts/")?;
+                match name.strip_prefix('-') {
+                    Some(offset) => self.lts_lines().nth(offset.parse().ok()?)?,
+                    None => self.lts_lines().find(|line| {
+                        line.codename
+                            .as_deref()
+                            .is_some_and(|codename| codename.eq_ignore_ascii_case(name))
+                    })?,
+                }
+            }
+        };
+
+        // LTS starts partway through the line (e.g., 18.12.0 for Hydrogen)
+        let start = self
+            .releases
+            .iter()
+            .filter(|r| r.version.major == line.major && r.lts.is_some())
+            .map(|r| &r.version)
+            .min()
+            .map_or_else(|| format!("{}.0.0", line.major), ToString::to_string);
+
+        VersionRange::parse(&format!(">={start} <{}", line.major + 1)).ok()
+    }
+
+    /// Resolve an alias to the newest release it refers to
+    pub fn resolve_alias(&self, alias: &str) -> Option<String> {
+        let range = self.alias_range(alias)?;
+        self.releases
+            .iter()
+            .find(|r| range.matches(&r.version))
+            .map(|r| r.version.to_string())
+    }
+
+    /// LTS release lines, newest first
+    fn lts_lines(&self) -> impl Iterator<Item = &ReleaseLine> {
+        self.lines
+            .values()
+            .rev()
+            .filter(|line| line.codename.is_some())
+    }
+}
+
+/// Picks the release satisfying a range according to `policy`
+///
+/// `releases` may be in any order.
+pub fn select_release<'r>(
+    releases: &'r [Release],
+    range: &VersionRange,
+    policy: RemoteVersionPolicy,
+) -> Option<&'r Release> {
+    let matching = releases.iter().filter(|r| range.matches(&r.version));
+
+    match policy {
+        RemoteVersionPolicy::Newest => matching.max_by(|a, b| a.version.cmp(&b.version)),
+        RemoteVersionPolicy::Lowest => matching.min_by(|a, b| a.version.cmp(&b.version)),
+        RemoteVersionPolicy::NewestLts => {
+            let newest_lts = matching
+                .clone()
+                .filter(|r| r.lts.is_some())
+                .max_by(|a, b| a.version.cmp(&b.version));
+            newest_lts.or_else(|| matching.max_by(|a, b| a.version.cmp(&b.version)))
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT).ok()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// Normalize codenames to the capitalized form nodejs.org uses ("Hydrogen")
+fn capitalize(codename: &str) -> String {
+    let mut chars = codename.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TEST_INDEX: &str = r#"{
+        "updated": "2024-11-20",
+        "releases": [
+            {"version": "23.3.0", "date": "2024-11-20"},
+            {"version": "22.11.0", "date": "2024-10-29", "lts": "Jod"},
+            {"version": "22.10.0", "date": "2024-10-16"},
+            {"version": "20.18.1", "date": "2024-11-20", "lts": "Iron"},
+            {"version": "20.9.0", "date": "2023-10-24", "lts": "Iron"},
+            {"version": "18.20.5", "date": "2024-11-12", "lts": "Hydrogen"},
+            {"version": "18.12.0", "date": "2022-10-25", "lts": "Hydrogen"},
+            {"version": "18.11.0", "date": "2022-10-13"}
+        ],
+        "lines": [
+            {"major": 22, "codename": "Jod", "eol": "2027-04-30"},
+            {"major": 18, "codename": "Hydrogen", "eol": "2025-04-30"}
+        ]
+    }"#;
+
+    fn v(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_index() {
+        let index = ReleaseIndex::bundled();
+        assert!(index.releases().len() > 10);
+        assert!(index.latest_lts().unwrap().lts.is_some());
+        assert_eq!(
+            index.line(18).unwrap().codename.as_deref(),
+            Some("Hydrogen")
+        );
+        assert!(index.line(18).unwrap().eol.is_some());
+    }
+
+    #[test]
+    fn test_bundled_index_lines_are_complete() {
+        let index = ReleaseIndex::bundled();
+
+        // Every minor from .0 to the newest is present in each line
+        let mut minors: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for release in index.releases() {
+            minors
+                .entry(release.version.major)
+                .or_default()
+                .push(release.version.minor);
+        }
+        for (major, mut line) in minors {
+            line.sort_unstable();
+            line.dedup();
+            let expected: Vec<u64> = (0..=*line.last().unwrap()).collect();
+            assert_eq!(line, expected, "missing minors in {major}.x");
+            assert!(index.line(major).is_some(), "no line entry for {major}.x");
+        }
+
+        // So ranges within a line resolve offline
+        let best = |range: &str| {
+            index
+                .best_match(&VersionRange::parse(range).unwrap())
+                .map(|r| r.version.to_string())
+        };
+        assert_eq!(best("~16.14").as_deref(), Some("16.14.2"));
+        assert_eq!(best("~22.5").as_deref(), Some("22.5.1"));
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+
+        assert_eq!(index.resolve_alias("node").as_deref(), Some("23.3.0"));
+        assert_eq!(index.resolve_alias("lts/*").as_deref(), Some("22.11.0"));
+        assert_eq!(index.resolve_alias("lts/-1").as_deref(), Some("20.18.1"));
+        assert_eq!(index.resolve_alias("lts/-2").as_deref(), Some("18.20.5"));
+        assert_eq!(
+            index.resolve_alias("lts/hydrogen").as_deref(),
+            Some("18.20.5")
+        );
+        assert_eq!(index.resolve_alias("lts/Iron").as_deref(), Some("20.18.1"));
+        assert_eq!(index.resolve_alias("lts/-3"), None);
+        assert_eq!(index.resolve_alias("lts/unknown"), None);
+        assert_eq!(index.resolve_alias("18.20.0"), None);
+
+        // LTS aliases exclude pre-LTS releases of the line
+        let range = index.alias_range("lts/hydrogen").unwrap();
+        assert!(range.matches(&v("18.20.9")));
+        assert!(!range.matches(&v("18.11.0")));
+        assert!(!range.matches(&v("20.0.0")));
+    }
+
+    #[test]
+    fn test_is_lts() {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+
+        assert!(index.is_lts(&v("22.11.0")));
+        assert!(!index.is_lts(&v("22.10.0")));
+        assert!(!index.is_lts(&v("23.3.0")));
+        assert!(index.is_lts(&v("20.9.0")));
+        // Newer than the index, on an LTS line
+        assert!(index.is_lts(&v("18.20.9")));
+        assert!(!index.is_lts(&v("23.4.0")));
+        assert!(!index.is_lts(&v("19.0.0")));
+    }
+
+    #[test]
+    fn test_best_match_prefers_lts() {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+
+        let best = |range: &str| {
+            index
+                .best_match(&VersionRange::parse(range).unwrap())
+                .map(|r| r.version.to_string())
+        };
+        assert_eq!(best(">=20").as_deref(), Some("22.11.0"));
+        assert_eq!(best(">=23").as_deref(), Some("23.3.0"));
+        assert_eq!(best("^18.0.0").as_deref(), Some("18.20.5"));
+        assert_eq!(best(">=30"), None);
+    }
+
+    #[test]
+    fn test_select_release_policies() {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+        let mut releases = index.releases().to_vec();
+        releases.reverse();
+
+        let select = |range: &str, policy| {
+            select_release(&releases, &VersionRange::parse(range).unwrap(), policy)
+                .map(|r| r.version.to_string())
+        };
+        assert_eq!(
+            select(">=18", RemoteVersionPolicy::Newest).as_deref(),
+            Some("23.3.0")
+        );
+        assert_eq!(
+            select(">=18", RemoteVersionPolicy::NewestLts).as_deref(),
+            Some("22.11.0")
+        );
+        assert_eq!(
+            select(">=18", RemoteVersionPolicy::Lowest).as_deref(),
+            Some("18.11.0")
+        );
+        assert_eq!(
+            select("^23", RemoteVersionPolicy::NewestLts).as_deref(),
+            Some("23.3.0")
+        );
+        assert_eq!(select(">=30", RemoteVersionPolicy::Newest), None);
+    }
+
+    #[test]
+    fn test_advisories() {
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+        let day = |date: &str| parse_date(date).unwrap();
+
+        // Past EOL wins over being outdated
+        let advisory = index.advisory(&v("18.12.0"), day("2025-06-01")).unwrap();
+        assert!(advisory.is_end_of_life());
+        assert_eq!(advisory.label(), "eol");
+        assert!(advisory.message("18.12.0").contains("2025-04-30"));
+
+        let advisory = index.advisory(&v("18.20.5"), day("2025-03-01")).unwrap();
+        assert_eq!(
+            advisory,
+            Advisory::NearEndOfLife {
+                major: 18,
+                eol: day("2025-04-30"),
+                days: 60
+            }
+        );
+
+        let advisory = index.advisory(&v("18.12.0"), day("2024-12-01")).unwrap();
+        assert_eq!(advisory.label(), "outdated");
+        assert!(advisory.message("18.12.0").contains("18.20.5 is available"));
+
+        // Up to date, or unknown to the index
+        assert_eq!(index.advisory(&v("22.11.0"), day("2024-12-01")), None);
+        assert_eq!(index.advisory(&v("30.0.0"), day("2024-12-01")), None);
+    }
+
+    #[test]
+    fn test_outdated_security_releases() {
+        let json = r#"[
+            {"version": "v20.11.1", "date": "2024-02-14", "lts": "Iron", "security": true},
+            {"version": "v20.11.0", "date": "2024-01-09", "lts": "Iron", "security": false}
+        ]"#;
+        let index = ReleaseIndex::from_json(json).unwrap();
+
+        let advisory = index
+            .advisory(&v("20.11.0"), parse_date("2024-03-01").unwrap())
+            .unwrap();
+        assert!(advisory.message("20.11.0").contains("security fixes"));
+    }
+
+    #[test]
+    fn test_dist_index_format() {
+        let json = r#"[
+            {"version": "v22.11.0", "date": "2024-10-29", "files": [], "lts": "Jod"},
+            {"version": "v23.1.0", "date": "2024-10-24", "files": [], "lts": false}
+        ]"#;
+        let mut index = ReleaseIndex::from_json(json).unwrap();
+
+        assert_eq!(index.latest().unwrap().version, v("23.1.0"));
+        assert_eq!(index.updated(), Some("2024-10-29"));
+        assert_eq!(index.line(22).unwrap().codename.as_deref(), Some("Jod"));
+        assert_eq!(index.line(22).unwrap().eol, None);
+
+        // EOL dates are carried over from another index
+        index.merge_lines(&ReleaseIndex::from_json(TEST_INDEX).unwrap());
+        assert_eq!(index.line(22).unwrap().eol, parse_date("2027-04-30"));
+        assert!(index.line(18).is_some());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("node-index.json");
+
+        let index = ReleaseIndex::from_json(TEST_INDEX).unwrap();
+        index.save(&path).unwrap();
+
+        assert_eq!(ReleaseIndex::load(&path).unwrap(), index);
+        assert_eq!(ReleaseIndex::fetch(path.to_str().unwrap()).unwrap(), index);
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        assert!(ReleaseIndex::from_json("not json").is_err());
+        assert!(ReleaseIndex::from_json(r#"{"releases": 5}"#).is_err());
+    }
+}
@@ -0,0 +1,483 @@
+//! Repository-wide map of Node.js version pins
+//!
+//! Walks a directory tree (honouring `.gitignore` files and skipping
+//! `node_modules`), collects every Node.js pin it finds and flags pins that
+//! contradict each other within a directory.
+
+use crate::glob;
+use crate::release_index::ReleaseIndex;
+use crate::version_file::{parse_version, PackageJson, VersionFile, VersionRange};
+use anyhow::{Context, Result};
+use log::{debug, trace};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a pin was declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PinSource {
+    /// .nvmrc file
+    Nvmrc,
+    /// .node-version file
+    NodeVersion,
+    /// nodejs entry in .tool-versions (asdf)
+    ToolVersions,
+    /// package.json engines.node
+    Engines,
+    /// package.json volta.node
+    Volta,
+}
+
+impl PinSource {
+    /// Name shown in reports (e.g. ".nvmrc", "package.json engines.node")
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nvmrc => ".nvmrc",
+            Self::NodeVersion => ".node-version",
+            Self::ToolVersions => ".tool-versions",
+            Self::Engines => "package.json engines.node",
+            Self::Volta => "package.json volta.node",
+        }
+    }
+}
+
+/// A Node.js version requirement found in a file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pin {
+    /// File declaring the pin
+    pub path: PathBuf,
+    /// Kind of declaration
+    pub source: PinSource,
+    /// Version, alias or range as written
+    pub version: String,
+}
+
+/// Two pins in the same directory that can't both hold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    /// Directory containing both pins
+    pub dir: PathBuf,
+    /// The pin that should satisfy `other`
+    pub pin: Pin,
+    /// The pin it contradicts (usually engines.node)
+    pub other: Pin,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+/// Result of scanning a tree
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanReport {
+    /// Every pin found, sorted by path
+    pub pins: Vec<Pin>,
+    /// Contradictory pins
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ScanReport {
+    /// Number of pins per distinct version string
+    pub fn versions(&self) -> BTreeMap<&str, usize> {
+        let mut versions = BTreeMap::new();
+        for pin in &self.pins {
+            *versions.entry(pin.version.as_str()).or_insert(0) += 1;
+        }
+        versions
+    }
+}
+
+/// `.gitignore`-style exclude patterns
+#[derive(Debug, Clone, Default)]
+pub struct Excludes {
+    rules: Vec<ExcludeRule>,
+}
+
+#[derive(Debug, Clone)]
+struct ExcludeRule {
+    /// Directory the pattern is relative to
+    base: PathBuf,
+    pattern: String,
+    negate: bool,
+    dir_only: bool,
+    /// Match the whole relative path instead of just the file name
+    anchored: bool,
+}
+
+impl Excludes {
+    /// Adds one pattern, relative to `base`
+    ///
+    /// Supports `*`, `?`, `**`, leading `/` anchors, trailing `/` for
+    /// directories and `!` negation. Blank lines and `#` comments are ignored.
+    pub fn add(&mut self, base: &Path, pattern: &str) {
+        let pattern = pattern.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return;
+        }
+
+        let (negate, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+
+        self.rules.push(ExcludeRule {
+            base: base.to_path_buf(),
+            pattern: pattern.trim_start_matches('/').to_string(),
+            negate,
+            dir_only,
+            anchored,
+        });
+    }
+
+    /// Adds the patterns of a `.gitignore` file in `dir`, if it has one
+    pub fn add_gitignore(&mut self, dir: &Path) {
+        let Ok(content) = fs::read_to_string(dir.join(".gitignore")) else {
+            return;
+        };
+        trace!("Loading {}", dir.join(".gitignore").display());
+        for line in content.lines() {
+            self.add(dir, line);
+        }
+    }
+
+    /// Whether `path` is excluded (the last matching pattern wins)
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let mut excluded = false;
+        for rule in &self.rules {
+            let Ok(relative) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let candidate = if rule.anchored {
+                relative.as_str()
+            } else {
+                relative.rsplit('/').next().unwrap_or_default()
+            };
+            if glob::matches(&rule.pattern, candidate) {
+                excluded = !rule.negate;
+            }
+        }
+        excluded
+    }
+}
+
+/// Scans `root` for Node.js pins
+///
+/// `node_modules`, `.git` and anything matched by `.gitignore` files or
+/// `excludes` is skipped. The release index resolves aliases when checking
+/// pins against engines.node.
+pub fn scan(root: &Path, mut excludes: Excludes, index: &ReleaseIndex) -> Result<ScanReport> {
+    let mut report = ScanReport::default();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        trace!("Scanning directory: {dir:?}");
+        excludes.add_gitignore(&dir);
+
+        let pins = dir_pins(&dir);
+        report.conflicts.extend(find_conflicts(&dir, &pins, index));
+        report.pins.extend(pins);
+
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("failed to read directory: {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if !is_dir || name == "node_modules" || name == ".git" {
+                continue;
+            }
+            let path = entry.path();
+            if excludes.is_excluded(&path, true) {
+                debug!("Excluded: {}", path.display());
+                continue;
+            }
+            pending.push(path);
+        }
+    }
+
+    report.pins.sort_by(|a, b| a.path.cmp(&b.path));
+    report.conflicts.sort_by(|a, b| a.dir.cmp(&b.dir));
+    Ok(report)
+}
+
+/// Every pin declared directly in `dir`; unreadable files are skipped
+pub fn dir_pins(dir: &Path) -> Vec<Pin> {
+    let mut pins = Vec::new();
+    let pin = |path: PathBuf, source, version: &str| Pin {
+        path,
+        source,
+        version: version.to_string(),
+    };
+
+    for (filename, source) in [
+        (".nvmrc", PinSource::Nvmrc),
+        (".node-version", PinSource::NodeVersion),
+        (".tool-versions", PinSource::ToolVersions),
+    ] {
+        match VersionFile::find_in(dir, filename) {
+            Ok(Some(file)) => pins.push(pin(file.path, source, &file.version)),
+            Ok(None) => {}
+            Err(e) => debug!("Skipping unreadable version file: {e:#}"),
+        }
+    }
+
+    let package_json = dir.join("package.json");
+    if package_json.is_file() {
+        match PackageJson::parse(&package_json) {
+            Ok(pkg) => {
+                if let Some(version) = pkg.node_version() {
+                    pins.push(pin(package_json.clone(), PinSource::Engines, version));
+                }
+                if let Some(version) = pkg.volta_node_version() {
+                    pins.push(pin(package_json.clone(), PinSource::Volta, version));
+                }
+            }
+            Err(e) => debug!("Skipping invalid package.json: {e:#}"),
+        }
+    }
+
+    pins
+}
+
+/// Pins in one directory that contradict its engines.node range, or each other
+pub fn find_conflicts(dir: &Path, pins: &[Pin], index: &ReleaseIndex) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let (engines, exact): (Vec<&Pin>, Vec<&Pin>) =
+        pins.iter().partition(|p| p.source == PinSource::Engines);
+
+    if let Some(engines) = engines.first() {
+        if let Ok(range) = VersionRange::parse(&engines.version) {
+            for pin in &exact {
+                if !version_satisfies(&pin.version, &range, index) {
+                    conflicts.push(Conflict {
+                        dir: dir.to_path_buf(),
+                        pin: (*pin).clone(),
+                        other: (*engines).clone(),
+                        message: format!(
+                            "{} pins {} but engines.node requires {}",
+                            pin.source.label(),
+                            pin.version,
+                            engines.version
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    // Version files that name different versions outright
+    if let Some((first, rest)) = exact.split_first() {
+        for pin in rest {
+            if normalize(&pin.version) != normalize(&first.version) {
+                conflicts.push(Conflict {
+                    dir: dir.to_path_buf(),
+                    pin: (*pin).clone(),
+                    other: (*first).clone(),
+                    message: format!(
+                        "{} pins {} but {} pins {}",
+                        pin.source.label(),
+                        pin.version,
+                        first.source.label(),
+                        first.version
+                    ),
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// The engines.node pin in `dir` that `version` doesn't satisfy, if any
+///
+/// Used during activation to catch a version file that contradicts the
+/// sibling package.json.
+pub fn unsatisfied_engines(dir: &Path, version: &str, index: &ReleaseIndex) -> Option<Pin> {
+    let path = dir.join("package.json");
+    if !path.is_file() {
+        return None;
+    }
+    let pkg = PackageJson::parse(&path).ok()?;
+    let engines = pkg.node_version()?;
+    let range = VersionRange::parse(engines).ok()?;
+
+    (!version_satisfies(version, &range, index)).then(|| Pin {
+        path,
+        source: PinSource::Engines,
+        version: engines.to_string(),
+    })
+}
+
+/// Whether a pinned version (or any release it could mean) satisfies `range`
+///
+/// Exact versions are checked directly, aliases are resolved through the
+/// index, and partial versions like "18" pass if any release they cover does.
+/// Pins that can't be interpreted aren't flagged.
+pub fn version_satisfies(version: &str, range: &VersionRange, index: &ReleaseIndex) -> bool {
+    if let Some(parsed) = parse_version(version) {
+        return range.matches(&parsed);
+    }
+    if let Some(resolved) = index.resolve_alias(version) {
+        return range.matches_str(&resolved);
+    }
+    match VersionRange::parse(version) {
+        Ok(pinned) => {
+            let covered: Vec<_> = index
+                .releases()
+                .iter()
+                .filter(|r| pinned.matches(&r.version))
+                .collect();
+            covered.is_empty() || covered.iter().any(|r| range.matches(&r.version))
+        }
+        Err(_) => true,
+    }
+}
+
+/// Version string without a leading `v`, for comparing pins
+fn normalize(version: &str) -> &str {
+    version.trim().trim_start_matches('v')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const TEST_INDEX: &str = r#"{
+        "releases": [
+            {"version": "22.11.0", "lts": "Jod"},
+            {"version": "20.18.1", "lts": "Iron"},
+            {"version": "18.20.5", "lts": "Hydrogen"}
+        ]
+    }"#;
+
+    fn index() -> ReleaseIndex {
+        ReleaseIndex::from_json(TEST_INDEX).unwrap()
+    }
+
+    #[test]
+    fn test_excludes() {
+        let root = Path::new("/repo");
+        let mut excludes = Excludes::default();
+        for line in [
+            "# comment",
+            "dist/",
+            "/fixtures",
+            "*.tmp",
+            "tmp*",
+            "!tmp-keep",
+        ] {
+            excludes.add(root, line);
+        }
+
+        assert!(excludes.is_excluded(&root.join("packages/web/dist"), true));
+        assert!(!excludes.is_excluded(&root.join("dist"), false));
+        assert!(excludes.is_excluded(&root.join("fixtures"), true));
+        assert!(!excludes.is_excluded(&root.join("packages/fixtures"), true));
+        assert!(excludes.is_excluded(&root.join("tmp-old"), true));
+        assert!(!excludes.is_excluded(&root.join("tmp-keep"), true));
+        assert!(!excludes.is_excluded(Path::new("/elsewhere/dist"), true));
+    }
+
+    #[test]
+    fn test_version_satisfies() {
+        let index = index();
+        let range = VersionRange::parse(">=20").unwrap();
+
+        assert!(version_satisfies("v20.11.0", &range, &index));
+        assert!(!version_satisfies("18.20.0", &range, &index));
+        assert!(version_satisfies("lts/iron", &range, &index));
+        assert!(!version_satisfies("lts/hydrogen", &range, &index));
+        assert!(version_satisfies("22", &range, &index));
+        assert!(!version_satisfies("18", &range, &index));
+        // Pins anvs can't interpret aren't reported
+        assert!(version_satisfies("system", &range, &index));
+    }
+
+    #[test]
+    fn test_unsatisfied_engines() {
+        let temp = tempdir().unwrap();
+        let index = index();
+        assert_eq!(unsatisfied_engines(temp.path(), "16.20.2", &index), None);
+
+        fs::write(
+            temp.path().join("package.json"),
+            r#"{"engines": {"node": ">=18"}}"#,
+        )
+        .unwrap();
+        let engines = unsatisfied_engines(temp.path(), "16.20.2", &index).unwrap();
+        assert_eq!(engines.source, PinSource::Engines);
+        assert_eq!(engines.version, ">=18");
+        assert_eq!(unsatisfied_engines(temp.path(), "18.20.0", &index), None);
+    }
+
+    #[test]
+    fn test_scan_tree() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+
+        write(".gitignore", "build/\n");
+        write(".nvmrc", "20.11.0\n");
+        write("package.json", r#"{"engines": {"node": ">=20"}}"#);
+        write("packages/api/.nvmrc", "18.20.0\n");
+        write(
+            "packages/api/package.json",
+            r#"{"engines": {"node": "^20"}, "volta": {"node": "20.11.0"}}"#,
+        );
+        write("packages/web/.tool-versions", "nodejs 22.11.0\n");
+        write("packages/web/.node-version", "20.11.0\n");
+        write("build/.nvmrc", "16\n");
+        write("node_modules/dep/.nvmrc", "0.12\n");
+
+        let report = scan(root, Excludes::default(), &index()).unwrap();
+
+        let found: Vec<_> = report
+            .pins
+            .iter()
+            .map(|p| (p.path.strip_prefix(root).unwrap(), p.source))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Path::new(".nvmrc"), PinSource::Nvmrc),
+                (Path::new("package.json"), PinSource::Engines),
+                (Path::new("packages/api/.nvmrc"), PinSource::Nvmrc),
+                (Path::new("packages/api/package.json"), PinSource::Engines),
+                (Path::new("packages/api/package.json"), PinSource::Volta),
+                (
+                    Path::new("packages/web/.node-version"),
+                    PinSource::NodeVersion
+                ),
+                (
+                    Path::new("packages/web/.tool-versions"),
+                    PinSource::ToolVersions
+                ),
+            ]
+        );
+
+        let messages: Vec<_> = report.conflicts.iter().map(|c| &c.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                ".nvmrc pins 18.20.0 but engines.node requires ^20",
+                "package.json volta.node pins 20.11.0 but .nvmrc pins 18.20.0",
+                ".tool-versions pins 22.11.0 but .node-version pins 20.11.0",
+            ]
+        );
+        assert_eq!(report.versions()["20.11.0"], 3);
+    }
+}
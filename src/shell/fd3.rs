@@ -1,3 +1,4 @@
+use super::CommandSink;
 use log::{debug, trace};
 use std::io;
 use std::os::unix::io::RawFd;
@@ -42,6 +43,14 @@ impl CommandWriter {
         Ok(Self { fd })
     }
 
+    /// Creates a CommandWriter that discards all commands
+    ///
+    /// Used where no shell is listening (e.g., the daemon resolving on a
+    /// client's behalf), so an inherited FD:3 is never written to.
+    pub fn disabled() -> Self {
+        Self { fd: None }
+    }
+
     /// Checks if a file descriptor is open
     ///
     /// Uses fcntl to check if the FD is valid without modifying it.
@@ -51,6 +60,16 @@ impl CommandWriter {
         unsafe { libc::fcntl(fd, libc::F_GETFD) != -1 }
     }
 
+    /// Checks if FD:3 is available
+    ///
+    /// # Returns
+    /// true if commands will be written to FD:3, false if they'll be discarded
+    pub fn is_available(&self) -> bool {
+        self.fd.is_some()
+    }
+}
+
+impl CommandSink for CommandWriter {
     /// Writes a shell command to FD:3
     ///
     /// The command will be executed by the parent shell. Multiple commands
@@ -66,7 +85,7 @@ impl CommandWriter {
     /// # Returns
     /// - Ok(()) if write successful or FD:3 not available
     /// - Err(io::Error) if write failed
-    pub fn write_command(&mut self, command: &str) -> io::Result<()> {
+    fn write_command(&mut self, command: &str) -> io::Result<()> {
         trace!("Writing command to FD:3: {command}");
 
         if let Some(fd) = self.fd {
@@ -95,14 +114,6 @@ impl CommandWriter {
 
         Ok(())
     }
-
-    /// Checks if FD:3 is available
-    ///
-    /// # Returns
-    /// true if commands will be written to FD:3, false if they'll be discarded
-    pub fn is_available(&self) -> bool {
-        self.fd.is_some()
-    }
 }
 
 impl Default for CommandWriter {
@@ -111,37 +122,6 @@ impl Default for CommandWriter {
     }
 }
 
-/// Mock command writer for testing
-#[cfg(test)]
-pub struct MockCommandWriter {
-    pub commands: Vec<String>,
-}
-
-#[cfg(test)]
-impl Default for MockCommandWriter {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-#[cfg(test)]
-impl MockCommandWriter {
-    pub fn new() -> Self {
-        Self {
-            commands: Vec::new(),
-        }
-    }
-
-    pub fn write_command(&mut self, cmd: &str) -> std::io::Result<()> {
-        self.commands.push(cmd.to_string());
-        Ok(())
-    }
-
-    pub fn is_available(&self) -> bool {
-        true
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,4 @@
-use super::CommandOutput;
+use super::{CommandOutput, CommandSink};
 use anyhow::Result;
 use std::io::{self, Write};
 
@@ -99,6 +99,22 @@ impl JsonCommandWriter {
     }
 }
 
+impl CommandSink for JsonCommandWriter {
+    /// Adds a command to the JSON output
+    fn write_command(&mut self, command: &str) -> io::Result<()> {
+        self.add_command(command.to_string());
+        Ok(())
+    }
+
+    /// Writes the collected commands to stdout
+    fn finish(&mut self) -> io::Result<()> {
+        let writer = Self {
+            commands: std::mem::take(&mut self.commands),
+        };
+        writer.write().map_err(io::Error::other)
+    }
+}
+
 impl Default for JsonCommandWriter {
     fn default() -> Self {
         Self::new()
@@ -171,6 +187,14 @@ mod tests {
         assert_eq!(writer.commands[0], "Write-Host 'Hello'");
     }
 
+    #[test]
+    fn test_sink_collects_commands() {
+        let mut writer = JsonCommandWriter::new();
+        CommandSink::write_command(&mut writer, "nvm use 18.20.0").unwrap();
+
+        assert_eq!(writer.commands, vec!["nvm use 18.20.0"]);
+    }
+
     #[test]
     fn test_json_serialization() {
         let output = CommandOutput {
@@ -1,20 +1,21 @@
 mod fd3;
 mod json_writer;
+mod sink;
+mod snapshot;
 
 pub use fd3::CommandWriter;
 pub use json_writer::JsonCommandWriter;
-
-#[cfg(test)]
-pub use fd3::MockCommandWriter;
+pub use sink::{CommandSink, DryRunPrinter, RecordingSink};
+pub use snapshot::{EnvSnapshot, SNAPSHOT_MARKER, SNAPSHOT_VARS};
 
 use serde::{Deserialize, Serialize};
 
 /// Protocol for communicating activation commands to shell
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputProtocol {
     /// Unix file descriptor #3 protocol (bash/zsh)
     Fd3,
-    /// JSON protocol for Windows PowerShell
+    /// JSON protocol for PowerShell and Nushell
     Json,
 }
 
@@ -27,15 +28,6 @@ impl OutputProtocol {
         #[cfg(not(windows))]
         return OutputProtocol::Fd3;
     }
-
-    /// Check if running in PowerShell (via environment)
-    pub fn from_env() -> Self {
-        if cfg!(windows) || std::env::var("PSModulePath").is_ok() {
-            OutputProtocol::Json
-        } else {
-            OutputProtocol::Fd3
-        }
-    }
 }
 
 /// JSON structure for command output (used by PowerShell)
@@ -56,25 +48,4 @@ mod tests {
         #[cfg(not(windows))]
         assert_eq!(protocol, OutputProtocol::Fd3);
     }
-
-    #[test]
-    fn test_protocol_from_env() {
-        // Without PowerShell env var
-        std::env::remove_var("PSModulePath");
-        let protocol = OutputProtocol::from_env();
-
-        #[cfg(windows)]
-        assert_eq!(protocol, OutputProtocol::Json);
-
-        #[cfg(not(windows))]
-        {
-            assert_eq!(protocol, OutputProtocol::Fd3);
-
-            // With PowerShell env var (simulating PowerShell on Unix)
-            std::env::set_var("PSModulePath", "/usr/share/powershell");
-            let protocol = OutputProtocol::from_env();
-            assert_eq!(protocol, OutputProtocol::Json);
-            std::env::remove_var("PSModulePath");
-        }
-    }
 }
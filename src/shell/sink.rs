@@ -0,0 +1,105 @@
+//! Command sinks
+//!
+//! Activation produces shell commands; a [`CommandSink`] decides where they
+//! go. The orchestrator and the daemon client only see the trait, so bash and
+//! zsh (FD:3), PowerShell and Nushell (JSON on stdout), dry runs and tests all
+//! share one activation path.
+
+use super::{CommandWriter, JsonCommandWriter, OutputProtocol};
+use std::io;
+
+/// Destination for commands the parent shell should run
+pub trait CommandSink {
+    /// Takes one command (must not contain newlines)
+    fn write_command(&mut self, command: &str) -> io::Result<()>;
+
+    /// Called once activation is over
+    ///
+    /// Sinks that buffer commands write them out here.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputProtocol {
+    /// Sink that speaks this protocol to the parent shell
+    pub fn sink(self) -> io::Result<Box<dyn CommandSink>> {
+        Ok(match self {
+            OutputProtocol::Fd3 => Box::new(CommandWriter::new()?),
+            OutputProtocol::Json => Box::new(JsonCommandWriter::new()),
+        })
+    }
+}
+
+/// Keeps commands in memory
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    commands: Vec<String>,
+}
+
+impl RecordingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commands written so far
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+}
+
+impl CommandSink for RecordingSink {
+    fn write_command(&mut self, command: &str) -> io::Result<()> {
+        self.commands.push(command.to_string());
+        Ok(())
+    }
+}
+
+/// Prints commands instead of handing them to the shell
+///
+/// Used by `anvs activate --dry-run`.
+#[derive(Debug, Default)]
+pub struct DryRunPrinter {
+    recorded: RecordingSink,
+}
+
+impl CommandSink for DryRunPrinter {
+    fn write_command(&mut self, command: &str) -> io::Result<()> {
+        self.recorded.write_command(command)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let commands = self.recorded.commands();
+        if commands.is_empty() {
+            crate::output::info("Dry run: no commands would be written to the shell");
+        } else {
+            crate::output::info("Dry run: would write to the shell:");
+            for command in commands {
+                println!("  {command}");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_sink() {
+        let mut sink = RecordingSink::new();
+        sink.write_command("nvm use 18.20.0").unwrap();
+        sink.write_command("echo done").unwrap();
+        sink.finish().unwrap();
+        assert_eq!(sink.commands(), ["nvm use 18.20.0", "echo done"]);
+    }
+
+    #[test]
+    fn test_protocol_sinks_accept_commands() {
+        for protocol in [OutputProtocol::Fd3, OutputProtocol::Json] {
+            let mut sink = protocol.sink().unwrap();
+            assert!(sink.write_command("echo test").is_ok());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use log::debug;
+
+/// Environment variables that Node.js version managers modify when switching versions
+///
+/// - `PATH`: nvm and fnm both prepend the active version's `bin` directory
+/// - `NVM_BIN`, `NVM_INC`: exported by `nvm use`
+/// - `FNM_MULTISHELL_PATH`: exported by `fnm env` / `fnm use`
+pub const SNAPSHOT_VARS: &[&str] = &["PATH", "NVM_BIN", "NVM_INC", "FNM_MULTISHELL_PATH"];
+
+/// Marker variable exported once a snapshot has been captured in the current shell
+pub const SNAPSHOT_MARKER: &str = "ANVS_ENV_SAVED";
+
+/// Prefix for variables holding the saved values (e.g., `ANVS_SAVED_PATH`)
+const SAVED_PREFIX: &str = "ANVS_SAVED_";
+
+/// Snapshot of the shell environment as it was before anvs first activated
+///
+/// The snapshot itself lives in the parent shell as exported variables, since
+/// anvs runs as a short-lived child process. This struct reads the current and
+/// saved values from the environment anvs inherited and generates the shell
+/// commands to capture or restore them.
+///
+/// # Protocol
+/// - First activation in a shell: export `ANVS_SAVED_<VAR>` for each set variable
+///   and `ANVS_ENV_SAVED=1`
+/// - Leaving all projects: export each saved value back (or unset variables that
+///   were not set originally), then clear the saved copies and the marker
+#[derive(Debug, Clone, Default)]
+pub struct EnvSnapshot {
+    /// Current values of `SNAPSHOT_VARS` (None = unset)
+    current: Vec<(String, Option<String>)>,
+    /// Saved values of `SNAPSHOT_VARS` (None = was unset when captured)
+    saved: Vec<(String, Option<String>)>,
+    /// Whether a snapshot has already been captured in this shell
+    captured: bool,
+}
+
+impl EnvSnapshot {
+    /// Read the snapshot state from the process environment
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Read the snapshot state using a custom variable lookup (for testing)
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let current = SNAPSHOT_VARS
+            .iter()
+            .map(|var| (var.to_string(), lookup(var)))
+            .collect();
+
+        let saved = SNAPSHOT_VARS
+            .iter()
+            .map(|var| (var.to_string(), lookup(&format!("{SAVED_PREFIX}{var}"))))
+            .collect();
+
+        let captured = lookup(SNAPSHOT_MARKER).is_some();
+        debug!("Environment snapshot captured: {captured}");
+
+        Self {
+            current,
+            saved,
+            captured,
+        }
+    }
+
+    /// Whether a snapshot has already been captured in this shell
+    pub fn is_captured(&self) -> bool {
+        self.captured
+    }
+
+    /// Value `var` had when the snapshot was captured (None = unset or not captured)
+    pub fn saved(&self, var: &str) -> Option<&str> {
+        self.saved
+            .iter()
+            .find(|(name, _)| name == var)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Generates the shell commands that save the current environment
+    ///
+    /// Returns an empty list if a snapshot already exists, so the first
+    /// snapshot in a shell is never overwritten by a later activation.
+    pub fn capture_commands(&self) -> Vec<String> {
+        if self.captured {
+            return Vec::new();
+        }
+
+        let mut commands: Vec<String> = self
+            .current
+            .iter()
+            .filter_map(|(var, value)| {
+                value
+                    .as_ref()
+                    .map(|value| format!("export {SAVED_PREFIX}{var}={}", escape(value)))
+            })
+            .collect();
+
+        commands.push(format!("export {SNAPSHOT_MARKER}=1"));
+        commands
+    }
+
+    /// Generates the shell commands that restore the saved environment
+    ///
+    /// Returns an empty list if no snapshot has been captured.
+    pub fn restore_commands(&self) -> Vec<String> {
+        if !self.captured {
+            return Vec::new();
+        }
+
+        let mut commands = Vec::new();
+
+        for (var, value) in &self.saved {
+            match value {
+                Some(value) => commands.push(format!("export {var}={}", escape(value))),
+                None => commands.push(format!("unset {var}")),
+            }
+        }
+
+        for (var, _) in &self.saved {
+            commands.push(format!("unset {SAVED_PREFIX}{var}"));
+        }
+        commands.push(format!("unset {SNAPSHOT_MARKER}"));
+
+        // Forget cached command locations from the project's Node.js version
+        commands.push("hash -r".to_string());
+
+        commands
+    }
+}
+
+/// Escapes a value for safe use in a shell `export` statement
+fn escape(value: &str) -> String {
+    shell_escape::escape(value.into()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn snapshot(vars: &[(&str, &str)]) -> EnvSnapshot {
+        let env: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        EnvSnapshot::from_lookup(|key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn test_capture_saves_set_variables() {
+        let snap = snapshot(&[("PATH", "/usr/bin:/bin"), ("NVM_BIN", "/nvm/v18/bin")]);
+        assert!(!snap.is_captured());
+
+        let commands = snap.capture_commands();
+        assert!(commands.contains(&"export ANVS_SAVED_PATH='/usr/bin:/bin'".to_string()));
+        assert!(commands.contains(&"export ANVS_SAVED_NVM_BIN=/nvm/v18/bin".to_string()));
+        assert!(!commands.iter().any(|c| c.contains("NVM_INC")));
+        assert_eq!(commands.last().unwrap(), "export ANVS_ENV_SAVED=1");
+    }
+
+    #[test]
+    fn test_capture_skipped_when_already_captured() {
+        let snap = snapshot(&[("PATH", "/usr/bin"), ("ANVS_ENV_SAVED", "1")]);
+        assert!(snap.is_captured());
+        assert!(snap.capture_commands().is_empty());
+    }
+
+    #[test]
+    fn test_restore_exports_saved_and_unsets_missing() {
+        let snap = snapshot(&[
+            ("PATH", "/nvm/v20/bin:/usr/bin"),
+            ("NVM_BIN", "/nvm/v20/bin"),
+            ("ANVS_SAVED_PATH", "/usr/bin"),
+            ("ANVS_ENV_SAVED", "1"),
+        ]);
+
+        let commands = snap.restore_commands();
+        assert!(commands.contains(&"export PATH=/usr/bin".to_string()));
+        assert!(commands.contains(&"unset NVM_BIN".to_string()));
+        assert!(commands.contains(&"unset ANVS_SAVED_PATH".to_string()));
+        assert!(commands.contains(&"unset ANVS_ENV_SAVED".to_string()));
+    }
+
+    #[test]
+    fn test_restore_empty_without_snapshot() {
+        let snap = snapshot(&[("PATH", "/usr/bin")]);
+        assert!(snap.restore_commands().is_empty());
+    }
+
+    #[test]
+    fn test_values_are_escaped() {
+        let snap = snapshot(&[("PATH", "/path with spaces/bin:$(whoami)")]);
+        let commands = snap.capture_commands();
+        assert!(commands
+            .contains(&"export ANVS_SAVED_PATH='/path with spaces/bin:$(whoami)'".to_string()));
+    }
+}
@@ -0,0 +1,344 @@
+//! Shims for processes that don't run the shell hook
+//!
+//! The cd hook only switches Node.js in interactive shells; IDEs, cron jobs
+//! and git hooks get whatever `node` is first on their PATH.
+//! `anvs shims install` links `node`, `npm`, `npx` and `corepack` in
+//! `~/.anvs/shims` to the anvs binary. Started under one of those names, anvs
+//! resolves the version for the current directory the way `anvs activate`
+//! would and execs the real binary from the version manager's install
+//! directory. Resolutions are cached in `~/.anvs/cache/shims.json`, so a cache
+//! hit only loads the config and checks a few mtimes.
+
+use crate::activation::Orchestrator;
+use crate::cache::ResolutionCache;
+use crate::config::Config;
+use crate::output;
+use crate::plugins::{PluginRegistry, VersionManagerPlugin};
+use crate::release_index::ReleaseIndex;
+use crate::shell::CommandWriter;
+use crate::version_file::{parse_version, VersionRange};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+/// Binaries anvs installs shims for
+pub const SHIMS: &[&str] = &["node", "npm", "npx", "corepack"];
+
+/// Default shims directory: ~/.anvs/shims
+pub fn shims_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".anvs").join("shims"))
+}
+
+/// Shim anvs was started as, given its `argv[0]`
+pub fn shim_name(argv0: &OsStr) -> Option<&'static str> {
+    let name = Path::new(argv0).file_name()?;
+    SHIMS.iter().copied().find(|shim| name == OsStr::new(shim))
+}
+
+/// Links every shim in `dir` to `exe`, replacing shims already there
+///
+/// # Returns
+/// The shims created
+pub fn install(dir: &Path, exe: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    SHIMS
+        .iter()
+        .map(|name| {
+            let shim = dir.join(name);
+            if shim.symlink_metadata().is_ok() {
+                fs::remove_file(&shim)
+                    .with_context(|| format!("failed to replace {}", shim.display()))?;
+            }
+            std::os::unix::fs::symlink(exe, &shim)
+                .with_context(|| format!("failed to create {}", shim.display()))?;
+            Ok(shim)
+        })
+        .collect()
+}
+
+/// Runs the real `tool` for the current directory
+///
+/// Only returns (with an exit code) if the binary couldn't be run.
+pub fn run(tool: &str) -> i32 {
+    match exec(tool) {
+        Ok(never) => match never {},
+        Err(e) => {
+            output::error(&format!("{tool} (anvs shim): {e:#}"));
+            1
+        }
+    }
+}
+
+fn exec(tool: &str) -> Result<std::convert::Infallible> {
+    let dir = env::current_dir().context("failed to get current directory")?;
+    let bin_dir = resolve(&dir)?;
+
+    let binary = match &bin_dir {
+        Some(bin_dir) => bin_dir.join(tool),
+        None => find_on_path(tool).with_context(|| {
+            format!(
+                "no version file applies to {} and no {tool} is on PATH",
+                dir.display()
+            )
+        })?,
+    };
+    if !binary.is_file() {
+        anyhow::bail!("{} does not exist", binary.display());
+    }
+    debug!("Shim {tool} → {}", binary.display());
+
+    let mut command = Command::new(&binary);
+    command.arg0(tool).args(env::args_os().skip(1));
+    // Scripts like npm run `#!/usr/bin/env node`: find this node directly
+    if let Some(bin_dir) = &bin_dir {
+        command.env("PATH", prepend_path(bin_dir));
+    }
+    let error = command.exec();
+    Err(error).with_context(|| format!("failed to run {}", binary.display()))
+}
+
+/// `bin` directory of the Node.js version for `dir`, from the cache when valid
+///
+/// # Returns
+/// `None` if no version file or default version applies (use PATH)
+pub fn resolve(dir: &Path) -> Result<Option<PathBuf>> {
+    let config = Config::load_for(dir)?;
+    let cache = ResolutionCache::open_default().ok().map(Arc::new);
+    let mut registry = PluginRegistry::from_config(&config);
+    if let Some(cache) = &cache {
+        registry = registry.with_cache(Arc::clone(cache));
+    }
+
+    let fingerprint = registry.fingerprint();
+    if let Some(bin_dir) = cache
+        .as_ref()
+        .and_then(|cache| cache.get_shim(dir, &fingerprint))
+    {
+        return Ok(Some(bin_dir));
+    }
+
+    let resolution = resolve_uncached(&config, &registry, cache.clone(), dir)?;
+    if let (Some(cache), Some(bin_dir)) = (&cache, &resolution.bin_dir) {
+        let watched = watched_paths(dir, resolution.version_file.as_deref());
+        if let Err(e) = cache.put_shim(dir, &fingerprint, &watched, bin_dir) {
+            warn!("Failed to cache shim resolution for {}: {e}", dir.display());
+        }
+    }
+    Ok(resolution.bin_dir)
+}
+
+/// Where a shim's binaries come from
+#[derive(Debug, Default, PartialEq)]
+struct Resolution {
+    /// `bin` directory of the version (`None` to use PATH)
+    bin_dir: Option<PathBuf>,
+    /// Version file the version came from
+    version_file: Option<PathBuf>,
+}
+
+fn resolve_uncached(
+    config: &Config,
+    registry: &PluginRegistry,
+    cache: Option<Arc<ResolutionCache>>,
+    dir: &Path,
+) -> Result<Resolution> {
+    let mut writer = CommandWriter::disabled();
+    let mut orchestrator = Orchestrator::new(config, registry, &mut writer);
+    if let Some(cache) = cache {
+        orchestrator = orchestrator.with_cache(cache);
+    }
+
+    let (version, plugin, version_file) = match orchestrator.find_target(dir)? {
+        Some(target) => (
+            target.version,
+            target.plugin,
+            Some(target.version_file.path),
+        ),
+        None => match default_version(config, registry)? {
+            Some(version) => (version, None, None),
+            None => return Ok(Resolution::default()),
+        },
+    };
+    if version == "system" {
+        return Ok(Resolution::default());
+    }
+
+    let preferred = plugin.map(|name| registry.preferring(&name));
+    let registry = preferred.as_ref().unwrap_or(registry);
+    for plugin in registry.available_plugins() {
+        if let Some(node_dir) = installed_dir(plugin.as_ref(), &version) {
+            return Ok(Resolution {
+                bin_dir: Some(node_dir.join("bin")),
+                version_file,
+            });
+        }
+    }
+
+    let source = version_file
+        .map(|file| format!(" (from {})", file.display()))
+        .unwrap_or_default();
+    anyhow::bail!(
+        "Node.js {version}{source} is not installed; run 'anvs activate' in {} to install it",
+        dir.display()
+    )
+}
+
+/// Version to use outside projects: `default_version`, or the first plugin's
+/// default
+fn default_version(config: &Config, registry: &PluginRegistry) -> Result<Option<String>> {
+    if let Some(version) = &config.default_version {
+        return Ok(Some(version.clone()));
+    }
+    for plugin in registry.available_plugins() {
+        if let Some(version) = plugin.default_version()? {
+            return Ok(Some(version));
+        }
+    }
+    Ok(None)
+}
+
+/// Install prefix of `version`, resolving partial versions like "18" to the
+/// newest installed match (as `nvm use 18` does)
+fn installed_dir(plugin: &dyn VersionManagerPlugin, version: &str) -> Option<PathBuf> {
+    if let Some(dir) = plugin.node_dir(version) {
+        return Some(dir);
+    }
+    let range = VersionRange::parse(version.trim_start_matches('v')).ok()?;
+    let newest = plugin
+        .list_versions()
+        .ok()?
+        .iter()
+        .filter_map(|installed| parse_version(installed))
+        .filter(|installed| range.matches(installed))
+        .max()?;
+    plugin.node_dir(&newest.to_string())
+}
+
+/// Paths whose changes invalidate a cached resolution for `dir`
+///
+/// Creating or removing a version file or `.anvs.yaml` changes the mtime of
+/// `dir` or one of its parents; editing one changes its own.
+fn watched_paths(dir: &Path, version_file: Option<&Path>) -> Vec<PathBuf> {
+    let mut watched: Vec<PathBuf> = dir.ancestors().map(Path::to_path_buf).collect();
+    watched.extend(version_file.map(Path::to_path_buf));
+    watched.extend(Config::user_config_path().ok());
+    watched.extend(Config::find_project_config(dir).ok().flatten());
+    watched.extend(ReleaseIndex::default_path().ok());
+    watched
+}
+
+/// First `tool` on PATH that isn't a shim
+fn find_on_path(tool: &str) -> Option<PathBuf> {
+    let anvs = env::current_exe().and_then(fs::canonicalize).ok();
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(tool))
+        .find(|candidate| is_executable(candidate) && fs::canonicalize(candidate).ok() != anvs)
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// PATH with `dir` in front
+fn prepend_path(dir: &Path) -> OsString {
+    let rest = env::var_os("PATH").unwrap_or_default();
+    env::join_paths(std::iter::once(dir.to_path_buf()).chain(env::split_paths(&rest)))
+        .unwrap_or(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::MockPlugin;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_shim_name() {
+        assert_eq!(
+            shim_name(OsStr::new("/home/me/.anvs/shims/npx")),
+            Some("npx")
+        );
+        assert_eq!(shim_name(OsStr::new("node")), Some("node"));
+        assert_eq!(shim_name(OsStr::new("/usr/local/bin/anvs")), None);
+    }
+
+    #[test]
+    fn test_install_links_every_shim() {
+        let temp = TempDir::new().unwrap();
+        let exe = temp.path().join("anvs");
+        fs::write(&exe, "").unwrap();
+        let dir = temp.path().join("shims");
+
+        assert_eq!(install(&dir, &exe).unwrap().len(), SHIMS.len());
+        // Installing again replaces the links
+        fs::write(dir.join("node"), "stale").unwrap();
+        install(&dir, &exe).unwrap();
+
+        for name in SHIMS {
+            assert_eq!(fs::read_link(dir.join(name)).unwrap(), exe);
+        }
+    }
+
+    fn config() -> Config {
+        Config {
+            plugins: vec!["mock".to_string()],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_version_file() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("app");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join(".nvmrc"), "18.20.0\n").unwrap();
+        let node_dir = temp.path().join("v18.20.0");
+
+        let plugin = MockPlugin::new("mock").with_node_dir(&node_dir);
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(plugin)]);
+        let resolution = resolve_uncached(&config(), &registry, None, &project).unwrap();
+        assert_eq!(resolution.bin_dir, Some(node_dir.join("bin")));
+        assert_eq!(resolution.version_file, Some(project.join(".nvmrc")));
+
+        // A version no plugin has installed is an error, not another node
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(MockPlugin::new("mock"))]);
+        let error = resolve_uncached(&config(), &registry, None, &project).unwrap_err();
+        assert!(error.to_string().contains("18.20.0"));
+    }
+
+    #[test]
+    fn test_resolve_outside_projects() {
+        let temp = TempDir::new().unwrap();
+        let node_dir = temp.path().join("v20.11.0");
+
+        let plugin = MockPlugin::new("mock")
+            .with_node_dir(&node_dir)
+            .with_default("20.11.0");
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(plugin)]);
+        let resolution = resolve_uncached(&config(), &registry, None, temp.path()).unwrap();
+        assert_eq!(resolution.bin_dir, Some(node_dir.join("bin")));
+        assert_eq!(resolution.version_file, None);
+
+        // Without a default, the shim falls through to PATH
+        let registry = PluginRegistry::with_plugins(vec![Arc::new(MockPlugin::new("mock"))]);
+        let resolution = resolve_uncached(&config(), &registry, None, temp.path()).unwrap();
+        assert_eq!(resolution, Resolution::default());
+    }
+
+    #[test]
+    fn test_watched_paths_cover_parents() {
+        let watched = watched_paths(Path::new("/work/app"), Some(Path::new("/work/.nvmrc")));
+        for path in ["/work/app", "/work", "/", "/work/.nvmrc"] {
+            assert!(watched.contains(&PathBuf::from(path)), "{path}");
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::PackageJson;
+use crate::config::PathRule;
 use anyhow::{Context, Result};
 use log::{debug, trace};
 use std::fs;
@@ -28,6 +29,8 @@ pub enum VersionFileSource {
     PackageJson,
     /// .tool-versions file (asdf)
     ToolVersions,
+    /// Path rule in the user config (`path` holds the rule's glob)
+    Rule,
     /// Other/unknown
     Other(String),
 }
@@ -66,43 +69,8 @@ impl VersionFile {
 
             // Try each filename in priority order
             for filename in filenames {
-                let file_path = dir.join(filename);
-
-                if file_path.exists() && file_path.is_file() {
-                    debug!("Found version file: {file_path:?}");
-
-                    // Special handling for package.json
-                    if filename == "package.json" {
-                        if let Ok(pkg) = PackageJson::parse(&file_path) {
-                            if let Some(node_version) = pkg.node_version() {
-                                debug!("Found Node.js version in package.json: {node_version}");
-                                return Ok(Some(Self {
-                                    path: file_path,
-                                    version: node_version.to_string(),
-                                    source: VersionFileSource::PackageJson,
-                                }));
-                            } else {
-                                debug!("package.json has no engines.node field, skipping");
-                                continue;
-                            }
-                        } else {
-                            debug!("Failed to parse package.json, skipping");
-                            continue;
-                        }
-                    }
-
-                    // Parse regular version files (.nvmrc, .node-version, etc.)
-                    let version = Self::parse(&file_path).with_context(|| {
-                        format!("failed to parse version file: {}", file_path.display())
-                    })?;
-
-                    let source = Self::detect_source(filename);
-
-                    return Ok(Some(Self {
-                        path: file_path,
-                        version,
-                        source,
-                    }));
+                if let Some(version_file) = Self::read(&dir.join(filename), filename)? {
+                    return Ok(Some(version_file));
                 }
             }
 
@@ -123,6 +91,123 @@ impl VersionFile {
         Ok(None)
     }
 
+    /// Version "file" for a path rule, if the rule sets a version
+    ///
+    /// The path is the rule's glob, so it shows up in diagnostics and the
+    /// shell hook's active key.
+    pub fn from_rule(rule: &PathRule) -> Option<Self> {
+        Some(Self {
+            path: PathBuf::from(&rule.path),
+            version: rule.version.clone()?,
+            source: VersionFileSource::Rule,
+        })
+    }
+
+    /// Find every version file under `root`
+    ///
+    /// Walks the whole tree (skipping `node_modules` and hidden directories)
+    /// and returns the highest-priority version file of each directory that
+    /// has one. Unreadable version files are skipped.
+    ///
+    /// # Arguments
+    /// * `root` - Directory to scan
+    /// * `filenames` - List of filenames to search for (in priority order)
+    pub fn find_all(root: &Path, filenames: &[String]) -> Result<Vec<Self>> {
+        let mut found = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            trace!("Scanning directory: {dir:?}");
+
+            for filename in filenames {
+                match Self::read(&dir.join(filename), filename) {
+                    Ok(Some(version_file)) => {
+                        found.push(version_file);
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(e) => debug!("Skipping unreadable version file: {e:#}"),
+                }
+            }
+
+            let entries = fs::read_dir(&dir)
+                .with_context(|| format!("failed to read directory: {}", dir.display()))?;
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+                if is_dir && name != "node_modules" && !name.starts_with('.') {
+                    pending.push(entry.path());
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(found)
+    }
+
+    /// Read the version file `filename` in `dir`, without searching parents
+    ///
+    /// Returns `None` if the file doesn't exist or declares no Node.js version.
+    pub fn find_in(dir: &Path, filename: &str) -> Result<Option<Self>> {
+        Self::read(&dir.join(filename), filename)
+    }
+
+    /// Read a version file, if it exists and declares a version
+    ///
+    /// Returns `None` for missing files and package.json files without
+    /// engines.node.
+    fn read(file_path: &Path, filename: &str) -> Result<Option<Self>> {
+        if !file_path.is_file() {
+            return Ok(None);
+        }
+        debug!("Found version file: {file_path:?}");
+
+        // Special handling for package.json
+        if filename == "package.json" {
+            let Ok(pkg) = PackageJson::parse(file_path) else {
+                debug!("Failed to parse package.json, skipping");
+                return Ok(None);
+            };
+            let Some(node_version) = pkg.node_version() else {
+                debug!("package.json has no engines.node field, skipping");
+                return Ok(None);
+            };
+
+            debug!("Found Node.js version in package.json: {node_version}");
+            return Ok(Some(Self {
+                path: file_path.to_path_buf(),
+                version: node_version.to_string(),
+                source: VersionFileSource::PackageJson,
+            }));
+        }
+
+        // asdf files list one tool per line; only the Node.js entry matters
+        if filename == ".tool-versions" {
+            let content = fs::read_to_string(file_path)
+                .with_context(|| format!("failed to read file: {}", file_path.display()))?;
+            let Some(version) = Self::parse_tool_versions(&content) else {
+                debug!(".tool-versions has no nodejs entry, skipping");
+                return Ok(None);
+            };
+            return Ok(Some(Self {
+                path: file_path.to_path_buf(),
+                version,
+                source: VersionFileSource::ToolVersions,
+            }));
+        }
+
+        // Parse regular version files (.nvmrc, .node-version, etc.)
+        let version = Self::parse(file_path)
+            .with_context(|| format!("failed to parse version file: {}", file_path.display()))?;
+
+        Ok(Some(Self {
+            path: file_path.to_path_buf(),
+            version,
+            source: Self::detect_source(filename),
+        }))
+    }
+
     /// Parse version string from file
     ///
     /// Reads the first non-empty line and trims whitespace.
@@ -149,6 +234,21 @@ impl VersionFile {
         )
     }
 
+    /// Extract the Node.js version from `.tool-versions` content
+    ///
+    /// Reads the first version of the `nodejs` (or `node`) line, e.g.
+    /// `nodejs 20.11.0 18.20.0` yields "20.11.0".
+    pub fn parse_tool_versions(content: &str) -> Option<String> {
+        content.lines().find_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("nodejs" | "node") => fields.next().map(str::to_string),
+                _ => None,
+            }
+        })
+    }
+
     /// Detect source type from filename
     fn detect_source(filename: &str) -> VersionFileSource {
         match filename {
@@ -376,4 +476,72 @@ mod tests {
         assert_eq!(vf.version, ">=20.0.0");
         assert_eq!(vf.source, VersionFileSource::PackageJson);
     }
+
+    #[test]
+    fn test_find_tool_versions() {
+        let temp_dir = tempdir().unwrap();
+        let filenames = vec![".tool-versions".to_string()];
+
+        fs::write(temp_dir.path().join(".tool-versions"), "python 3.12.1\n").unwrap();
+        assert!(VersionFile::find(temp_dir.path(), &filenames)
+            .unwrap()
+            .is_none());
+
+        fs::write(
+            temp_dir.path().join(".tool-versions"),
+            "# tools\npython 3.12.1\nnodejs 20.11.0 18.20.0 # fallback\n",
+        )
+        .unwrap();
+        let found = VersionFile::find(temp_dir.path(), &filenames)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.version, "20.11.0");
+        assert_eq!(found.source, VersionFileSource::ToolVersions);
+    }
+
+    #[test]
+    fn test_find_all_walks_tree() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        for dir in ["a", "a/nested", "b", "node_modules/dep", ".git/x", "empty"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join(".nvmrc"), "20.11.0").unwrap();
+        fs::write(root.join("a/.nvmrc"), "16").unwrap();
+        fs::write(root.join("a/.node-version"), "18.20.0").unwrap();
+        fs::write(root.join("a/nested/.node-version"), "18.20.0").unwrap();
+        fs::write(
+            root.join("b/package.json"),
+            r#"{"engines": {"node": ">=20"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("node_modules/dep/.nvmrc"), "8").unwrap();
+        fs::write(root.join(".git/x/.nvmrc"), "8").unwrap();
+
+        let filenames = vec![
+            ".nvmrc".to_string(),
+            ".node-version".to_string(),
+            "package.json".to_string(),
+        ];
+        let found = VersionFile::find_all(root, &filenames).unwrap();
+
+        let summary: Vec<_> = found
+            .iter()
+            .map(|vf| {
+                (
+                    vf.path.strip_prefix(root).unwrap().display().to_string(),
+                    vf.version.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (".nvmrc".to_string(), "20.11.0"),
+                ("a/.nvmrc".to_string(), "16"),
+                ("a/nested/.node-version".to_string(), "18.20.0"),
+                ("b/package.json".to_string(), ">=20"),
+            ]
+        );
+    }
 }
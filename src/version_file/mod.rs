@@ -1,7 +1,11 @@
 mod finder;
 mod package_json;
+mod range;
 mod semver;
+mod workspace;
 
 pub use finder::{VersionFile, VersionFileSource};
-pub use package_json::{EnginesField, PackageJson};
+pub use package_json::{EnginesField, PackageJson, VoltaField};
+pub use range::{parse_version, RangeError, VersionRange};
 pub use semver::SemverResolver;
+pub use workspace::{find_workspace_root, WorkspacePins};
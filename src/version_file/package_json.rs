@@ -13,6 +13,18 @@ pub struct PackageJson {
     #[serde(default)]
     pub engines: Option<EnginesField>,
 
+    /// Volta toolchain pins ("volta": {"node": "18.20.0"})
+    #[serde(default)]
+    pub volta: Option<VoltaField>,
+
+    /// npm/yarn/pnpm workspace globs (an array, or {"packages": [...]})
+    #[serde(default)]
+    pub workspaces: Option<serde_json::Value>,
+
+    /// Package manager pinned for corepack ("packageManager": "pnpm@8.15.4")
+    #[serde(default, rename = "packageManager")]
+    pub package_manager: Option<String>,
+
     // Other common fields (optional, for future use)
     #[serde(default)]
     pub name: Option<String>,
@@ -31,6 +43,13 @@ pub struct EnginesField {
     pub npm: Option<String>,
 }
 
+/// Represents the "volta" field in package.json
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct VoltaField {
+    #[serde(default)]
+    pub node: Option<String>,
+}
+
 impl PackageJson {
     /// Parse package.json file
     ///
@@ -68,6 +87,16 @@ impl PackageJson {
         self.engines.as_ref().and_then(|e| e.node.as_deref())
     }
 
+    /// Extract the Node.js version pinned by Volta (volta.node)
+    pub fn volta_node_version(&self) -> Option<&str> {
+        self.volta.as_ref().and_then(|v| v.node.as_deref())
+    }
+
+    /// Extract the npm version requirement from engines.npm
+    pub fn npm_version(&self) -> Option<&str> {
+        self.engines.as_ref().and_then(|e| e.npm.as_deref())
+    }
+
     /// Check if package.json has Node.js version requirement
     pub fn has_node_version(&self) -> bool {
         self.node_version().is_some()
@@ -100,9 +129,38 @@ mod tests {
         let pkg = PackageJson::parse(&pkg_path).unwrap();
         assert_eq!(pkg.name, Some("test-app".to_string()));
         assert_eq!(pkg.node_version(), Some(">=18.0.0"));
+        assert_eq!(pkg.npm_version(), Some(">=9.0.0"));
         assert!(pkg.has_node_version());
     }
 
+    #[test]
+    fn test_parse_package_json_with_package_manager() {
+        let temp_dir = tempdir().unwrap();
+        let pkg_path = temp_dir.path().join("package.json");
+
+        fs::write(&pkg_path, r#"{"packageManager": "pnpm@8.15.4"}"#).unwrap();
+
+        let pkg = PackageJson::parse(&pkg_path).unwrap();
+        assert_eq!(pkg.package_manager.as_deref(), Some("pnpm@8.15.4"));
+        assert_eq!(pkg.npm_version(), None);
+    }
+
+    #[test]
+    fn test_parse_package_json_with_volta() {
+        let temp_dir = tempdir().unwrap();
+        let pkg_path = temp_dir.path().join("package.json");
+
+        fs::write(
+            &pkg_path,
+            r#"{"name": "test-app", "volta": {"node": "20.11.0", "npm": "10.2.4"}}"#,
+        )
+        .unwrap();
+
+        let pkg = PackageJson::parse(&pkg_path).unwrap();
+        assert_eq!(pkg.volta_node_version(), Some("20.11.0"));
+        assert!(!pkg.has_node_version());
+    }
+
     #[test]
     fn test_parse_package_json_without_engines() {
         let temp_dir = tempdir().unwrap();
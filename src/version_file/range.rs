@@ -0,0 +1,492 @@
+//! npm-compatible version ranges
+//!
+//! `package.json` `engines.node` values follow node-semver, not Cargo: they
+//! use `||`, hyphen ranges (`16 - 20`), x-ranges (`18.x`), space-separated
+//! comparator sets (`>=18 <21`) and node's prerelease rules. This module
+//! implements those semantics on top of `semver::Version`.
+//!
+//! Every range is desugared into sets of primitive comparators (`<`, `<=`,
+//! `>`, `>=`, `=`), exactly as node-semver does, e.g.
+//! `^1.2.3` → `>=1.2.3 <2.0.0-0` and `1.2` → `>=1.2.0 <1.3.0-0`.
+
+use semver::{Prerelease, Version};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Error returned when a string is not a valid npm range
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid version range '{range}': {reason}")]
+pub struct RangeError {
+    range: String,
+    reason: String,
+}
+
+/// Primitive comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Eq,
+}
+
+/// A single primitive comparator, e.g. `>=1.2.3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn new(op: Op, version: Version) -> Self {
+        Self { op, version }
+    }
+
+    /// Comparator that no version satisfies (`<0.0.0-0`)
+    fn none() -> Self {
+        Self::new(Op::Lt, version_pre0(0, 0, 0))
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp_precedence(&self.version);
+        match self.op {
+            Op::Lt => ordering == Ordering::Less,
+            Op::Lte => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Gte => ordering != Ordering::Less,
+            Op::Eq => ordering == Ordering::Equal,
+        }
+    }
+}
+
+/// Operator as written in the range (before desugaring)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeOp {
+    /// No operator, or `=`
+    Exact,
+    Tilde,
+    Caret,
+    Primitive(Op),
+}
+
+/// Possibly incomplete version (`1`, `1.2`, `1.x`, `1.2.3-beta`)
+///
+/// `None` components are wildcards (missing, `x`, `X` or `*`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Prerelease,
+}
+
+impl Partial {
+    /// The partial as a full version with wildcards as zero
+    fn floor(&self) -> Version {
+        let mut version = Version::new(
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+        );
+        version.pre = self.pre.clone();
+        version
+    }
+}
+
+/// A node-semver range: alternatives (`||`) of comparator sets
+///
+/// An empty set matches every release version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    raw: String,
+    sets: Vec<Vec<Comparator>>,
+}
+
+impl VersionRange {
+    /// Parse an npm range such as `>=18 <21`, `^18 || ^20`, `18.x` or `16 - 20`
+    pub fn parse(range: &str) -> Result<Self, RangeError> {
+        let error = |reason: String| RangeError {
+            range: range.to_string(),
+            reason,
+        };
+
+        let sets = range
+            .split("||")
+            .map(|set| parse_set(set).map_err(error))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            raw: range.trim().to_string(),
+            sets,
+        })
+    }
+
+    /// Returns true if `version` satisfies the range
+    ///
+    /// Prerelease versions only match a comparator set that mentions a
+    /// prerelease of the same `major.minor.patch` (node-semver behaviour), so
+    /// `>=18` never selects `22.0.0-rc.1`.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.sets.iter().any(|set| set_matches(set, version))
+    }
+
+    /// Like [`matches`](Self::matches), for a version string (`v` prefix allowed)
+    ///
+    /// Strings that aren't valid versions never match.
+    pub fn matches_str(&self, version: &str) -> bool {
+        parse_version(version).is_some_and(|v| self.matches(&v))
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = RangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// Parse a version, accepting a leading `v` or `=` (as node does)
+pub fn parse_version(version: &str) -> Option<Version> {
+    let trimmed = version
+        .trim()
+        .trim_start_matches('=')
+        .trim_start_matches('v');
+    Version::parse(trimmed).ok()
+}
+
+fn set_matches(set: &[Comparator], version: &Version) -> bool {
+    if !set.iter().all(|c| c.matches(version)) {
+        return false;
+    }
+
+    if version.pre.is_empty() {
+        return true;
+    }
+
+    // A prerelease is only allowed if some comparator opts into prereleases
+    // of the same major.minor.patch tuple
+    set.iter().any(|c| {
+        !c.version.pre.is_empty()
+            && c.version.major == version.major
+            && c.version.minor == version.minor
+            && c.version.patch == version.patch
+    })
+}
+
+/// Parse one comparator set (the text between `||`)
+fn parse_set(set: &str) -> Result<Vec<Comparator>, String> {
+    let tokens = tokenize(set)?;
+
+    // Hyphen ranges must make up the whole set: `1.2.3 - 2.3.4`
+    if let [from, dash, to] = tokens.as_slice() {
+        if dash == "-" {
+            return hyphen(&parse_partial(from)?, &parse_partial(to)?);
+        }
+    }
+
+    let mut comparators = Vec::new();
+    for token in &tokens {
+        let (op, partial) = parse_comparator(token)?;
+        comparators.extend(desugar(op, &partial));
+    }
+    Ok(comparators)
+}
+
+/// Split a set into comparator tokens, joining operators to their versions
+/// (`>= 1.2.3` → `>=1.2.3`)
+fn tokenize(set: &str) -> Result<Vec<String>, String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut pending_op: Option<String> = None;
+
+    for word in set.split_whitespace() {
+        let is_operator = word
+            .chars()
+            .all(|c| matches!(c, '<' | '>' | '=' | '~' | '^'));
+        if is_operator {
+            if pending_op.is_some() {
+                return Err(format!("unexpected operator '{word}'"));
+            }
+            pending_op = Some(word.to_string());
+        } else if let Some(op) = pending_op.take() {
+            tokens.push(format!("{op}{word}"));
+        } else {
+            tokens.push(word.to_string());
+        }
+    }
+
+    if let Some(op) = pending_op {
+        return Err(format!("operator '{op}' is missing a version"));
+    }
+    Ok(tokens)
+}
+
+/// Split a token into its operator and partial version
+fn parse_comparator(token: &str) -> Result<(RangeOp, Partial), String> {
+    const OPERATORS: [(&str, RangeOp); 8] = [
+        (">=", RangeOp::Primitive(Op::Gte)),
+        ("<=", RangeOp::Primitive(Op::Lte)),
+        ("~>", RangeOp::Tilde),
+        (">", RangeOp::Primitive(Op::Gt)),
+        ("<", RangeOp::Primitive(Op::Lt)),
+        ("=", RangeOp::Exact),
+        ("~", RangeOp::Tilde),
+        ("^", RangeOp::Caret),
+    ];
+
+    for (prefix, op) in OPERATORS {
+        if let Some(rest) = token.strip_prefix(prefix) {
+            return Ok((op, parse_partial(rest)?));
+        }
+    }
+    Ok((RangeOp::Exact, parse_partial(token)?))
+}
+
+/// Parse a partial version such as `1`, `1.x`, `v1.2.3-beta.1+build`
+fn parse_partial(text: &str) -> Result<Partial, String> {
+    let text = text.trim_start_matches('=').trim_start_matches('v');
+
+    // Build metadata never affects matching
+    let text = text.split_once('+').map_or(text, |(version, _)| version);
+
+    let (core, pre) = match text.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (text, None),
+    };
+
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() > 3 || parts.iter().any(|p| p.is_empty()) {
+        return Err(format!("'{text}' is not a version"));
+    }
+
+    let mut numbers = [None; 3];
+    let mut wildcard = false;
+    for (i, part) in parts.iter().enumerate() {
+        if matches!(*part, "x" | "X" | "*") {
+            wildcard = true;
+        } else if wildcard {
+            // Anything after a wildcard is a wildcard too (`1.x.3` == `1.x`)
+        } else {
+            numbers[i] = Some(parse_number(part)?);
+        }
+    }
+    let [major, minor, patch] = numbers;
+
+    let pre = match pre {
+        Some(_) if patch.is_none() => {
+            return Err(format!("prerelease on incomplete version '{text}'"));
+        }
+        Some(pre) => {
+            Prerelease::new(pre).map_err(|e| format!("invalid prerelease '{pre}': {e}"))?
+        }
+        None => Prerelease::EMPTY,
+    };
+
+    Ok(Partial {
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+/// Parse a numeric identifier (no leading zeros)
+fn parse_number(part: &str) -> Result<u64, String> {
+    if !part.chars().all(|c| c.is_ascii_digit()) || (part.len() > 1 && part.starts_with('0')) {
+        return Err(format!("'{part}' is not a version number"));
+    }
+    part.parse()
+        .map_err(|_| format!("'{part}' is not a version number"))
+}
+
+fn version_pre0(major: u64, minor: u64, patch: u64) -> Version {
+    let mut version = Version::new(major, minor, patch);
+    version.pre = Prerelease::new("0").expect("'0' is a valid prerelease");
+    version
+}
+
+/// Desugar one comparator into primitive comparators
+fn desugar(op: RangeOp, partial: &Partial) -> Vec<Comparator> {
+    let Partial {
+        major,
+        minor,
+        patch,
+        ..
+    } = partial;
+
+    // `*`, `x`, `^x`, `>=*`: any version; `>*`, `<*`: no version
+    let Some(major) = *major else {
+        return match op {
+            RangeOp::Primitive(Op::Gt) | RangeOp::Primitive(Op::Lt) => vec![Comparator::none()],
+            _ => Vec::new(),
+        };
+    };
+
+    match op {
+        RangeOp::Exact => match (minor, patch) {
+            (None, _) => major_range(major),
+            (Some(minor), None) => minor_range(major, *minor),
+            (Some(_), Some(_)) => vec![Comparator::new(Op::Eq, partial.floor())],
+        },
+        RangeOp::Tilde => match (minor, patch) {
+            (None, _) => major_range(major),
+            (Some(minor), None) => minor_range(major, *minor),
+            (Some(minor), Some(_)) => vec![
+                Comparator::new(Op::Gte, partial.floor()),
+                Comparator::new(Op::Lt, version_pre0(major, minor + 1, 0)),
+            ],
+        },
+        RangeOp::Caret => {
+            let upper = match (major, minor, patch) {
+                (0, Some(0), Some(patch)) => version_pre0(0, 0, patch + 1),
+                (0, Some(minor), _) => version_pre0(0, minor + 1, 0),
+                (major, _, _) => version_pre0(major + 1, 0, 0),
+            };
+            vec![
+                Comparator::new(Op::Gte, partial.floor()),
+                Comparator::new(Op::Lt, upper),
+            ]
+        }
+        RangeOp::Primitive(op) => match (minor, patch) {
+            (Some(_), Some(_)) => vec![Comparator::new(op, partial.floor())],
+            // x-range with an operator, e.g. `>1.2` → `>=1.3.0`, `<=1` → `<2.0.0-0`
+            (minor, _) => {
+                let version = match (op, minor) {
+                    (Op::Gt, None) | (Op::Lte, None) => version_pre0(major + 1, 0, 0),
+                    (Op::Gt, Some(minor)) | (Op::Lte, Some(minor)) => {
+                        version_pre0(major, minor + 1, 0)
+                    }
+                    (_, minor) => version_pre0(major, minor.unwrap_or(0), 0),
+                };
+                match op {
+                    Op::Gt | Op::Gte => {
+                        vec![Comparator::new(Op::Gte, strip_pre(version))]
+                    }
+                    Op::Lt | Op::Lte => vec![Comparator::new(Op::Lt, version)],
+                    Op::Eq => unreachable!("'=' is parsed as RangeOp::Exact"),
+                }
+            }
+        },
+    }
+}
+
+fn strip_pre(mut version: Version) -> Version {
+    version.pre = Prerelease::EMPTY;
+    version
+}
+
+/// `1` or `1.x` → `>=1.0.0 <2.0.0-0`
+fn major_range(major: u64) -> Vec<Comparator> {
+    vec![
+        Comparator::new(Op::Gte, Version::new(major, 0, 0)),
+        Comparator::new(Op::Lt, version_pre0(major + 1, 0, 0)),
+    ]
+}
+
+/// `1.2` or `1.2.x` → `>=1.2.0 <1.3.0-0`
+fn minor_range(major: u64, minor: u64) -> Vec<Comparator> {
+    vec![
+        Comparator::new(Op::Gte, Version::new(major, minor, 0)),
+        Comparator::new(Op::Lt, version_pre0(major, minor + 1, 0)),
+    ]
+}
+
+/// `A - B` → `>=A <=B`, with partial bounds widened like x-ranges
+fn hyphen(from: &Partial, to: &Partial) -> Result<Vec<Comparator>, String> {
+    let mut comparators = Vec::new();
+
+    if from.major.is_some() {
+        comparators.push(Comparator::new(Op::Gte, from.floor()));
+    }
+
+    match (to.major, to.minor, to.patch) {
+        (None, _, _) => {}
+        (Some(major), None, _) => {
+            comparators.push(Comparator::new(Op::Lt, version_pre0(major + 1, 0, 0)))
+        }
+        (Some(major), Some(minor), None) => {
+            comparators.push(Comparator::new(Op::Lt, version_pre0(major, minor + 1, 0)))
+        }
+        (Some(_), Some(_), Some(_)) => comparators.push(Comparator::new(Op::Lte, to.floor())),
+    }
+
+    Ok(comparators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfies(range: &str, version: &str) -> bool {
+        VersionRange::parse(range).unwrap().matches_str(version)
+    }
+
+    #[test]
+    fn test_common_engines_values() {
+        assert!(satisfies(">=18 <21", "20.11.0"));
+        assert!(!satisfies(">=18 <21", "21.0.0"));
+        assert!(satisfies("^18 || ^20", "18.20.0"));
+        assert!(satisfies("^18 || ^20", "20.11.0"));
+        assert!(!satisfies("^18 || ^20", "19.9.0"));
+        assert!(satisfies("18.x", "18.20.0"));
+        assert!(!satisfies("18.x", "19.0.0"));
+        assert!(satisfies("16 - 20", "20.11.0"));
+        assert!(!satisfies("16 - 20", "21.0.0"));
+        assert!(satisfies(">=18.0.0-0", "18.0.0"));
+        assert!(satisfies(">=18.0.0-0", "18.0.0-rc.1"));
+    }
+
+    #[test]
+    fn test_desugaring() {
+        let range = VersionRange::parse("^1.2.3").unwrap();
+        assert_eq!(
+            range.sets[0],
+            vec![
+                Comparator::new(Op::Gte, Version::new(1, 2, 3)),
+                Comparator::new(Op::Lt, version_pre0(2, 0, 0)),
+            ]
+        );
+        assert_eq!(
+            VersionRange::parse(">1.2").unwrap().sets[0],
+            vec![Comparator::new(Op::Gte, Version::new(1, 3, 0))]
+        );
+        assert_eq!(VersionRange::parse("*").unwrap().sets[0], vec![]);
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        for range in [
+            "lts/hydrogen",
+            "latest",
+            "node",
+            ">=",
+            "1.2.3.4",
+            "01.2.3",
+            "1.x-beta",
+        ] {
+            assert!(
+                VersionRange::parse(range).is_err(),
+                "{range} should be invalid"
+            );
+        }
+        let err = VersionRange::parse("latest").unwrap_err();
+        assert!(err.to_string().contains("latest"));
+    }
+
+    #[test]
+    fn test_display_and_parse_version() {
+        let range: VersionRange = " >=18 <21 ".parse().unwrap();
+        assert_eq!(range.to_string(), ">=18 <21");
+        assert_eq!(parse_version("v18.20.0"), Some(Version::new(18, 20, 0)));
+        assert_eq!(parse_version("lts/iron"), None);
+    }
+}
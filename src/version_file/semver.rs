@@ -1,20 +1,35 @@
+use super::range::{parse_version, VersionRange};
+use crate::config::RemoteVersionPolicy;
 use crate::plugins::VersionManagerPlugin;
+use crate::release_index::select_release;
 use anyhow::{Context, Result};
-use log::{debug, trace};
-use semver::{Version, VersionReq};
+use log::{debug, trace, warn};
+use semver::Version;
 
 /// Semver range resolver
 ///
-/// Resolves semver ranges (e.g., ">=18.0.0", "^20.0.0") to specific versions
-/// by querying the version manager for installed versions and finding the best match.
+/// Resolves npm-style ranges (e.g., ">=18.0.0", "^20.0.0", "^18 || ^20") to
+/// specific versions by querying the version manager for installed versions and finding the best match.
+/// If nothing installed matches, picks a version to install from the versions
+/// the version manager can install.
 pub struct SemverResolver<'a> {
     version_manager: &'a dyn VersionManagerPlugin,
+    policy: RemoteVersionPolicy,
 }
 
 impl<'a> SemverResolver<'a> {
     /// Create new semver resolver
     pub fn new(version_manager: &'a dyn VersionManagerPlugin) -> Self {
-        Self { version_manager }
+        Self {
+            version_manager,
+            policy: RemoteVersionPolicy::default(),
+        }
+    }
+
+    /// Sets which available version to pick when nothing installed matches
+    pub fn with_policy(mut self, policy: RemoteVersionPolicy) -> Self {
+        self.policy = policy;
+        self
     }
 
     /// Resolve semver range to specific version
@@ -24,6 +39,7 @@ impl<'a> SemverResolver<'a> {
     ///
     /// # Returns
     /// * Specific version if match found (e.g., "18.20.5")
+    /// * Version to install if only an available (not installed) version matches
     /// * Original range if no match or not a valid semver range (pass through to version manager)
     ///
     /// # Examples
@@ -43,7 +59,7 @@ impl<'a> SemverResolver<'a> {
         }
 
         // Try to parse as semver range
-        let version_req = match VersionReq::parse(range) {
+        let version_req = match VersionRange::parse(range) {
             Ok(req) => req,
             Err(e) => {
                 debug!("Not a valid semver range ({e}), passing through: {range}");
@@ -57,21 +73,38 @@ impl<'a> SemverResolver<'a> {
             .get_installed_versions()
             .context("failed to get installed versions from version manager")?;
 
-        if installed.is_empty() {
-            debug!("No versions installed, returning original range");
-            return Ok(range.to_string());
-        }
-
         // Find best match
         if let Some(best_match) = self.find_best_match(&version_req, &installed) {
             debug!("Resolved {range} → {best_match}");
-            Ok(best_match)
-        } else {
-            debug!("No installed version matches {range}, returning original");
-            Ok(range.to_string())
+            return Ok(best_match);
+        }
+
+        debug!("No installed version matches {range}, checking available versions");
+        match self.find_best_available(&version_req) {
+            Some(available) => {
+                debug!("Resolved {range} → {available} (not installed)");
+                Ok(available)
+            }
+            None => {
+                debug!("No available version matches {range}, returning original");
+                Ok(range.to_string())
+            }
         }
     }
 
+    /// Find the available (remote) version to install for a range
+    fn find_best_available(&self, req: &VersionRange) -> Option<String> {
+        let releases = match self.version_manager.list_remote_versions() {
+            Ok(releases) => releases,
+            Err(e) => {
+                warn!("Failed to list available versions: {e}");
+                return None;
+            }
+        };
+
+        select_release(&releases, req, self.policy).map(|release| release.version.to_string())
+    }
+
     /// Get list of installed versions from version manager
     fn get_installed_versions(&self) -> Result<Vec<String>> {
         trace!("Querying version manager for installed versions");
@@ -88,15 +121,13 @@ impl<'a> SemverResolver<'a> {
     /// Find best match for semver range
     ///
     /// Returns the highest version that satisfies the range.
-    fn find_best_match(&self, req: &VersionReq, versions: &[String]) -> Option<String> {
+    fn find_best_match(&self, req: &VersionRange, versions: &[String]) -> Option<String> {
         let mut matching_versions: Vec<(Version, String)> = versions
             .iter()
             .filter_map(|v| {
                 // Try to parse as semver (handle both "18.20.0" and "v18.20.0")
-                let trimmed = v.trim_start_matches('v');
-
-                match Version::parse(trimmed) {
-                    Ok(parsed) => {
+                match parse_version(v) {
+                    Some(parsed) => {
                         if req.matches(&parsed) {
                             trace!("Version {v} matches requirement");
                             Some((parsed, v.clone()))
@@ -105,8 +136,8 @@ impl<'a> SemverResolver<'a> {
                             None
                         }
                     }
-                    Err(e) => {
-                        trace!("Skipping non-semver version {v}: {e}");
+                    None => {
+                        trace!("Skipping non-semver version {v}");
                         None
                     }
                 }
@@ -192,6 +223,29 @@ mod tests {
         assert_eq!(result, ">=18.0.0"); // Returns original range
     }
 
+    #[test]
+    fn test_resolve_uninstalled_from_remote() {
+        use crate::config::RemoteVersionPolicy;
+
+        let mock = create_mock_plugin(vec!["16.0.0"]).with_remote_versions(&[
+            ("18.20.0", Some("Hydrogen")),
+            ("20.11.0", Some("Iron")),
+            ("21.6.0", None),
+        ]);
+
+        let resolver = SemverResolver::new(&mock);
+        assert_eq!(resolver.resolve(">=18.0.0").unwrap(), "20.11.0");
+        // Installed versions still win
+        assert_eq!(resolver.resolve(">=16.0.0").unwrap(), "16.0.0");
+        assert_eq!(resolver.resolve(">=30").unwrap(), ">=30");
+
+        let resolver = SemverResolver::new(&mock).with_policy(RemoteVersionPolicy::Newest);
+        assert_eq!(resolver.resolve(">=18.0.0").unwrap(), "21.6.0");
+
+        let resolver = SemverResolver::new(&mock).with_policy(RemoteVersionPolicy::Lowest);
+        assert_eq!(resolver.resolve(">=18.0.0").unwrap(), "18.20.0");
+    }
+
     #[test]
     fn test_resolve_invalid_semver() {
         let mock = create_mock_plugin(vec!["18.20.0"]);
@@ -230,9 +284,24 @@ mod tests {
         let mock = create_mock_plugin(vec!["16.0.0", "18.20.0", "20.11.0"]);
         let resolver = SemverResolver::new(&mock);
 
-        // Test with simpler OR syntax (semver crate handles comma as OR)
-        let result = resolver.resolve(">=20").unwrap();
-        assert_eq!(result, "20.11.0");
+        // Highest version in either set
+        let result = resolver.resolve("^16 || ^18").unwrap();
+        assert_eq!(result, "18.20.0");
+    }
+
+    #[test]
+    fn test_resolve_npm_ranges() {
+        let mock = create_mock_plugin(vec!["16.20.2", "18.20.0", "20.11.0", "21.7.0-rc.1"]);
+        let resolver = SemverResolver::new(&mock);
+
+        // Space-separated comparators are ANDed
+        assert_eq!(resolver.resolve(">=18 <21").unwrap(), "20.11.0");
+        // Hyphen ranges include the whole upper partial
+        assert_eq!(resolver.resolve("16 - 18").unwrap(), "18.20.0");
+        assert_eq!(resolver.resolve("18.x").unwrap(), "18.20.0");
+        // Prereleases only match when the range opts into that tuple
+        assert_eq!(resolver.resolve(">=20").unwrap(), "20.11.0");
+        assert_eq!(resolver.resolve(">=21.7.0-0").unwrap(), "21.7.0-rc.1");
     }
 
     #[test]
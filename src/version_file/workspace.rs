@@ -0,0 +1,253 @@
+use super::{PackageJson, VersionFile};
+use crate::config::{Config, RulePrecedence, WorkspaceStrategy};
+use anyhow::Result;
+use log::{debug, trace};
+use std::path::{Path, PathBuf};
+
+/// Files that mark the root of a pnpm, lerna or nx workspace
+const WORKSPACE_MARKERS: &[&str] = &["pnpm-workspace.yaml", "lerna.json", "nx.json"];
+
+/// Version files that apply to a directory inside a workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspacePins {
+    /// Nearest version file (what `VersionFile::find` returns)
+    pub nearest: VersionFile,
+
+    /// Version file in the workspace root, if the strategy uses it and it
+    /// differs from `nearest`
+    pub root: Option<VersionFile>,
+}
+
+impl WorkspacePins {
+    /// Finds the version files for `start_dir` according to `strategy`
+    ///
+    /// With `WorkspaceStrategy::Nearest`, workspace roots aren't looked up at all.
+    ///
+    /// # Returns
+    /// * `Ok(None)` - No version file found
+    pub fn find(
+        start_dir: &Path,
+        filenames: &[String],
+        strategy: WorkspaceStrategy,
+    ) -> Result<Option<Self>> {
+        let Some(nearest) = VersionFile::find(start_dir, filenames)? else {
+            return Ok(None);
+        };
+        if strategy == WorkspaceStrategy::Nearest {
+            return Ok(Some(Self {
+                nearest,
+                root: None,
+            }));
+        }
+
+        let start = start_dir.canonicalize()?;
+        let root = match find_workspace_root(&start) {
+            Some(root) => root_version_file(&root, filenames)?,
+            None => None,
+        };
+
+        Ok(Some(Self {
+            root: root.filter(|root| root.path != nearest.path),
+            nearest,
+        }))
+    }
+
+    /// Finds the version files for `start_dir`, consulting the config's path rules
+    ///
+    /// A matching rule's version is used instead of version files when
+    /// `rule_precedence` is `rule`, and otherwise only if no version file is found.
+    pub fn locate(start_dir: &Path, config: &Config) -> Result<Option<Self>> {
+        let start = start_dir.canonicalize()?;
+        let rule = config.rule_for(&start).and_then(VersionFile::from_rule);
+
+        if let Some(rule) = &rule {
+            debug!(
+                "Path rule matches {}: {}",
+                start.display(),
+                rule.path.display()
+            );
+            if config.rule_precedence == RulePrecedence::Rule {
+                return Ok(Some(Self {
+                    nearest: rule.clone(),
+                    root: None,
+                }));
+            }
+        }
+
+        let pins = Self::find(&start, &config.version_files, config.workspace_strategy)?;
+        Ok(pins.or_else(|| {
+            rule.map(|nearest| Self {
+                nearest,
+                root: None,
+            })
+        }))
+    }
+
+    /// The version file to activate: the workspace root's pin if there is one
+    pub fn selected(&self) -> &VersionFile {
+        self.root.as_ref().unwrap_or(&self.nearest)
+    }
+}
+
+/// Highest-priority version file directly in the workspace root
+fn root_version_file(root: &Path, filenames: &[String]) -> Result<Option<VersionFile>> {
+    for filename in filenames {
+        if let Some(version_file) = VersionFile::find_in(root, filename)? {
+            debug!("Workspace root pin: {}", version_file.path.display());
+            return Ok(Some(version_file));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the nearest workspace root containing `start_dir`
+///
+/// A workspace root has a `pnpm-workspace.yaml`, `lerna.json` or `nx.json`,
+/// or a package.json with a `workspaces` field. The search stops at HOME.
+pub fn find_workspace_root(start_dir: &Path) -> Option<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+
+    for dir in start_dir.ancestors() {
+        trace!("Checking for workspace root: {dir:?}");
+        if is_workspace_root(dir) {
+            debug!("Found workspace root: {}", dir.display());
+            return Some(dir.to_path_buf());
+        }
+        if dir == home {
+            break;
+        }
+    }
+    None
+}
+
+/// Whether `dir` declares a workspace
+fn is_workspace_root(dir: &Path) -> bool {
+    if WORKSPACE_MARKERS
+        .iter()
+        .any(|marker| dir.join(marker).is_file())
+    {
+        return true;
+    }
+
+    let package_json = dir.join("package.json");
+    package_json.is_file()
+        && PackageJson::parse(&package_json).is_ok_and(|pkg| pkg.workspaces.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version_file::VersionFileSource;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn files() -> Vec<String> {
+        vec![".nvmrc".to_string(), "package.json".to_string()]
+    }
+
+    #[test]
+    fn test_workspace_markers() {
+        for (file, content) in [
+            ("pnpm-workspace.yaml", "packages:\n  - 'packages/*'\n"),
+            ("lerna.json", "{}"),
+            ("nx.json", "{}"),
+            ("package.json", r#"{"workspaces": ["packages/*"]}"#),
+            (
+                "package.json",
+                r#"{"workspaces": {"packages": ["packages/*"]}}"#,
+            ),
+        ] {
+            let temp = tempdir().unwrap();
+            let package = temp.path().join("packages/api");
+            fs::create_dir_all(&package).unwrap();
+            fs::write(temp.path().join(file), content).unwrap();
+
+            assert_eq!(
+                find_workspace_root(&package).as_deref(),
+                Some(temp.path()),
+                "{file}"
+            );
+        }
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("package.json"), r#"{"name": "app"}"#).unwrap();
+        assert_eq!(find_workspace_root(temp.path()), None);
+    }
+
+    #[test]
+    fn test_locate_with_rules() {
+        let temp = tempdir().unwrap();
+        let legacy = temp.path().canonicalize().unwrap().join("legacy");
+        fs::create_dir_all(legacy.join("app")).unwrap();
+
+        let mut config = Config {
+            rules: vec![crate::config::PathRule {
+                path: format!("{}/**", legacy.display()),
+                version: Some("14".to_string()),
+                plugin: None,
+            }],
+            ..Config::default()
+        };
+
+        let found = WorkspacePins::locate(&legacy.join("app"), &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.selected().version, "14");
+        assert_eq!(found.selected().source, VersionFileSource::Rule);
+
+        // Version files win unless rules take precedence
+        fs::write(legacy.join("app/.nvmrc"), "16\n").unwrap();
+        let found = WorkspacePins::locate(&legacy.join("app"), &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.selected().version, "16");
+
+        config.rule_precedence = RulePrecedence::Rule;
+        let found = WorkspacePins::locate(&legacy.join("app"), &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.selected().version, "14");
+
+        assert!(WorkspacePins::locate(temp.path(), &config)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_workspace_strategies() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        let package = root.join("packages/api");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages: ['packages/*']\n",
+        )
+        .unwrap();
+        fs::write(root.join(".nvmrc"), "20.11.0\n").unwrap();
+        fs::write(
+            package.join("package.json"),
+            r#"{"engines": {"node": ">=18"}}"#,
+        )
+        .unwrap();
+
+        let nearest = WorkspacePins::find(&package, &files(), WorkspaceStrategy::Nearest)
+            .unwrap()
+            .unwrap();
+        assert_eq!(nearest.selected().version, ">=18");
+        assert_eq!(nearest.root, None);
+
+        let pins = WorkspacePins::find(&package, &files(), WorkspaceStrategy::Root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(pins.selected().version, "20.11.0");
+        assert_eq!(pins.nearest.version, ">=18");
+
+        // At the root itself, the nearest file is the root pin
+        let pins = WorkspacePins::find(&root, &files(), WorkspaceStrategy::Root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(pins.selected().version, "20.11.0");
+        assert_eq!(pins.root, None);
+    }
+}
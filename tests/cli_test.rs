@@ -39,6 +39,7 @@ fn test_activate_command() {
     use tempfile::tempdir;
 
     let temp_dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
     let version_file = temp_dir.path().join(".nvmrc");
     fs::write(&version_file, "18.20.0").unwrap();
 
@@ -47,8 +48,14 @@ fn test_activate_command() {
     // - Succeed and prompt to install the version (if a version manager is available)
     // - Fail with "no version manager plugins available" (if no version managers installed)
     // We just verify the command runs and processes the version file correctly.
+    // An isolated HOME keeps the activation out of the real ~/.anvs history
     let mut cmd = Command::cargo_bin("anvs").unwrap();
-    let output = cmd.arg("activate").arg(temp_dir.path()).output().unwrap();
+    let output = cmd
+        .env("HOME", home.path())
+        .arg("activate")
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
 
     // The command should either succeed with install prompt, or fail with helpful error
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -82,3 +89,578 @@ fn test_no_command_shows_help() {
         .success()
         .stdout(predicate::str::contains("Usage:"));
 }
+
+/// Sets up a project, a user config and a fake `fnm` that answers `--version`
+/// but sleeps for `sleep_secs` on every other command
+fn hung_fnm_env(config: &str, sleep_secs: &str) -> (tempfile::TempDir, Command) {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let bin = temp.path().join("bin");
+    let project = temp.path().join("project");
+    fs::create_dir(&bin).unwrap();
+    fs::create_dir(&project).unwrap();
+    fs::write(project.join(".nvmrc"), "18.20.0").unwrap();
+    fs::write(temp.path().join(".anvsrc"), config).unwrap();
+
+    let fnm = bin.join("fnm");
+    fs::write(
+        &fnm,
+        format!("#!/bin/sh\n[ \"$1\" = --version ] && echo 1.0.0 && exit 0\nsleep {sleep_secs}\n"),
+    )
+    .unwrap();
+    fs::set_permissions(&fnm, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut cmd = Command::cargo_bin("anvs").unwrap();
+    cmd.env("HOME", temp.path())
+        .env("PATH", format!("{}:/usr/bin:/bin", bin.display()))
+        .env_remove("ANVS_DAEMON_SOCKET")
+        .arg("activate")
+        .arg(&project);
+    (temp, cmd)
+}
+
+#[test]
+fn test_activate_hung_version_manager_times_out() {
+    let (_temp, mut cmd) = hung_fnm_env("plugins: [fnm]\ncommand_timeout_ms: 300\n", "30");
+
+    let started = std::time::Instant::now();
+    let output = cmd.output().unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!output.status.success());
+    assert!(text.contains("timed out after 300ms"), "got: {text}");
+    assert!(text.contains("command_timeout_ms"), "got: {text}");
+}
+
+#[test]
+fn test_activate_deadline_exits_cleanly() {
+    let (_temp, mut cmd) = hung_fnm_env(
+        "plugins: [fnm]\ncommand_timeout_ms: 5000\nactivation_timeout_ms: 100\n",
+        "0.5",
+    );
+
+    let output = cmd.output().unwrap();
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.status.success(), "got: {text}");
+    assert!(
+        text.contains("activation took longer than 100ms"),
+        "got: {text}"
+    );
+}
+
+#[test]
+fn test_index_update_from_file() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let dist = temp.path().join("index.json");
+    fs::write(
+        &dist,
+        r#"[
+            {"version": "v99.1.0", "date": "2099-01-02", "files": [], "lts": false},
+            {"version": "v98.3.0", "date": "2098-11-01", "files": [], "lts": "Zeta"}
+        ]"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["index", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bundled"));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["index", "update"])
+        .arg(&dist)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved 2 releases"));
+
+    assert!(temp.path().join(".anvs/node-index.json").exists());
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["index", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Latest: 99.1.0"))
+        .stdout(predicate::str::contains("Latest LTS: 98.3.0 (Zeta)"));
+
+    // Invalid sources leave the saved index alone
+    fs::write(&dist, "not json").unwrap();
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["index", "update"])
+        .arg(&dist)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_audit_reports_end_of_life_pins() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().join("home");
+    let root = temp.path().join("code");
+    fs::create_dir_all(&home).unwrap();
+    fs::create_dir_all(root.join("legacy")).unwrap();
+    fs::create_dir_all(root.join("legacy/node_modules/dep")).unwrap();
+    fs::write(root.join("legacy/.nvmrc"), "16.20.2\n").unwrap();
+    fs::write(root.join("legacy/node_modules/dep/.nvmrc"), "0.12\n").unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .args(["audit", "--json"])
+        .arg(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"status\": \"eol\""))
+        .stdout(predicate::str::contains("node_modules").not());
+
+    fs::write(home.join(".anvsrc"), "eol_severity: block\n").unwrap();
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("audit")
+        .arg(&root)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("legacy/.nvmrc"));
+}
+
+#[test]
+fn test_scan_reports_conflicts() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path().join("repo");
+    fs::create_dir_all(root.join("packages/api")).unwrap();
+    fs::create_dir_all(root.join("fixtures")).unwrap();
+    fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+    fs::write(root.join(".nvmrc"), "20.11.0\n").unwrap();
+    fs::write(root.join("packages/api/.nvmrc"), "18.20.0\n").unwrap();
+    fs::write(
+        root.join("packages/api/package.json"),
+        r#"{"engines": {"node": ">=20"}}"#,
+    )
+    .unwrap();
+    fs::write(root.join("fixtures/.nvmrc"), "0.12\n").unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["scan", "--exclude", "fixtures"])
+        .arg(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("packages/api/.nvmrc"))
+        .stdout(predicate::str::contains(
+            ".nvmrc pins 18.20.0 but engines.node requires >=20",
+        ))
+        .stdout(predicate::str::contains(
+            "3 pins, 3 distinct versions, 1 conflicts",
+        ))
+        .stdout(predicate::str::contains("0.12").not());
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["scan", "--json"])
+        .arg(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"conflicts\""))
+        .stdout(predicate::str::contains("\"source\": \"engines\""));
+}
+
+#[test]
+fn test_check_nvmrc_against_engines() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let project = temp.path().join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join(".nvmrc"), "16\n").unwrap();
+    fs::write(
+        project.join("package.json"),
+        r#"{"engines": {"node": ">=18"}}"#,
+    )
+    .unwrap();
+
+    // Diagnostics only, unless strict
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .arg("check")
+        .arg(project.join("src"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            ".nvmrc pins 16 but engines.node requires >=18",
+        ));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["check", "--strict"])
+        .arg(&project)
+        .assert()
+        .failure();
+
+    fs::write(project.join(".nvmrc"), "20\n").unwrap();
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", temp.path())
+        .args(["check", "--strict"])
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All version sources agree"));
+}
+
+#[test]
+fn test_which_reports_path_rule() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let legacy = home.join("work/legacy/app");
+    fs::create_dir_all(&legacy).unwrap();
+    fs::write(
+        home.join(".anvsrc"),
+        "rules:\n  - path: ~/work/legacy/**\n    version: \"14.21.3\"\n    plugin: fnm\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("which")
+        .arg(&legacy)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Rule: ~/work/legacy/** (from {})",
+            home.join(".anvsrc").display()
+        )))
+        .stdout(predicate::str::contains("Version: 14.21.3"))
+        .stdout(predicate::str::contains("Plugin: fnm (from rule)"));
+
+    // Rules from a project's .anvs.yaml name that file
+    let project_config = home.join("work/.anvs.yaml");
+    fs::write(
+        &project_config,
+        "rules:\n  - path: ~/work/legacy/**\n    version: \"16.20.2\"\n",
+    )
+    .unwrap();
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("which")
+        .arg(&legacy)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Rule: ~/work/legacy/** (from {})",
+            project_config.display()
+        )))
+        .stdout(predicate::str::contains("Version: 16.20.2"));
+    fs::remove_file(&project_config).unwrap();
+
+    fs::write(legacy.join(".nvmrc"), "20.11.0\n").unwrap();
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("which")
+        .arg(&legacy)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Version file:"))
+        .stdout(predicate::str::contains(
+            "Rule ~/work/legacy/** also matches, but the version file takes precedence",
+        ));
+}
+
+#[test]
+fn test_trust_project_hooks() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let project = home.join("work/app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join(".anvs.yaml"),
+        "hooks:\n  post_activate:\n    - npm rebuild\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("trust")
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("post_activate: npm rebuild"))
+        .stdout(predicate::str::contains("Trusted"));
+
+    let trusted = fs::read_to_string(home.join(".anvs/trusted.json")).unwrap();
+    assert!(trusted.contains("npm rebuild"));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("trust")
+        .arg("--revoke")
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Revoked trust"));
+}
+
+#[test]
+fn test_shims() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let write_script = |path: std::path::PathBuf, output: &str| {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("#!/bin/sh\necho \"{output} $*\"\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    fs::create_dir_all(home.join(".nvm")).unwrap();
+    fs::write(home.join(".nvm/nvm.sh"), "").unwrap();
+    write_script(home.join(".nvm/versions/node/v18.20.0/bin/node"), "node 18");
+    write_script(home.join("bin/npx"), "system npx");
+    fs::write(home.join(".anvsrc"), "plugins: [nvm]\n").unwrap();
+    let project = home.join("app");
+    let missing = home.join("new-app");
+    let elsewhere = home.join("elsewhere");
+    for (dir, nvmrc) in [
+        (&project, Some("18.20.0")),
+        (&missing, Some("22.0.0")),
+        (&elsewhere, None),
+    ] {
+        fs::create_dir_all(dir).unwrap();
+        if let Some(version) = nvmrc {
+            fs::write(dir.join(".nvmrc"), version).unwrap();
+        }
+    }
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("shims")
+        .arg("install")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed 4 shims"));
+
+    let shim = |name: &str, dir: &std::path::Path| {
+        let mut command = Command::new(home.join(".anvs/shims").join(name));
+        command
+            .current_dir(dir)
+            .env("HOME", &home)
+            .env("NVM_DIR", home.join(".nvm"))
+            .env(
+                "PATH",
+                format!("{}:/usr/bin:/bin", home.join("bin").display()),
+            );
+        command
+    };
+
+    // The second run is served from the cache
+    for _ in 0..2 {
+        shim("node", &project)
+            .arg("--version")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("node 18 --version"));
+    }
+    assert!(home.join(".anvs/cache/shims.json").exists());
+
+    // Outside projects, without a default version, the next one on PATH runs
+    shim("npx", &elsewhere)
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("system npx cowsay"));
+
+    shim("node", &missing)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Node.js 22.0.0"));
+}
+
+#[test]
+fn test_prompt_reset() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let project = home.join("work/app");
+    fs::create_dir_all(home.join(".anvs")).unwrap();
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        home.join(".anvs/prompts.json"),
+        format!(
+            r#"{{"{}": {{"choice": "never"}}, "/elsewhere": {{"choice": "always"}}}}"#,
+            project.display()
+        ),
+    )
+    .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("prompt")
+        .arg("reset")
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Forgot install answers for 1 project",
+        ));
+
+    let state = fs::read_to_string(home.join(".anvs/prompts.json")).unwrap();
+    assert!(!state.contains("work/app"));
+    assert!(state.contains("/elsewhere"));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("prompt")
+        .arg("reset")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 project"));
+}
+
+#[test]
+fn test_log_records_activations() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let project = home.join("work/app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join(".nvmrc"), "18.20.0\n").unwrap();
+    fs::write(home.join(".anvsrc"), "auto_install: never\n").unwrap();
+
+    // Whether or not a version manager is found, the decision is recorded
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .env("ANVS_DAEMON_SOCKET", home.join("no-daemon.sock"))
+        .arg("activate")
+        .arg(&project)
+        .output()
+        .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("log")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"requirement\":\"18.20.0\""))
+        .stdout(predicate::str::contains(project.display().to_string()));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("log")
+        .arg("--since")
+        .arg("2000-01-01T00:00:00Z")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~/work/app/.nvmrc"));
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .arg("log")
+        .arg("--since")
+        .arg("soon")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_activate_dry_run() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let project = home.join("work/app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join(".nvmrc"), "18.20.0\n").unwrap();
+    fs::write(home.join(".anvsrc"), "auto_install: prompt\n").unwrap();
+
+    // Never prompts (stdin is closed) and never records history
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .env("ANVS_DAEMON_SOCKET", home.join("no-daemon.sock"))
+        .arg("activate")
+        .arg("--dry-run")
+        .arg(&project)
+        .write_stdin("")
+        .assert()
+        .stdout(predicate::str::contains("Dry run:"));
+
+    assert!(!home.join(".anvs/activations.jsonl").exists());
+}
+
+#[test]
+fn test_activate_json_protocol() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let home = temp.path().canonicalize().unwrap();
+    let project = home.join("work/app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        home.join(".anvsrc"),
+        "hooks:\n  on_leave:\n    - echo bye\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("anvs")
+        .unwrap()
+        .env("HOME", &home)
+        .env("ANVS_DAEMON_SOCKET", home.join("no-daemon.sock"))
+        .arg("activate")
+        .arg("--protocol")
+        .arg("json")
+        .arg("--use-default")
+        .arg(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "__ANVS_COMMANDS_START__\n{\"commands\":[\"echo bye\"]}\n__ANVS_COMMANDS_END__",
+        ));
+}
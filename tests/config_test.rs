@@ -65,6 +65,7 @@ fn test_config_merge_precedence() {
         auto_install: AutoInstallMode::Never,
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     let override_config = Config {
@@ -73,6 +74,7 @@ fn test_config_merge_precedence() {
         auto_install: AutoInstallMode::Always,
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     // Test that override values would take precedence
@@ -189,6 +191,7 @@ fn test_config_validation() {
         auto_install: AutoInstallMode::Prompt,
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
     assert!(invalid_config.validate().is_err());
 
@@ -199,6 +202,7 @@ fn test_config_validation() {
         auto_install: AutoInstallMode::Prompt,
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
     assert!(invalid_config.validate().is_err());
 
@@ -239,6 +243,7 @@ fn test_config_serde_round_trip() {
         auto_install: AutoInstallMode::Always,
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     // Serialize to YAML
@@ -192,6 +192,7 @@ mod auto_install_flows {
             use_default: true,
             version_files: vec![".nvmrc".to_string()],
             default_version: None,
+            ..Config::default()
         };
         assert_eq!(always.auto_install, AutoInstallMode::Always);
 
@@ -201,6 +202,7 @@ mod auto_install_flows {
             use_default: true,
             version_files: vec![".nvmrc".to_string()],
             default_version: None,
+            ..Config::default()
         };
         assert_eq!(never.auto_install, AutoInstallMode::Never);
 
@@ -210,6 +212,7 @@ mod auto_install_flows {
             use_default: true,
             version_files: vec![".nvmrc".to_string()],
             default_version: None,
+            ..Config::default()
         };
         assert_eq!(prompt.auto_install, AutoInstallMode::Prompt);
     }
@@ -14,8 +14,8 @@ source "$PROJECT_ROOT/shell/anvs.sh"
 
 # Test 1: Functions are defined
 echo " Test 1: Checking function definitions..."
-if ! declare -f __anvs_find_file > /dev/null; then
-    echo " Function __anvs_find_file not defined"
+if ! declare -f __anvs_hook_check > /dev/null; then
+    echo " Function __anvs_hook_check not defined"
     exit 1
 fi
 
@@ -39,25 +39,40 @@ if [[ -z "${ANVS_SHELL_LOADED:-}" ]]; then
 fi
 echo " ANVS_SHELL_LOADED is set"
 
-# Test 3: Find file function
-echo " Test 3: Testing __anvs_find_file..."
+# Test 3: Hook check function
+echo " Test 3: Testing __anvs_hook_check..."
 
-# Create temp directory with .nvmrc
-TEMP_DIR=$(mktemp -d)
-trap "rm -rf $TEMP_DIR" EXIT
+ANVS_BIN="$PROJECT_ROOT/target/debug/anvs"
+if [[ -x "$ANVS_BIN" ]]; then
+    # Create temp directory with .nvmrc
+    TEMP_DIR=$(mktemp -d)
+    trap "rm -rf $TEMP_DIR" EXIT
 
-mkdir -p "$TEMP_DIR/project/subdir"
-echo "18.20.0" > "$TEMP_DIR/project/.nvmrc"
+    mkdir -p "$TEMP_DIR/project/subdir"
+    echo "18.20.0" > "$TEMP_DIR/project/.nvmrc"
 
-# Call __anvs_find_file directly without cd (to avoid triggering __anvs_chpwd)
-result=$(__anvs_find_file "$TEMP_DIR/project/subdir" || echo "")
+    # Use the freshly built binary with an isolated HOME (no user config)
+    anvs() { HOME="$TEMP_DIR" "$ANVS_BIN" "$@"; }
 
-if [[ "$result" != "$TEMP_DIR/project/.nvmrc" ]]; then
-    echo " Expected to find $TEMP_DIR/project/.nvmrc, got: $result"
-    exit 1
-fi
+    # Call __anvs_hook_check directly without cd (to avoid triggering __anvs_chpwd)
+    result=$(ANVS_ACTIVE_KEY="" __anvs_hook_check "$TEMP_DIR/project/subdir" || echo "")
+
+    if [[ "$result" != *"/project/.nvmrc:18.20.0" ]]; then
+        echo " Expected a key for $TEMP_DIR/project/.nvmrc, got: $result"
+        exit 1
+    fi
 
-echo " __anvs_find_file works correctly"
+    result=$(ANVS_ACTIVE_KEY="$result" __anvs_hook_check "$TEMP_DIR/project/subdir" || echo "")
+    if [[ "$result" != "unchanged" ]]; then
+        echo " Expected 'unchanged' for the active key, got: $result"
+        exit 1
+    fi
+
+    unset -f anvs
+    echo " __anvs_hook_check works correctly"
+else
+    echo " Skipping: $ANVS_BIN not built"
+fi
 
 # Test 4: Debug function
 echo " Test 4: Testing __anvs_debug..."
@@ -38,6 +38,21 @@ fn test_shell_script_execution() {
     );
 }
 
+#[test]
+fn test_anvs_sh_pins_fd3_protocol() {
+    // The protocol must not depend on variables inherited from pwsh or nu
+    let script = std::fs::read_to_string("shell/anvs.sh").unwrap();
+    let calls: Vec<&str> = script
+        .lines()
+        .filter(|line| line.contains("$(anvs activate"))
+        .collect();
+
+    assert_eq!(calls.len(), 2);
+    for call in calls {
+        assert!(call.contains("--protocol fd3"), "unpinned: {call}");
+    }
+}
+
 #[test]
 fn test_profile_detection_bash() {
     use anvs::setup::Shell;
@@ -37,6 +37,7 @@ fn test_detection_results_to_config() {
         version_files: vec![".nvmrc".to_string(), ".node-version".to_string()],
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     assert_eq!(config.plugins, vec!["nvm".to_string()]);
@@ -52,6 +53,7 @@ fn test_config_validation() {
         version_files: vec![".nvmrc".to_string()],
         use_default: true,
         default_version: None,
+        ..Config::default()
     };
 
     // Should not panic when used